@@ -0,0 +1,370 @@
+//! Blocking Collection, Disbursements and Remittance Products
+//!
+//! A synchronous facade over `MomoCollection`, `MomoDisbursements` and `MomoRemittance` for
+//! scripts that do not want to set up an async runtime. Every call is driven to completion on a
+//! runtime owned by the product.
+//!
+//! The methods block the current thread and must not be called from within an existing async
+//! runtime (for example inside `#[tokio::main]`), tokio panics when a runtime is blocked on from
+//! inside another one. Async code should use the async products directly.
+//!
+//! Requires the `blocking` feature.
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{
+    Balance, Environment, MomoCollection, MomoDisbursements, MomoRemittance, RequestToPay,
+    RequestToPayResult, Submitted, TransactionId, TranserId, TransferRequest, TransferResult,
+};
+
+/// # Collection
+/// Blocking version of the Collection product.
+pub struct Collection {
+    inner: MomoCollection,
+    runtime: Runtime,
+}
+
+impl Collection {
+    /// Create a new instance of the blocking Collection
+    ///
+    /// # Parameters
+    ///
+    /// * 'url', MTN MOMO collection url
+    /// * 'environment', environement to be used, default = Sandbox
+    /// * 'api_user', the api user to be used
+    /// * 'api_key', the api key to be use
+    /// * 'primary_key', the primary key of the collection product
+    /// * 'secondary_key', the secondary key of the collection product
+    ///
+    /// # Returns
+    /// * Collection
+    pub fn new(
+        url: String,
+        environment: Environment,
+        api_user: String,
+        api_key: String,
+        primary_key: String,
+        secondary_key: String,
+    ) -> Result<Collection, Box<dyn std::error::Error>> {
+        let inner = MomoCollection::new(
+            url,
+            environment,
+            api_user,
+            api_key,
+            primary_key,
+            secondary_key,
+        );
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        Ok(Collection { inner, runtime })
+    }
+
+    /// This operation is used to request a payment from a consumer (Payer).
+    /// See `MomoCollection::request_to_pay`.
+    ///
+    /// # Parameters
+    ///
+    /// * 'request': RequestToPay
+    /// * 'callback_url', the callback url to send updates to
+    ///
+    /// # Returns
+    ///
+    /// * 'Submitted<TransactionId>' (external_id), the transaction id of the payment.
+    pub fn request_to_pay(
+        &self,
+        request: RequestToPay,
+        callback_url: Option<&str>,
+    ) -> Result<Submitted<TransactionId>, Box<dyn std::error::Error>> {
+        self.runtime
+            .block_on(self.inner.request_to_pay(request, callback_url))
+    }
+
+    /// this operation is used to get the status of a request to pay.
+    ///
+    /// # Parameters
+    ///
+    /// * 'payment_id', the payment id that we are trying to get the status
+    ///
+    /// # Returns
+    ///
+    /// *  'RequestToPayResult', the is the result of the request to pay
+    pub fn request_to_pay_transaction_status(
+        &self,
+        payment_id: &str,
+    ) -> Result<RequestToPayResult, Box<dyn std::error::Error>> {
+        self.runtime
+            .block_on(self.inner.request_to_pay_transaction_status(payment_id))
+    }
+
+    /// This operation is used to get the balance of the account.
+    /// # Returns
+    ///
+    /// * 'Balance', the balance
+    pub fn get_account_balance(&self) -> Result<Balance, Box<dyn std::error::Error>> {
+        self.runtime.block_on(self.inner.get_account_balance())
+    }
+}
+
+/// # Disbursements
+/// Blocking version of the Disbursements product.
+pub struct Disbursements {
+    inner: MomoDisbursements,
+    runtime: Runtime,
+}
+
+impl Disbursements {
+    /// Create a new instance of the blocking Disbursements
+    ///
+    /// # Parameters
+    ///
+    /// * 'url', MTN MOMO disbursements url
+    /// * 'environment', environement to be used, default = Sandbox
+    /// * 'api_user', the api user to be used
+    /// * 'api_key', the api key to be use
+    /// * 'primary_key', the primary key of the disbursements product
+    /// * 'secondary_key', the secondary key of the disbursements product
+    ///
+    /// # Returns
+    /// * Disbursements
+    pub fn new(
+        url: String,
+        environment: Environment,
+        api_user: String,
+        api_key: String,
+        primary_key: String,
+        secondary_key: String,
+    ) -> Result<Disbursements, Box<dyn std::error::Error>> {
+        let inner = MomoDisbursements::new(
+            url,
+            environment,
+            api_user,
+            api_key,
+            primary_key,
+            secondary_key,
+        );
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        Ok(Disbursements { inner, runtime })
+    }
+
+    /// Transfer an amount from the account to a payee.
+    /// See `MomoDisbursements::transfer`.
+    ///
+    /// # Parameters
+    ///
+    /// * 'transfer', the transfer to make
+    /// * 'callback_url', the callback url to send updates to
+    ///
+    /// # Returns
+    ///
+    /// * 'Submitted<TranserId>', the id of the transfer
+    pub fn transfer(
+        &self,
+        transfer: TransferRequest,
+        callback_url: Option<&str>,
+    ) -> Result<Submitted<TranserId>, Box<dyn std::error::Error>> {
+        self.runtime
+            .block_on(self.inner.transfer(transfer, callback_url))
+    }
+
+    /// Get the status of a transfer.
+    ///
+    /// # Parameters
+    ///
+    /// * 'transfer_id', the id of the transfer
+    ///
+    /// # Returns
+    ///
+    /// * 'TransferResult'
+    pub fn get_transfer_status(
+        &self,
+        transfer_id: &str,
+    ) -> Result<TransferResult, Box<dyn std::error::Error>> {
+        self.runtime
+            .block_on(self.inner.get_transfer_status(transfer_id))
+    }
+
+    /// This operation is used to get the balance of the account.
+    /// # Returns
+    ///
+    /// * 'Balance', the balance
+    pub fn get_account_balance(&self) -> Result<Balance, Box<dyn std::error::Error>> {
+        self.runtime.block_on(self.inner.get_account_balance())
+    }
+}
+
+/// # Remittance
+/// Blocking version of the Remittance product.
+pub struct Remittance {
+    inner: MomoRemittance,
+    runtime: Runtime,
+}
+
+impl Remittance {
+    /// Create a new instance of the blocking Remittance
+    ///
+    /// # Parameters
+    ///
+    /// * 'url', MTN MOMO remittance url
+    /// * 'environment', environement to be used, default = Sandbox
+    /// * 'api_user', the api user to be used
+    /// * 'api_key', the api key to be use
+    /// * 'primary_key', the primary key of the remittance product
+    /// * 'secondary_key', the secondary key of the remittance product
+    ///
+    /// # Returns
+    /// * Remittance
+    pub fn new(
+        url: String,
+        environment: Environment,
+        api_user: String,
+        api_key: String,
+        primary_key: String,
+        secondary_key: String,
+    ) -> Result<Remittance, Box<dyn std::error::Error>> {
+        let inner = MomoRemittance::new(
+            url,
+            environment,
+            api_user,
+            api_key,
+            primary_key,
+            secondary_key,
+        );
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        Ok(Remittance { inner, runtime })
+    }
+
+    /// Transfer an amount from the account to a payee.
+    /// See `MomoRemittance::transfer`.
+    ///
+    /// # Parameters
+    ///
+    /// * 'transfer', the transfer to make
+    ///
+    /// # Returns
+    ///
+    /// * 'Submitted<TranserId>', the id of the transfer
+    pub fn transfer(
+        &self,
+        transfer: TransferRequest,
+    ) -> Result<Submitted<TranserId>, Box<dyn std::error::Error>> {
+        self.runtime.block_on(self.inner.transfer(transfer))
+    }
+
+    /// Get the status of a transfer.
+    ///
+    /// # Parameters
+    ///
+    /// * 'transfer_id', the id of the transfer
+    ///
+    /// # Returns
+    ///
+    /// * 'TransferResult'
+    pub fn get_transfer_status(
+        &self,
+        transfer_id: &str,
+    ) -> Result<TransferResult, Box<dyn std::error::Error>> {
+        self.runtime
+            .block_on(self.inner.get_transfer_status(transfer_id))
+    }
+
+    /// This operation is used to get the balance of the account.
+    /// # Returns
+    ///
+    /// * 'Balance', the balance
+    pub fn get_account_balance(&self) -> Result<Balance, Box<dyn std::error::Error>> {
+        self.runtime.block_on(self.inner.get_account_balance())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::products::test_server::{short_lived_token_routes, spawn_mock};
+    use crate::Currency;
+    use poem::{get, handler};
+
+    #[handler]
+    fn mock_account_balance() -> &'static str {
+        r#"{"availableBalance":"1000","currency":"EUR"}"#
+    }
+
+    /// Serve the token and balance endpoints of every product, returns the url
+    fn mock_server() -> String {
+        // the tests are synchronous, the server runs on a runtime of its own
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let url = {
+            let _runtime = runtime.enter();
+            // the tokens expire at once: the access token is cached per product for the whole
+            // process, a token left valid would be used by the tests expecting MTN to refuse one
+            spawn_mock(
+                short_lived_token_routes()
+                    .at("/:product/v1_0/account/balance", get(mock_account_balance)),
+            )
+        };
+        std::thread::spawn(move || runtime.block_on(std::future::pending::<()>()));
+        url
+    }
+
+    fn credentials() -> (Environment, String, String, String, String) {
+        (
+            Environment::Sandbox,
+            "mock_api_user".to_string(),
+            "mock_api_key".to_string(),
+            "mock_primary_key".to_string(),
+            "mock_secondary_key".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_get_account_balance() {
+        let (environment, api_user, api_key, primary_key, secondary_key) = credentials();
+        let collection = Collection::new(
+            mock_server(),
+            environment,
+            api_user,
+            api_key,
+            primary_key,
+            secondary_key,
+        )
+        .unwrap();
+        let balance = collection
+            .get_account_balance()
+            .expect("Error getting account balance");
+        assert_eq!(balance.available_balance, "1000");
+        assert_eq!(balance.currency, Currency::EUR);
+    }
+
+    #[test]
+    fn test_disbursements_and_remittance_balance() {
+        let url = mock_server();
+        let (environment, api_user, api_key, primary_key, secondary_key) = credentials();
+        let disbursements = Disbursements::new(
+            url.clone(),
+            environment,
+            api_user.clone(),
+            api_key.clone(),
+            primary_key.clone(),
+            secondary_key.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            disbursements
+                .get_account_balance()
+                .unwrap()
+                .available_balance,
+            "1000"
+        );
+        let remittance = Remittance::new(
+            url,
+            environment,
+            api_user,
+            api_key,
+            primary_key,
+            secondary_key,
+        )
+        .unwrap();
+        assert_eq!(
+            remittance.get_account_balance().unwrap().available_balance,
+            "1000"
+        );
+    }
+}
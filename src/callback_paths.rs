@@ -0,0 +1,38 @@
+//! The paths the callback server receives each kind of callback on
+//!
+//! `CallbackType::route_path` gives the path of a kind of callback, the callback server
+//! registers its routes from it and the products build their X-Callback-Url from it, so that they
+//! can't drift apart. A callback is received on the path followed by `/<callback type>`, see
+//! `CallbackType::path`.
+//!
+//! The disbursement paths keep the spelling the server always used, so that the callback urls
+//! already given to MTN keep working.
+
+pub const COLLECTION_REQUEST_TO_PAY: &str = "/collection_request_to_pay";
+pub const COLLECTION_REQUEST_TO_WITHDRAW_V1: &str = "/collection_request_to_withdraw_v1";
+pub const COLLECTION_REQUEST_TO_WITHDRAW_V2: &str = "/collection_request_to_withdraw_v2";
+pub const COLLECTION_INVOICE: &str = "/collection_invoice";
+pub const COLLECTION_PAYMENT: &str = "/collection_payment";
+pub const COLLECTION_PRE_APPROVAL: &str = "/collection_preapproval";
+pub const DISBURSEMENT_DEPOSIT_V1: &str = "/disbursement_deposit_V1";
+pub const DISBURSEMENT_DEPOSIT_V2: &str = "/disbursement_deposit_v2";
+pub const DISBURSEMENT_REFUND_V1: &str = "/disburseemnt_refund_v1";
+pub const DISBURSEMENT_REFUND_V2: &str = "/disburseemnt_refund_v2";
+pub const DISBURSEMENT_TRANSFER: &str = "/disburseemnt_transfer";
+pub const REMITTANCE_CASH_TRANSFER: &str = "/remittance_cash_transfer";
+pub const REMITTANCE_TRANSFER: &str = "/remittance_transfer";
+
+#[cfg(test)]
+mod tests {
+    use crate::CallbackType;
+
+    #[test]
+    fn test_every_callback_type_has_its_own_path() {
+        let mut paths: Vec<String> = CallbackType::ALL.iter().map(CallbackType::path).collect();
+        assert!(paths.iter().all(|path| path.starts_with('/')));
+        paths.sort();
+        paths.dedup();
+        assert_eq!(paths.len(), CallbackType::ALL.len());
+        assert_eq!(CallbackType::None.route_path(), "");
+    }
+}
@@ -0,0 +1,150 @@
+//! The stream of callbacks of a running callback server
+//!
+//! `MomoCallbackListener::serve_with_config` and the other `serve` functions spawn the server
+//! and return a `CallbackStream`. The server runs as long as the stream is kept: dropping the
+//! stream stops it at once, and `CallbackStream::close` stops it gracefully and hands back the
+//! callbacks still waiting in the channel.
+//!
+//! `MomoCallbackListener::serve_broadcast` returns a `CallbackBroadcast` instead, which keeps
+//! the server running the same way.
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use tokio::{
+    sync::{broadcast, mpsc::Receiver},
+    task::JoinHandle,
+};
+
+use crate::{MomoUpdates, ShutdownHandle};
+
+/// # CallbackStream
+/// The callbacks received by a callback server, which stops when the stream is dropped
+pub struct CallbackStream {
+    updates: Receiver<MomoUpdates>,
+    stop: ShutdownHandle,
+    server: Option<JoinHandle<()>>,
+}
+
+impl CallbackStream {
+    /// The stream of the server spawned as `server`, which stops when `stop` is shut down
+    pub(crate) fn new(
+        updates: Receiver<MomoUpdates>,
+        stop: ShutdownHandle,
+        server: JoinHandle<()>,
+    ) -> CallbackStream {
+        CallbackStream {
+            updates,
+            stop,
+            server: Some(server),
+        }
+    }
+
+    /// Stop the server gracefully, like `CallbackServerConfig::shutdown` does, and close the
+    /// channel
+    ///
+    /// # Returns
+    ///
+    /// * 'Vec<MomoUpdates>', the callbacks received but not read from the stream yet, oldest
+    ///   first
+    pub async fn close(mut self) -> Vec<MomoUpdates> {
+        self.stop.shutdown();
+        let mut pending = vec![];
+        if let Some(mut server) = self.server.take() {
+            // the requests still being served wait for room in the channel, keep reading it
+            // until the server is done with them
+            loop {
+                tokio::select! {
+                    result = &mut server => {
+                        if let Err(err) = result {
+                            tracing::error!("the callback server task failed: {}", err);
+                        }
+                        break;
+                    }
+                    Some(update) = self.updates.recv() => pending.push(update),
+                }
+            }
+        }
+        self.updates.close();
+        while let Ok(update) = self.updates.try_recv() {
+            pending.push(update);
+        }
+        pending
+    }
+}
+
+impl Stream for CallbackStream {
+    type Item = MomoUpdates;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<MomoUpdates>> {
+        self.updates.poll_recv(cx)
+    }
+}
+
+impl Drop for CallbackStream {
+    fn drop(&mut self) {
+        self.stop.shutdown();
+        if let Some(server) = self.server.take() {
+            server.abort();
+        }
+    }
+}
+
+/// # CallbackBroadcast
+/// The callbacks received by a callback server, handed to every subscriber, the server stops
+/// when the broadcast is dropped
+pub struct CallbackBroadcast {
+    sender: broadcast::Sender<MomoUpdates>,
+    stop: ShutdownHandle,
+    forward: Option<JoinHandle<()>>,
+}
+
+impl CallbackBroadcast {
+    /// Forward the callbacks of the stream to a broadcast channel keeping 'capacity' callbacks,
+    /// which must be checked by the caller
+    pub(crate) fn new(updates: CallbackStream, capacity: usize) -> CallbackBroadcast {
+        let (sender, _) = broadcast::channel(capacity);
+        let stop = updates.stop.clone();
+        let forward = sender.clone();
+        let forward = tokio::spawn(async move {
+            let mut updates = updates;
+            while let Some(update) = futures_util::StreamExt::next(&mut updates).await {
+                // an error only means nobody is subscribed
+                let _ = forward.send(update);
+            }
+        });
+        CallbackBroadcast {
+            sender,
+            stop,
+            forward: Some(forward),
+        }
+    }
+
+    /// A new subscriber, getting the callbacks received from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<MomoUpdates> {
+        self.sender.subscribe()
+    }
+
+    /// Stop the server gracefully, like `CallbackServerConfig::shutdown` does, the subscribers
+    /// get the callbacks already received, then `RecvError::Closed`
+    pub async fn close(mut self) {
+        self.stop.shutdown();
+        if let Some(forward) = self.forward.take() {
+            if let Err(err) = forward.await {
+                tracing::error!("the callback forwarding task failed: {}", err);
+            }
+        }
+    }
+}
+
+impl Drop for CallbackBroadcast {
+    fn drop(&mut self) {
+        self.stop.shutdown();
+        if let Some(forward) = self.forward.take() {
+            forward.abort();
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Callbacks that could not be handed over to the stream
+//!
+//! A callback is lost when it is still being served after `CallbackStream::close` closed the
+//! channel, or when the channel is full and `CallbackServerConfig::drop_when_full` is set. A
+//! `DeadLetterSink` set in `CallbackServerConfig::dead_letter` gets these callbacks instead, to
+//! replay them later with `replay_callbacks`. Dropping the stream does not hand anything over:
+//! it aborts the server, and the requests it was still serving are cut off without an answer.
+//!
+//! With the `dead-letter-file` feature, `FileDeadLetterSink` appends them to a file.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::{CallbackType, MomoUpdates};
+
+/// Why a callback could not be handed over to the stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeadLetterReason {
+    /// the channel was full and `drop_when_full` is set
+    ChannelFull,
+    /// the stream of callbacks was closed
+    ChannelClosed,
+}
+
+impl fmt::Display for DeadLetterReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DeadLetterReason::ChannelFull => write!(f, "the callback channel is full"),
+            DeadLetterReason::ChannelClosed => write!(f, "the callback stream was closed"),
+        }
+    }
+}
+
+/// # DeadLetter
+/// A callback that could not be handed over to the stream, as kept by `FileDeadLetterSink`
+///
+/// - 'reason', why it could not be handed over
+/// - 'update_type', the kind of callback, from the path it was sent to
+/// - 'remote_address', the address the callback came from
+/// - 'raw_body', the body of the callback, as received
+/// - 'dropped_at', when it could not be handed over
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub reason: DeadLetterReason,
+    pub update_type: CallbackType,
+    pub remote_address: String,
+    pub raw_body: String,
+    pub dropped_at: DateTime<Utc>,
+}
+
+impl DeadLetter {
+    /// The dead letter of a callback, dropped now
+    pub fn new(update: &MomoUpdates, reason: DeadLetterReason) -> DeadLetter {
+        DeadLetter {
+            reason,
+            update_type: update.update_type,
+            remote_address: update.remote_address.clone(),
+            raw_body: update.raw_body.clone(),
+            dropped_at: Utc::now(),
+        }
+    }
+}
+
+/// # DeadLetterSink
+/// Keeps the callbacks that could not be handed over to the stream, e.g. in a file or a queue
+pub trait DeadLetterSink: Send + Sync {
+    /// Keep a callback that could not be handed over to the stream
+    ///
+    /// # Returns
+    ///
+    /// * 'Result<(), Box<dyn Error>>', why the callback could not be kept, it is logged and the
+    ///   callback is lost
+    fn dead_letter<'a>(
+        &'a self,
+        update: &'a MomoUpdates,
+        reason: DeadLetterReason,
+    ) -> BoxFuture<'a, Result<(), Box<dyn std::error::Error + Send + Sync>>>;
+}
+
+/// # FileDeadLetterSink
+/// A `DeadLetterSink` appending every callback to a file, one JSON `DeadLetter` per line
+///
+/// Requires the `dead-letter-file` feature.
+#[cfg(feature = "dead-letter-file")]
+#[derive(Debug, Clone)]
+pub struct FileDeadLetterSink {
+    path: std::path::PathBuf,
+    lock: std::sync::Arc<tokio::sync::Mutex<()>>,
+}
+
+#[cfg(feature = "dead-letter-file")]
+impl FileDeadLetterSink {
+    /// Append the dead letters to the given file, created when it does not exist
+    pub fn new(path: impl Into<std::path::PathBuf>) -> FileDeadLetterSink {
+        FileDeadLetterSink {
+            path: path.into(),
+            lock: Default::default(),
+        }
+    }
+
+    /// The dead letters kept so far, oldest first, to replay them
+    ///
+    /// # Returns
+    ///
+    /// * 'Vec<DeadLetter>', none when the file does not exist, or an error when it can't be read
+    ///   or a line is not a dead letter
+    pub async fn dead_letters(&self) -> Result<Vec<DeadLetter>, Box<dyn std::error::Error>> {
+        let _guard = self.lock.lock().await;
+        let content = match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(Box::new(err)),
+        };
+        let mut dead_letters = vec![];
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            dead_letters.push(serde_json::from_str(line)?);
+        }
+        Ok(dead_letters)
+    }
+}
+
+#[cfg(feature = "dead-letter-file")]
+impl DeadLetterSink for FileDeadLetterSink {
+    fn dead_letter<'a>(
+        &'a self,
+        update: &'a MomoUpdates,
+        reason: DeadLetterReason,
+    ) -> BoxFuture<'a, Result<(), Box<dyn std::error::Error + Send + Sync>>> {
+        Box::pin(async move {
+            use tokio::io::AsyncWriteExt;
+
+            let mut line = serde_json::to_string(&DeadLetter::new(update, reason))?;
+            line.push('\n');
+            let _guard = self.lock.lock().await;
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await?;
+            file.write_all(line.as_bytes()).await?;
+            file.flush().await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, feature = "dead-letter-file"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_dead_letter_sink() {
+        let path =
+            std::env::temp_dir().join(format!("dead_letters_{}.jsonl", uuid::Uuid::new_v4()));
+        let sink = FileDeadLetterSink::new(&path);
+        assert!(sink.dead_letters().await.unwrap().is_empty());
+
+        for (raw_body, reason) in [
+            (r#"{"externalId":"first"}"#, DeadLetterReason::ChannelFull),
+            (
+                r#"{"externalId":"second"}"#,
+                DeadLetterReason::ChannelClosed,
+            ),
+        ] {
+            let update = MomoUpdates {
+                remote_address: "127.0.0.1:50000".to_string(),
+                raw_body: raw_body.to_string(),
+                response: None,
+                parse_error: None,
+                update_type: CallbackType::RequestToPay,
+            };
+            sink.dead_letter(&update, reason).await.unwrap();
+        }
+
+        let dead_letters = sink.dead_letters().await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(dead_letters.len(), 2);
+        assert_eq!(dead_letters[0].raw_body, r#"{"externalId":"first"}"#);
+        assert_eq!(dead_letters[0].reason, DeadLetterReason::ChannelFull);
+        assert_eq!(dead_letters[1].reason, DeadLetterReason::ChannelClosed);
+        assert_eq!(dead_letters[1].update_type, CallbackType::RequestToPay);
+    }
+}
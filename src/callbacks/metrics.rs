@@ -0,0 +1,77 @@
+//! Prometheus metrics of the callback server, served on `GET /metrics` when
+//! `CallbackServerConfig::metrics` is set
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Counters updated by the callback handlers
+#[derive(Default)]
+pub(crate) struct CallbackMetrics {
+    received: Mutex<BTreeMap<String, u64>>,
+    parse_failures: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl CallbackMetrics {
+    /// Count a callback received on the given path
+    pub(crate) fn record_received(&self, path: &str, parsed: bool) {
+        *self
+            .received
+            .lock()
+            .unwrap()
+            .entry(path.to_string())
+            .or_default() += 1;
+        if !parsed {
+            self.parse_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a callback dropped because the channel was full
+    pub(crate) fn record_dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Render the metrics in the Prometheus text format
+    ///
+    /// # Parameters
+    ///
+    /// * 'channel_depth', how many callbacks are waiting for the consumer of the stream
+    pub(crate) fn render(&self, channel_depth: usize) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP callbacks_received_total Callbacks received, by path\n");
+        out.push_str("# TYPE callbacks_received_total counter\n");
+        for (path, count) in self.received.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "callbacks_received_total{{path=\"{}\"}} {}",
+                path, count
+            );
+        }
+        out.push_str(
+            "# HELP callbacks_parse_failures_total Callbacks whose body could not be parsed\n",
+        );
+        out.push_str("# TYPE callbacks_parse_failures_total counter\n");
+        let _ = writeln!(
+            out,
+            "callbacks_parse_failures_total {}",
+            self.parse_failures.load(Ordering::Relaxed)
+        );
+        out.push_str(
+            "# HELP callbacks_dropped_total Callbacks dropped because the channel was full\n",
+        );
+        out.push_str("# TYPE callbacks_dropped_total counter\n");
+        let _ = writeln!(
+            out,
+            "callbacks_dropped_total {}",
+            self.dropped.load(Ordering::Relaxed)
+        );
+        out.push_str(
+            "# HELP callbacks_channel_depth Callbacks waiting for the consumer of the stream\n",
+        );
+        out.push_str("# TYPE callbacks_channel_depth gauge\n");
+        let _ = writeln!(out, "callbacks_channel_depth {}", channel_depth);
+        out
+    }
+}
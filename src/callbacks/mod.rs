@@ -0,0 +1,15 @@
+#[cfg(feature = "callback-server")]
+pub mod callback_stream;
+#[cfg(feature = "callback-server")]
+pub mod dead_letter;
+#[cfg(feature = "metrics")]
+pub(crate) mod metrics;
+#[cfg(feature = "callback-server")]
+pub mod readiness;
+#[cfg(feature = "callback-server")]
+pub mod router;
+#[cfg(feature = "callback-server")]
+pub mod server_config;
+#[cfg(feature = "callback-server")]
+pub mod sink;
+pub mod stream_ext;
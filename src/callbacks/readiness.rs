@@ -0,0 +1,79 @@
+//! Readiness checks for the callback server
+//!
+//! `GET /health` only tells that the process is up, `GET /ready` runs the configured
+//! readiness checks and answers 503 as long as one of them fails.
+
+use futures_util::future::BoxFuture;
+
+use crate::products::collection::Collection;
+use crate::products::disbursements::Disbursements;
+use crate::products::remittance::Remittance;
+
+/// # ReadinessCheck
+/// Something the callback server needs before it can be considered ready
+pub trait ReadinessCheck: Send + Sync {
+    /// Run the check
+    ///
+    /// # Returns
+    ///
+    /// * 'Result<(), String>', the reason of the failure when the check fails
+    fn check(&self) -> BoxFuture<'_, Result<(), String>>;
+}
+
+/// the products are ready when they hold (or can create) a valid access token,
+/// a cached token does not hit MTN again until it expires
+impl ReadinessCheck for Collection {
+    fn check(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            self.get_valid_access_token()
+                .await
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        })
+    }
+}
+
+impl ReadinessCheck for Disbursements {
+    fn check(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            self.get_valid_access_token()
+                .await
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        })
+    }
+}
+
+impl ReadinessCheck for Remittance {
+    fn check(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            self.get_valid_access_token()
+                .await
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::products::test_server::{disbursements_at, spawn_mock};
+    use poem::{handler, post, Route};
+
+    #[handler]
+    fn unauthorized() -> poem::Response {
+        poem::Response::builder()
+            .status(poem::http::StatusCode::UNAUTHORIZED)
+            .body(r#"{"error":"login_failed"}"#)
+    }
+
+    #[tokio::test]
+    async fn test_check_fails_when_mtn_refuses_the_token() {
+        let url = spawn_mock(Route::new().at("/disbursement/token/", post(unauthorized)));
+
+        let disbursements = disbursements_at(url);
+        let reason = disbursements.check().await.unwrap_err();
+        assert!(reason.contains("login_failed"));
+    }
+}
@@ -0,0 +1,203 @@
+//! Callback router
+//!
+//! Correlates the callbacks received by `MomoCallbackListener::serve` with the transactions
+//! that triggered them, so several transactions can be awaited concurrently without racing
+//! on `stream.next()`.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+use tokio::sync::oneshot;
+
+use crate::{CallbackResponse, MomoUpdates};
+
+/// Maximum number of callbacks kept around for an external id nobody is waiting for yet
+const MAX_UNCLAIMED_CALLBACKS: usize = 1024;
+
+#[derive(Default)]
+struct RouterState {
+    waiting: HashMap<String, oneshot::Sender<CallbackResponse>>,
+    unclaimed: VecDeque<(String, CallbackResponse)>,
+}
+
+impl RouterState {
+    fn dispatch(&mut self, response: CallbackResponse) {
+        let Some(external_id) = response.external_id().map(str::to_string) else {
+            return;
+        };
+        let response = match self.waiting.remove(&external_id) {
+            Some(sender) => match sender.send(response) {
+                Ok(()) => return,
+                Err(response) => response,
+            },
+            None => response,
+        };
+        if self.unclaimed.len() == MAX_UNCLAIMED_CALLBACKS {
+            self.unclaimed.pop_front();
+        }
+        self.unclaimed.push_back((external_id, response));
+    }
+}
+
+/// # CallbackRouter
+/// Consumes a stream of callbacks and hands each one to whoever is waiting for its external id.
+///
+/// Callbacks that arrive before anyone waits for them are kept (up to 1024) and returned by the
+/// next matching `wait_for`. Callbacks without an external id (pre-approval, payment) are dropped.
+pub struct CallbackRouter {
+    state: Arc<Mutex<RouterState>>,
+}
+
+impl CallbackRouter {
+    /// Create a new CallbackRouter, the stream is consumed on a background task
+    ///
+    /// # Parameters
+    ///
+    /// * 'updates', the stream of callbacks, as returned by `MomoCallbackListener::serve`
+    ///
+    /// # Returns
+    ///
+    /// * 'CallbackRouter'
+    pub fn new<S>(updates: S) -> CallbackRouter
+    where
+        S: Stream<Item = MomoUpdates> + Send + 'static,
+    {
+        let state = Arc::new(Mutex::new(RouterState::default()));
+        let router_state = state.clone();
+        tokio::spawn(async move {
+            let mut updates = std::pin::pin!(updates);
+            while let Some(update) = updates.next().await {
+                if let Some(response) = update.response {
+                    router_state.lock().unwrap().dispatch(response);
+                }
+            }
+        });
+        CallbackRouter { state }
+    }
+
+    /// Wait for the callback of the transaction with the given external id.
+    /// A second wait on the same external id replaces the first one.
+    ///
+    /// # Parameters
+    ///
+    /// * 'external_id', the external id of the transaction
+    /// * 'timeout', how long to wait for the callback
+    ///
+    /// # Returns
+    ///
+    /// * 'Option<CallbackResponse>', None if no callback arrived before the timeout
+    pub async fn wait_for(&self, external_id: &str, timeout: Duration) -> Option<CallbackResponse> {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            if let Some(position) = state.unclaimed.iter().position(|(id, _)| id == external_id) {
+                return state
+                    .unclaimed
+                    .remove(position)
+                    .map(|(_, response)| response);
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.waiting.insert(external_id.to_string(), sender);
+            receiver
+        };
+
+        match tokio::time::timeout(timeout, receiver).await {
+            Ok(response) => response.ok(),
+            Err(_) => {
+                let mut state = self.state.lock().unwrap();
+                if state
+                    .waiting
+                    .get(external_id)
+                    .is_some_and(|sender| sender.is_closed())
+                {
+                    state.waiting.remove(external_id);
+                }
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enums::request_to_pay_status::RequestToPayStatus, CallbackType, Party, PartyIdType,
+    };
+    use tokio::sync::mpsc;
+
+    fn request_to_pay_success(external_id: &str) -> MomoUpdates {
+        MomoUpdates {
+            remote_address: "127.0.0.1".to_string(),
+            raw_body: String::new(),
+            parse_error: None,
+            response: Some(CallbackResponse::RequestToPaySuccess {
+                financial_transaction_id: "363440463".to_string(),
+                external_id: external_id.to_string(),
+                amount: "100".to_string(),
+                currency: "EUR".to_string(),
+                payer: Party {
+                    party_id_type: PartyIdType::MSISDN,
+                    party_id: "46733123450".to_string(),
+                },
+                payee_note: Some("test_payee_note".to_string()),
+                payer_message: Some("test_payer_message".to_string()),
+                status: RequestToPayStatus::SUCCESSFULL,
+            }),
+            update_type: CallbackType::RequestToPay,
+        }
+    }
+
+    fn router() -> (mpsc::Sender<MomoUpdates>, CallbackRouter) {
+        let (tx, mut rx) = mpsc::channel::<MomoUpdates>(32);
+        let router = CallbackRouter::new(async_stream::stream! {
+            while let Some(msg) = rx.recv().await {
+                yield msg;
+            }
+        });
+        (tx, router)
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_concurrent_transactions() {
+        let (tx, router) = router();
+        let timeout = Duration::from_secs(5);
+
+        let (first, second, _) = tokio::join!(
+            router.wait_for("first", timeout),
+            router.wait_for("second", timeout),
+            async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                tx.send(request_to_pay_success("second")).await.unwrap();
+                tx.send(request_to_pay_success("first")).await.unwrap();
+            }
+        );
+
+        assert_eq!(first.unwrap().external_id(), Some("first"));
+        assert_eq!(second.unwrap().external_id(), Some("second"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_callback_received_before_waiting() {
+        let (tx, router) = router();
+        tx.send(request_to_pay_success("early")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let response = router.wait_for("early", Duration::from_secs(5)).await;
+        assert_eq!(response.unwrap().external_id(), Some("early"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_timeout() {
+        let (tx, router) = router();
+        tx.send(request_to_pay_success("other")).await.unwrap();
+
+        let response = router.wait_for("missing", Duration::from_millis(100)).await;
+        assert!(response.is_none());
+        assert!(router.state.lock().unwrap().waiting.is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+//! Callback server configuration
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use super::dead_letter::DeadLetterSink;
+use super::readiness::ReadinessCheck;
+use super::sink::CallbackSink;
+use crate::metrics::Metrics;
+
+/// # SocketBinding
+/// A port the callback server listens on
+///
+/// - 'port', the port to listen on
+/// - 'tls', the certificate to serve HTTPS with, None for plain HTTP
+#[derive(Debug, Clone)]
+pub struct SocketBinding {
+    pub port: u16,
+    pub tls: Option<TlsCertificate>,
+}
+
+/// # CorsConfig
+/// The CORS policy of the callback server, for a browser application sharing its port, MTN
+/// calls the server from its own servers and needs none
+///
+/// - 'allowed_origins', the origins allowed to call the server, e.g. "https://shop.example.com",
+///   empty = any origin
+/// - 'allowed_methods', the methods they can use, e.g. "GET", empty = any method
+/// - 'allowed_headers', the headers they can send, e.g. "content-type", empty = any header
+#[derive(Debug, Clone, Default)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+}
+
+impl CorsConfig {
+    /// The CORS middleware applying the policy, or an error naming the first origin, method or
+    /// header that is not valid
+    pub(crate) fn middleware(&self) -> Result<poem::middleware::Cors, std::io::Error> {
+        let invalid = |kind: &str, value: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not a valid CORS {}", value, kind),
+            )
+        };
+        let mut cors = poem::middleware::Cors::new();
+        for origin in self.allowed_origins.iter() {
+            let origin =
+                poem::http::HeaderValue::from_str(origin).map_err(|_| invalid("origin", origin))?;
+            cors = cors.allow_origin(origin);
+        }
+        for method in self.allowed_methods.iter() {
+            let method = poem::http::Method::from_bytes(method.as_bytes())
+                .map_err(|_| invalid("method", method))?;
+            cors = cors.allow_method(method);
+        }
+        for header in self.allowed_headers.iter() {
+            let header = poem::http::HeaderName::from_bytes(header.as_bytes())
+                .map_err(|_| invalid("header", header))?;
+            cors = cors.allow_header(header);
+        }
+        Ok(cors)
+    }
+}
+
+/// # TlsCertificate
+/// A certificate the callback server serves HTTPS with
+///
+/// The `rustls-tls` feature serves `Pem` certificates and the `native-tls` feature `Pkcs12`
+/// archives, the server refuses to start with the other kind.
+#[derive(Clone)]
+pub enum TlsCertificate {
+    /// 'cert', the PEM encoded certificate chain, 'key', the PEM encoded private key
+    Pem { cert: Vec<u8>, key: Vec<u8> },
+    /// 'archive', the DER encoded PKCS#12 archive holding the certificate chain and the private
+    /// key, 'password', the password of the archive
+    Pkcs12 { archive: Vec<u8>, password: String },
+}
+
+impl TlsCertificate {
+    /// A PEM certificate chain and private key, for the `rustls-tls` feature
+    pub fn pem(cert: Vec<u8>, key: Vec<u8>) -> TlsCertificate {
+        TlsCertificate::Pem { cert, key }
+    }
+
+    /// A DER encoded PKCS#12 archive and its password, for the `native-tls` feature
+    pub fn pkcs12(der: Vec<u8>, password: &str) -> TlsCertificate {
+        TlsCertificate::Pkcs12 {
+            archive: der,
+            password: password.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Debug for TlsCertificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TlsCertificate::Pem { .. } => f.debug_struct("Pem").finish_non_exhaustive(),
+            TlsCertificate::Pkcs12 { .. } => f.debug_struct("Pkcs12").finish_non_exhaustive(),
+        }
+    }
+}
+
+/// # ShutdownHandle
+/// Stops a callback server started with it in `CallbackServerConfig::shutdown`
+///
+/// The server stops accepting connections, waits up to `CallbackServerConfig::shutdown_grace`
+/// for the callbacks being received, then drops the remaining connections. The stream of
+/// callbacks ends once the callbacks already received are consumed.
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl ShutdownHandle {
+    pub fn new() -> ShutdownHandle {
+        let (sender, _) = watch::channel(false);
+        ShutdownHandle {
+            sender: Arc::new(sender),
+        }
+    }
+
+    /// Stop the servers started with this handle
+    pub fn shutdown(&self) {
+        self.sender.send_replace(true);
+    }
+
+    /// Resolves once `shutdown` is called, straight away if it already was
+    pub(crate) async fn wait(&self) {
+        let mut receiver = self.sender.subscribe();
+        let _ = receiver.wait_for(|stopped| *stopped).await;
+    }
+}
+
+impl Default for ShutdownHandle {
+    fn default() -> Self {
+        ShutdownHandle::new()
+    }
+}
+
+/// # CallbackServerConfig
+/// Configuration of the server started by `MomoCallbackListener::serve_with_config`
+///
+/// - 'host', the address to bind to, default = 0.0.0.0
+/// - 'http_port', the port to listen on, default = 3000
+/// - 'additional_addresses', other addresses to listen on as well (for example an internal
+///   interface), callbacks from every address end up in the same stream, default = none
+/// - 'bindings', other ports of `host` to listen on, each one optionally with TLS (for example
+///   both 80 and 443), callbacks from every port end up in the same stream, default = none
+/// - 'channel_capacity', how many callbacks can be waiting for the consumer of the stream, at
+///   least 1, default = 100
+/// - 'drop_when_full', when the channel is full, drop (and log) new callbacks instead of holding
+///   the HTTP response until the consumer catches up, default = false
+/// - 'ack_status_code', the HTTP status returned to MTN once a callback is received, default = 200
+/// - 'ack_response', the body returned to MTN once a callback is received,
+///   default = "Callback received successfully"
+/// - 'metrics', serve Prometheus metrics on `GET /metrics` (requires the `metrics` feature),
+///   default = false
+/// - 'readiness_checks', what `GET /ready` runs before answering 200, for example the products
+///   used by the application, default = none
+/// - 'log_bodies', log the callback bodies at debug level, with the payer details masked (see
+///   `mtnmomo::redaction`), otherwise only the kind of callback and its external id are logged,
+///   default = false
+/// - 'shutdown', stops the server when triggered, default = none, the server runs as long as
+///   the runtime
+/// - 'shutdown_grace', how long a shutdown waits for the requests in flight before dropping
+///   their connections, None waits for them however long they take, default = 10 seconds
+/// - 'sink', stores every callback before it is handed over to the stream, see `CallbackSink`,
+///   default = none
+/// - 'dead_letter', gets the callbacks the stream could not take, see `DeadLetterSink`,
+///   default = none, they are logged and lost
+/// - 'metrics_hooks', told about every callback received, see `mtnmomo::metrics::Metrics`,
+///   default = none
+/// - 'cors', the CORS policy of the server, see `CorsConfig`, default = none, no CORS headers
+///   are sent and browsers refuse cross-origin calls
+#[derive(Clone)]
+pub struct CallbackServerConfig {
+    pub host: String,
+    pub http_port: u16,
+    pub additional_addresses: Vec<SocketAddr>,
+    pub bindings: Vec<SocketBinding>,
+    pub channel_capacity: usize,
+    pub drop_when_full: bool,
+    pub ack_status_code: u16,
+    pub ack_response: Option<String>,
+    #[cfg(feature = "metrics")]
+    pub metrics: bool,
+    pub readiness_checks: Vec<Arc<dyn ReadinessCheck>>,
+    pub log_bodies: bool,
+    pub shutdown: Option<ShutdownHandle>,
+    pub shutdown_grace: Option<Duration>,
+    pub sink: Option<Arc<dyn CallbackSink>>,
+    pub dead_letter: Option<Arc<dyn DeadLetterSink>>,
+    pub metrics_hooks: Option<Arc<dyn Metrics>>,
+    pub cors: Option<CorsConfig>,
+}
+
+impl Default for CallbackServerConfig {
+    fn default() -> Self {
+        CallbackServerConfig {
+            host: "0.0.0.0".to_string(),
+            http_port: 3000,
+            additional_addresses: vec![],
+            bindings: vec![],
+            channel_capacity: 100,
+            drop_when_full: false,
+            ack_status_code: 200,
+            ack_response: None,
+            #[cfg(feature = "metrics")]
+            metrics: false,
+            readiness_checks: vec![],
+            log_bodies: false,
+            shutdown: None,
+            shutdown_grace: Some(Duration::from_secs(10)),
+            sink: None,
+            dead_letter: None,
+            metrics_hooks: None,
+            cors: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for CallbackServerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("CallbackServerConfig");
+        debug
+            .field("host", &self.host)
+            .field("http_port", &self.http_port)
+            .field("additional_addresses", &self.additional_addresses)
+            .field("bindings", &self.bindings)
+            .field("channel_capacity", &self.channel_capacity)
+            .field("drop_when_full", &self.drop_when_full)
+            .field("ack_status_code", &self.ack_status_code)
+            .field("ack_response", &self.ack_response)
+            .field("readiness_checks", &self.readiness_checks.len())
+            .field("log_bodies", &self.log_bodies)
+            .field("shutdown", &self.shutdown.is_some())
+            .field("shutdown_grace", &self.shutdown_grace)
+            .field("sink", &self.sink.is_some())
+            .field("dead_letter", &self.dead_letter.is_some())
+            .field("metrics_hooks", &self.metrics_hooks.is_some())
+            .field("cors", &self.cors);
+        #[cfg(feature = "metrics")]
+        debug.field("metrics", &self.metrics);
+        debug.finish()
+    }
+}
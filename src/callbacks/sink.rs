@@ -0,0 +1,36 @@
+//! Persistence of the callbacks received by the callback server
+//!
+//! A `CallbackSink` set in `CallbackServerConfig::sink` stores every callback before it is
+//! handed over to the stream, so callbacks are stored even when nobody consumes the stream.
+
+use futures_util::future::BoxFuture;
+
+use crate::MomoUpdates;
+
+/// # CallbackSink
+/// Stores the callbacks received, e.g. in a database or a queue
+pub trait CallbackSink: Send + Sync {
+    /// Store a callback, the callback is still delivered to the stream when it fails
+    ///
+    /// # Returns
+    ///
+    /// * 'Result<(), Box<dyn Error>>', why the callback could not be stored, it is logged
+    fn store<'a>(
+        &'a self,
+        update: &'a MomoUpdates,
+    ) -> BoxFuture<'a, Result<(), Box<dyn std::error::Error + Send + Sync>>>;
+}
+
+/// # NoopCallbackSink
+/// A `CallbackSink` storing nothing, what the callback server does without a sink
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopCallbackSink;
+
+impl CallbackSink for NoopCallbackSink {
+    fn store<'a>(
+        &'a self,
+        _update: &'a MomoUpdates,
+    ) -> BoxFuture<'a, Result<(), Box<dyn std::error::Error + Send + Sync>>> {
+        Box::pin(async { Ok(()) })
+    }
+}
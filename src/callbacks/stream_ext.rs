@@ -0,0 +1,395 @@
+//! Stream helpers for MTN MoMo callbacks
+//!
+//! `MomoCallbackListener::serve` yields every callback it receives. The `MomoUpdatesStreamExt`
+//! trait narrows that stream down to the callbacks a consumer cares about, and `CallbackWaiter`
+//! waits for the callback of one transaction at a time.
+
+use std::{collections::VecDeque, time::Duration};
+
+use futures_core::Stream;
+use futures_util::{
+    future::{self, Either},
+    pin_mut, StreamExt,
+};
+
+use crate::{CallbackResponse, CallbackType, MomoUpdates};
+
+pub trait MomoUpdatesStreamExt: Stream<Item = MomoUpdates> + Sized {
+    /// Keep only the request to pay callbacks, successful or failed
+    fn only_request_to_pay(self) -> impl Stream<Item = MomoUpdates> {
+        self.filter(|update| {
+            future::ready(matches!(
+                update.response,
+                Some(CallbackResponse::RequestToPaySuccess { .. })
+                    | Some(CallbackResponse::RequestToPayFailed { .. })
+            ))
+        })
+    }
+
+    /// Keep only the callbacks of one kind, from the route they were received on
+    ///
+    /// # Parameters
+    ///
+    /// * 'callback_type', the kind of callbacks to keep, e.g. `CallbackType::CollectionPreApproval`
+    fn only_callback_type(self, callback_type: CallbackType) -> impl Stream<Item = MomoUpdates> {
+        self.filter(move |update| future::ready(update.update_type == callback_type))
+    }
+
+    /// Keep only the callbacks reporting a failed transaction
+    fn only_failures(self) -> impl Stream<Item = MomoUpdates> {
+        self.filter(|update| {
+            future::ready(matches!(
+                &update.response,
+                Some(response) if !response.status_is_success()
+            ))
+        })
+    }
+
+    /// Keep only the callbacks of the transaction with the given external id
+    ///
+    /// # Parameters
+    ///
+    /// * 'external_id', the external id of the transaction, as returned when the request was made
+    fn by_external_id(self, external_id: impl Into<String>) -> impl Stream<Item = MomoUpdates> {
+        let external_id = external_id.into();
+        self.filter(move |update| future::ready(has_external_id(update, &external_id)))
+    }
+}
+
+impl<S: Stream<Item = MomoUpdates>> MomoUpdatesStreamExt for S {}
+
+fn has_external_id(update: &MomoUpdates, external_id: &str) -> bool {
+    update
+        .response
+        .as_ref()
+        .and_then(CallbackResponse::external_id)
+        == Some(external_id)
+}
+
+/// # CallbackWaiter
+/// Waits on a stream of callbacks for the callback of a given transaction
+///
+/// The callbacks of other transactions read in the meantime are kept, the next wait for their
+/// external id returns them without reading the stream. At most `DEFAULT_PENDING_LIMIT` of them
+/// are kept, see `with_pending_limit`, then the oldest one is dropped and logged.
+///
+/// ```no_run
+/// # async fn example(updates: impl futures_core::Stream<Item = mtnmomo::MomoUpdates>) {
+/// use mtnmomo::CallbackWaiter;
+///
+/// let mut waiter = CallbackWaiter::new(Box::pin(updates));
+/// let update = waiter
+///     .wait_for_callback("external_id", std::time::Duration::from_secs(60))
+///     .await;
+/// # }
+/// ```
+pub struct CallbackWaiter<S> {
+    stream: S,
+    pending: VecDeque<MomoUpdates>,
+    pending_limit: usize,
+}
+
+/// How many callbacks of other transactions a `CallbackWaiter` keeps by default
+pub const DEFAULT_PENDING_LIMIT: usize = 1000;
+
+impl<S: Stream<Item = MomoUpdates> + Unpin> CallbackWaiter<S> {
+    /// Wrap a stream of callbacks, e.g. the pinned stream of `MomoCallbackListener::serve`
+    pub fn new(stream: S) -> CallbackWaiter<S> {
+        CallbackWaiter {
+            stream,
+            pending: VecDeque::new(),
+            pending_limit: DEFAULT_PENDING_LIMIT,
+        }
+    }
+
+    /// Keep at most the given number of callbacks of other transactions, the oldest one is
+    /// dropped, and logged, to make room for a new one, default = `DEFAULT_PENDING_LIMIT`
+    ///
+    /// # Parameters
+    ///
+    /// * 'limit', how many callbacks are kept, 0 drops every callback not waited for
+    ///
+    /// # Returns
+    /// * CallbackWaiter
+    pub fn with_pending_limit(mut self, limit: usize) -> CallbackWaiter<S> {
+        self.pending_limit = limit;
+        while self.pending.len() > limit {
+            self.drop_oldest();
+        }
+        self
+    }
+
+    /// Wait for the callback of the transaction with the given external id
+    ///
+    /// # Parameters
+    ///
+    /// * 'external_id', the external id of the transaction, as returned when the request was made
+    /// * 'timeout', how long to wait for the callback
+    ///
+    /// # Returns
+    ///
+    /// * the callback, None if it did not arrive in time or the stream ended
+    pub async fn wait_for_callback(
+        &mut self,
+        external_id: &str,
+        timeout: Duration,
+    ) -> Option<MomoUpdates> {
+        if let Some(position) = self
+            .pending
+            .iter()
+            .position(|update| has_external_id(update, external_id))
+        {
+            return self.pending.remove(position);
+        }
+        let deadline = crate::products::sleep(timeout);
+        pin_mut!(deadline);
+        loop {
+            match future::select(self.stream.next(), &mut deadline).await {
+                Either::Left((Some(update), _)) => {
+                    if has_external_id(&update, external_id) {
+                        return Some(update);
+                    }
+                    self.pending.push_back(update);
+                    if self.pending.len() > self.pending_limit {
+                        self.drop_oldest();
+                    }
+                }
+                Either::Left((None, _)) | Either::Right(_) => return None,
+            }
+        }
+    }
+
+    /// The callbacks read while waiting that were not returned yet, oldest first
+    pub fn pending(&self) -> impl Iterator<Item = &MomoUpdates> {
+        self.pending.iter()
+    }
+
+    /// Give the stream back, with the callbacks read but not returned yet
+    pub fn into_parts(self) -> (S, Vec<MomoUpdates>) {
+        (self.stream, self.pending.into())
+    }
+
+    /// Drop the oldest callback kept, no wait claimed it before the limit was reached
+    fn drop_oldest(&mut self) {
+        if let Some(update) = self.pending.pop_front() {
+            tracing::warn!(
+                update_type = %update.update_type,
+                external_id = ?update.response.as_ref().and_then(CallbackResponse::external_id),
+                "dropped a callback nobody waited for, {} callbacks are kept at most",
+                self.pending_limit
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enums::{reason::RequestToPayReason, request_to_pay_status::RequestToPayStatus},
+        Party, PartyIdType, Reason,
+    };
+    use futures_util::stream;
+
+    fn payer() -> Party {
+        Party {
+            party_id_type: PartyIdType::MSISDN,
+            party_id: "46733123450".to_string(),
+        }
+    }
+
+    fn request_to_pay_success(external_id: &str) -> MomoUpdates {
+        MomoUpdates {
+            remote_address: "127.0.0.1".to_string(),
+            raw_body: String::new(),
+            parse_error: None,
+            response: Some(CallbackResponse::RequestToPaySuccess {
+                financial_transaction_id: "363440463".to_string(),
+                external_id: external_id.to_string(),
+                amount: "100".to_string(),
+                currency: "EUR".to_string(),
+                payer: payer(),
+                payee_note: Some("test_payee_note".to_string()),
+                payer_message: Some("test_payer_message".to_string()),
+                status: RequestToPayStatus::SUCCESSFULL,
+            }),
+            update_type: CallbackType::RequestToPay,
+        }
+    }
+
+    fn request_to_pay_failed(external_id: &str) -> MomoUpdates {
+        MomoUpdates {
+            remote_address: "127.0.0.1".to_string(),
+            raw_body: String::new(),
+            parse_error: None,
+            response: Some(CallbackResponse::RequestToPayFailed {
+                financial_transaction_id: None,
+                external_id: external_id.to_string(),
+                amount: "100".to_string(),
+                currency: "EUR".to_string(),
+                payer: payer(),
+                payee_note: Some("test_payee_note".to_string()),
+                payer_message: Some("test_payer_message".to_string()),
+                status: RequestToPayStatus::FAILED,
+                reason: Reason {
+                    code: RequestToPayReason::PAYERNOTFOUND,
+                    message: "payer not found".to_string(),
+                },
+            }),
+            update_type: CallbackType::RequestToPay,
+        }
+    }
+
+    fn pre_approval_failed() -> MomoUpdates {
+        MomoUpdates {
+            remote_address: "127.0.0.1".to_string(),
+            raw_body: String::new(),
+            parse_error: None,
+            response: Some(CallbackResponse::PreApprovalFailed {
+                payer: payer(),
+                payer_currency: "EUR".to_string(),
+                status: "FAILED".to_string(),
+                expiration_date_time: "2024-09-16T18:05:00Z".to_string(),
+                reason: Reason {
+                    code: RequestToPayReason::APPROVALREJECTED,
+                    message: "approval rejected".to_string(),
+                },
+            }),
+            update_type: CallbackType::CollectionPreApproval,
+        }
+    }
+
+    fn updates() -> impl Stream<Item = MomoUpdates> {
+        stream::iter(vec![
+            request_to_pay_success("first"),
+            pre_approval_failed(),
+            request_to_pay_failed("second"),
+            request_to_pay_success("second"),
+        ])
+    }
+
+    fn external_ids(updates: Vec<MomoUpdates>) -> Vec<Option<String>> {
+        updates
+            .iter()
+            .map(|update| {
+                update
+                    .response
+                    .as_ref()
+                    .and_then(CallbackResponse::external_id)
+                    .map(str::to_string)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_only_request_to_pay() {
+        let updates: Vec<MomoUpdates> = updates().only_request_to_pay().collect().await;
+        assert_eq!(
+            external_ids(updates),
+            vec![
+                Some("first".to_string()),
+                Some("second".to_string()),
+                Some("second".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_callback_type() {
+        let pre_approvals: Vec<MomoUpdates> = updates()
+            .only_callback_type(CallbackType::CollectionPreApproval)
+            .collect()
+            .await;
+        assert_eq!(pre_approvals.len(), 1);
+        assert!(matches!(
+            pre_approvals[0].response,
+            Some(CallbackResponse::PreApprovalFailed { .. })
+        ));
+        let invoices: Vec<MomoUpdates> = updates()
+            .only_callback_type(CallbackType::Invoice)
+            .collect()
+            .await;
+        assert!(invoices.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_only_failures() {
+        let updates: Vec<MomoUpdates> = updates().only_failures().collect().await;
+        assert_eq!(updates.len(), 2);
+        assert!(matches!(
+            updates[0].response,
+            Some(CallbackResponse::PreApprovalFailed { .. })
+        ));
+        assert!(matches!(
+            updates[1].response,
+            Some(CallbackResponse::RequestToPayFailed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_by_external_id() {
+        let updates: Vec<MomoUpdates> = updates().by_external_id("second").collect().await;
+        assert_eq!(
+            external_ids(updates),
+            vec![Some("second".to_string()), Some("second".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_callback_interleaved() {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let updates = stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|update| (update, receiver))
+        });
+        let mut waiter = CallbackWaiter::new(Box::pin(updates));
+        let timeout = std::time::Duration::from_secs(1);
+
+        sender.send(request_to_pay_success("first")).unwrap();
+        sender.send(pre_approval_failed()).unwrap();
+        sender.send(request_to_pay_failed("second")).unwrap();
+        sender.send(request_to_pay_success("third")).unwrap();
+
+        // the callbacks of first and of the pre-approval are kept while waiting for second
+        let second = waiter.wait_for_callback("second", timeout).await.unwrap();
+        assert!(matches!(
+            second.response,
+            Some(CallbackResponse::RequestToPayFailed { .. })
+        ));
+        assert_eq!(waiter.pending().count(), 2);
+
+        let first = waiter.wait_for_callback("first", timeout).await.unwrap();
+        assert_eq!(external_ids(vec![first]), vec![Some("first".to_string())]);
+        let third = waiter.wait_for_callback("third", timeout).await.unwrap();
+        assert_eq!(external_ids(vec![third]), vec![Some("third".to_string())]);
+
+        let missing = waiter
+            .wait_for_callback("missing", std::time::Duration::from_millis(20))
+            .await;
+        assert!(missing.is_none());
+        let (_, pending) = waiter.into_parts();
+        assert_eq!(pending.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_callback_pending_limit() {
+        let updates = stream::iter(vec![
+            request_to_pay_success("first"),
+            request_to_pay_success("second"),
+            request_to_pay_success("third"),
+            request_to_pay_success("wanted"),
+        ]);
+        let mut waiter = CallbackWaiter::new(updates).with_pending_limit(2);
+        let timeout = std::time::Duration::from_secs(1);
+
+        assert!(waiter.wait_for_callback("wanted", timeout).await.is_some());
+        // first was dropped to make room for third
+        assert_eq!(
+            external_ids(waiter.pending().cloned().collect()),
+            vec![Some("second".to_string()), Some("third".to_string())]
+        );
+
+        let mut waiter = waiter.with_pending_limit(0);
+        assert_eq!(waiter.pending().count(), 0);
+        assert!(waiter.wait_for_callback("second", timeout).await.is_none());
+    }
+}
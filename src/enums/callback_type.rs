@@ -4,6 +4,8 @@ use std::fmt;
 #[doc(hidden)]
 use serde::{Deserialize, Serialize};
 
+use crate::{callback_paths, enums::disbursement_operation::DisbursementOperation};
+
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
 pub enum CallbackType {
     #[serde(rename = "REQUEST_TO_PAY")]
@@ -50,6 +52,116 @@ pub enum CallbackType {
 }
 
 impl CallbackType {
+    /// Every kind of callback the callback server has a route for
+    pub const ALL: [CallbackType; 13] = [
+        CallbackType::RequestToPay,
+        CallbackType::RequestToWithdrawV1,
+        CallbackType::RequestToWithdrawV2,
+        CallbackType::Invoice,
+        CallbackType::CollectionPayment,
+        CallbackType::CollectionPreApproval,
+        CallbackType::DisbursementDepositV1,
+        CallbackType::DisbursementDepositV2,
+        CallbackType::DisbursementRefundV1,
+        CallbackType::DisbursementRefundV2,
+        CallbackType::DisbusrementTransfer,
+        CallbackType::RemittanceCashTransfer,
+        CallbackType::RemittanceTransfer,
+    ];
+
+    /// The path the callback server receives this kind of callback on, followed by
+    /// `/<callback type>`, e.g. "/collection_request_to_pay". Empty for `CallbackType::None`.
+    ///
+    /// See `callback_paths` for the paths of every kind of callback.
+    pub fn route_path(&self) -> &'static str {
+        match *self {
+            CallbackType::RequestToPay => callback_paths::COLLECTION_REQUEST_TO_PAY,
+            CallbackType::RequestToWithdrawV1 => callback_paths::COLLECTION_REQUEST_TO_WITHDRAW_V1,
+            CallbackType::RequestToWithdrawV2 => callback_paths::COLLECTION_REQUEST_TO_WITHDRAW_V2,
+            CallbackType::Invoice => callback_paths::COLLECTION_INVOICE,
+            CallbackType::CollectionPayment => callback_paths::COLLECTION_PAYMENT,
+            CallbackType::CollectionPreApproval => callback_paths::COLLECTION_PRE_APPROVAL,
+            CallbackType::DisbursementDepositV1 => callback_paths::DISBURSEMENT_DEPOSIT_V1,
+            CallbackType::DisbursementDepositV2 => callback_paths::DISBURSEMENT_DEPOSIT_V2,
+            CallbackType::DisbursementRefundV1 => callback_paths::DISBURSEMENT_REFUND_V1,
+            CallbackType::DisbursementRefundV2 => callback_paths::DISBURSEMENT_REFUND_V2,
+            CallbackType::DisbusrementTransfer => callback_paths::DISBURSEMENT_TRANSFER,
+            CallbackType::RemittanceCashTransfer => callback_paths::REMITTANCE_CASH_TRANSFER,
+            CallbackType::RemittanceTransfer => callback_paths::REMITTANCE_TRANSFER,
+            CallbackType::None => "",
+        }
+    }
+
+    /// The disbursement operation this kind of callback reports on, a remittance transfer is a
+    /// transfer too
+    ///
+    /// # Returns
+    ///
+    /// * 'Option<DisbursementOperation>', None when the callback is not a disbursement callback
+    pub fn disbursement_operation(&self) -> Option<DisbursementOperation> {
+        match *self {
+            CallbackType::DisbursementDepositV1 | CallbackType::DisbursementDepositV2 => {
+                Some(DisbursementOperation::Deposit)
+            }
+            CallbackType::DisbursementRefundV1 | CallbackType::DisbursementRefundV2 => {
+                Some(DisbursementOperation::Refund)
+            }
+            CallbackType::DisbusrementTransfer | CallbackType::RemittanceTransfer => {
+                Some(DisbursementOperation::Transfer)
+            }
+            _ => None,
+        }
+    }
+
+    /// The full path the callback server receives this kind of callback on
+    ///
+    /// # Returns
+    ///
+    /// * e.g. "/collection_request_to_pay/REQUEST_TO_PAY"
+    pub fn path(&self) -> String {
+        format!("{}/{}", self.route_path(), self)
+    }
+
+    /// The X-Callback-Url to give MTN for this kind of callback
+    ///
+    /// # Parameters
+    ///
+    /// * 'base_url', where the callback server is reachable, e.g. "https://callbacks.example.com"
+    ///
+    /// # Returns
+    ///
+    /// * e.g. "https://callbacks.example.com/collection_request_to_pay/REQUEST_TO_PAY"
+    pub fn callback_url(&self, base_url: &str) -> String {
+        format!("{}{}", base_url.trim_end_matches('/'), self.path())
+    }
+
+    /// The kind of callback received on a path, the reverse of `CallbackType::path`
+    ///
+    /// The last segment of the path decides, e.g. REQUEST_TO_PAY, so that the path can be mounted
+    /// under a prefix, a path ending with the route path of a kind of callback is of that kind.
+    ///
+    /// # Parameters
+    ///
+    /// * 'path', the path the callback was sent to, e.g. "/collection_request_to_pay/REQUEST_TO_PAY"
+    ///
+    /// # Returns
+    ///
+    /// * 'CallbackType', `CallbackType::None` when the path is not the one of a kind of callback
+    pub fn from_path(path: &str) -> CallbackType {
+        let path = path.split('?').next().unwrap_or_default().trim_end_matches('/');
+        let (route, segment) = path.rsplit_once('/').unwrap_or(("", path));
+        match CallbackType::from_string(segment) {
+            CallbackType::None => CallbackType::ALL
+                .into_iter()
+                .find(|callback_type| {
+                    path.ends_with(callback_type.route_path())
+                        || route.ends_with(callback_type.route_path())
+                })
+                .unwrap_or(CallbackType::None),
+            callback_type => callback_type,
+        }
+    }
+
     pub fn from_string(s: &str) -> CallbackType {
         match s {
             "REQUEST_TO_PAY" => CallbackType::RequestToPay,
@@ -90,3 +202,40 @@ impl fmt::Display for CallbackType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_url() {
+        assert_eq!(
+            CallbackType::RequestToPay.callback_url("https://callbacks.example.com/"),
+            "https://callbacks.example.com/collection_request_to_pay/REQUEST_TO_PAY"
+        );
+        for callback_type in CallbackType::ALL {
+            assert!(callback_type.route_path().starts_with('/'));
+            assert_eq!(
+                CallbackType::from_string(&callback_type.to_string()),
+                callback_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_path() {
+        for callback_type in CallbackType::ALL {
+            assert_eq!(CallbackType::from_path(&callback_type.path()), callback_type);
+            assert_eq!(
+                CallbackType::from_path(&format!("/momo{}/?id=1", callback_type.path())),
+                callback_type
+            );
+            assert_eq!(
+                CallbackType::from_path(&format!("{}/UNKNOWN", callback_type.route_path())),
+                callback_type
+            );
+        }
+        assert_eq!(CallbackType::from_path("/health"), CallbackType::None);
+        assert_eq!(CallbackType::from_path(""), CallbackType::None);
+    }
+}
@@ -0,0 +1,29 @@
+#[doc(hidden)]
+use std::fmt;
+
+#[doc(hidden)]
+use serde::{Serialize, Deserialize};
+
+
+/// The operation a disbursement callback reports on, MTN sends the same body for all of them, so
+/// it is known from the route the callback was received on, see `CallbackType::disbursement_operation`
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum DisbursementOperation {
+    #[serde(rename = "DEPOSIT")]
+    Deposit,
+    #[serde(rename = "REFUND")]
+    Refund,
+    #[serde(rename = "TRANSFER")]
+    Transfer,
+}
+
+
+impl fmt::Display for DisbursementOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DisbursementOperation::Deposit => write!(f, "DEPOSIT"),
+            DisbursementOperation::Refund => write!(f, "REFUND"),
+            DisbursementOperation::Transfer => write!(f, "TRANSFER"),
+        }
+    }
+}
@@ -1,9 +1,13 @@
 #[doc(hidden)]
-use std::fmt;
+use std::{fmt, str::FromStr};
 
 #[doc(hidden)]
 use serde::{Deserialize, Serialize};
 
+/// The target environment, sent to MTN in the X-Target-Environment header
+///
+/// In production MTN expects the name of the market the API user was created in, e.g.
+/// "mtncongo" for `Environment::MTNCONGO`, not a generic production value.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
 pub enum Environment {
     /// The following environments are available
@@ -41,3 +45,98 @@ impl fmt::Display for Environment {
         }
     }
 }
+
+impl Environment {
+    /// What the host of the MTN url is expected to contain for this environment
+    ///
+    /// # Returns
+    ///
+    /// * 'sandbox.momodeveloper.mtn.com' for the sandbox, 'proxy.momoapi.mtn.com' otherwise
+    pub fn expected_host_contains(&self) -> &'static str {
+        match *self {
+            Environment::Sandbox => "sandbox.momodeveloper.mtn.com",
+            _ => "proxy.momoapi.mtn.com",
+        }
+    }
+
+    /// true when the url points at the MTN host of the other kind of environment, e.g. sandbox
+    /// keys used against the production url. Other hosts (a proxy, a mock server) are not checked.
+    ///
+    /// # Parameters
+    ///
+    /// * 'url', the url of the MTN api
+    pub fn is_mismatched_url(&self, url: &str) -> bool {
+        let other = match *self {
+            Environment::Sandbox => Environment::Live,
+            _ => Environment::Sandbox,
+        };
+        url.contains(other.expected_host_contains())
+    }
+}
+
+/// Parse the value sent in the X-Target-Environment header, e.g. "sandbox" or "mtnuganda"
+impl FromStr for Environment {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sandbox" => Ok(Environment::Sandbox),
+            "mtnuganda" => Ok(Environment::MTNUGANDA),
+            "mtnivorycoast" => Ok(Environment::MTNIVORYCOAST),
+            "mtnghana" => Ok(Environment::MTNGHANA),
+            "mtnzambia" => Ok(Environment::MTNZAMBIA),
+            "mtncameroon" => Ok(Environment::MTNCAMEROON),
+            "mtnbenin" => Ok(Environment::MTNBENIN),
+            "mtncongo" => Ok(Environment::MTNCONGO),
+            "mtnliberia" => Ok(Environment::MTNLIBERIA),
+            "mtnswaziland" => Ok(Environment::MTNSWAZILAND),
+            "mtnguineaconakry" => Ok(Environment::MTNGUINEACONAKRY),
+            "mtnsouthafrica" => Ok(Environment::MTNSOUTHAFRICA),
+            "live" => Ok(Environment::Live),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not a known environment", s),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            "sandbox".parse::<Environment>().unwrap(),
+            Environment::Sandbox
+        );
+        assert_eq!(
+            "MTNUGANDA".parse::<Environment>().unwrap(),
+            Environment::MTNUGANDA
+        );
+        assert!("production".parse::<Environment>().is_err());
+
+        for environment in [
+            Environment::Sandbox,
+            Environment::MTNCONGO,
+            Environment::Live,
+        ] {
+            assert_eq!(
+                environment.to_string().parse::<Environment>().unwrap(),
+                environment
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_mismatched_url() {
+        let sandbox_url = "https://sandbox.momodeveloper.mtn.com";
+        let production_url = "https://proxy.momoapi.mtn.com";
+        assert!(!Environment::Sandbox.is_mismatched_url(sandbox_url));
+        assert!(Environment::Sandbox.is_mismatched_url(production_url));
+        assert!(!Environment::MTNUGANDA.is_mismatched_url(production_url));
+        assert!(Environment::MTNUGANDA.is_mismatched_url(sandbox_url));
+        assert!(!Environment::Sandbox.is_mismatched_url("http://127.0.0.1:3000"));
+    }
+}
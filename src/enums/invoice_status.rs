@@ -0,0 +1,53 @@
+#[doc(hidden)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum InvoiceStatus {
+    PENDING,
+    SUCCESSFUL,
+    FAILED,
+    CANCELLED,
+    EXPIRED,
+    UNKNOWN,
+}
+
+impl InvoiceStatus {
+    pub fn from_string(s: &str) -> InvoiceStatus {
+        match s {
+            "PENDING" => InvoiceStatus::PENDING,
+            "SUCCESSFUL" => InvoiceStatus::SUCCESSFUL,
+            "FAILED" => InvoiceStatus::FAILED,
+            "CANCELLED" => InvoiceStatus::CANCELLED,
+            "EXPIRED" => InvoiceStatus::EXPIRED,
+            _ => InvoiceStatus::UNKNOWN,
+        }
+    }
+
+    /// true once the invoice can no longer change, i.e. anything but PENDING
+    /// (an unknown status is not considered final)
+    pub fn is_final(&self) -> bool {
+        !matches!(self, InvoiceStatus::PENDING | InvoiceStatus::UNKNOWN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_string() {
+        for (status, expected, is_final) in [
+            ("PENDING", InvoiceStatus::PENDING, false),
+            ("SUCCESSFUL", InvoiceStatus::SUCCESSFUL, true),
+            ("FAILED", InvoiceStatus::FAILED, true),
+            ("CANCELLED", InvoiceStatus::CANCELLED, true),
+            ("EXPIRED", InvoiceStatus::EXPIRED, true),
+            ("PAID", InvoiceStatus::UNKNOWN, false),
+            ("", InvoiceStatus::UNKNOWN, false),
+        ] {
+            let parsed = InvoiceStatus::from_string(status);
+            assert_eq!(parsed, expected, "{}", status);
+            assert_eq!(parsed.is_final(), is_final, "{}", status);
+        }
+    }
+}
@@ -1,7 +1,10 @@
 pub mod access_type;
 pub mod callback_type;
 pub mod currency;
+pub mod disbursement_operation;
 pub mod environment;
+pub mod invoice_status;
+pub mod msisdn_policy;
 pub mod party_id_type;
 pub mod payer_identification_type;
 pub mod reason;
@@ -0,0 +1,25 @@
+#[doc(hidden)]
+use serde::{Deserialize, Serialize};
+
+/// How the products format mobile numbers before sending them to MTN
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum MsisdnPolicy {
+    /// remove the spaces, dashes and dots, keep the leading `+` when there is one
+    #[default]
+    KeepPlus,
+    /// remove the spaces, dashes and dots as well as the leading `+`, e.g. +242 06 481 8006 -> 242064818006
+    StripPlus,
+}
+
+impl MsisdnPolicy {
+    pub fn normalize(&self, msisdn: &str) -> String {
+        let msisdn: String = msisdn
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-' && *c != '.')
+            .collect();
+        match self {
+            MsisdnPolicy::KeepPlus => msisdn,
+            MsisdnPolicy::StripPlus => msisdn.trim_start_matches('+').to_string(),
+        }
+    }
+}
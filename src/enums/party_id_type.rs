@@ -23,4 +23,14 @@ impl fmt::Display for PartyIdType {
             PartyIdType::PARTYCODE => write!(f, "PARTY_CODE"),
         }
     }
-}
\ No newline at end of file
+}
+impl PartyIdType {
+    /// The account holder id type MTN expects in the accountholder paths, e.g. "msisdn"
+    pub fn account_holder_id_type(&self) -> &'static str {
+        match *self {
+            PartyIdType::MSISDN => "msisdn",
+            PartyIdType::EMAIL => "email",
+            PartyIdType::PARTYCODE => "party_code",
+        }
+    }
+}
@@ -27,4 +27,90 @@ pub enum RequestToPayReason {
     SERVICEUNAVAILABLE,
      #[serde(rename = "COULD_NOT_PERFORM_TRANSACTION")]
     COULDNOTPERFORMTRANSACTION,
+    /// A reason code this crate does not know yet, so that a new code MTN starts sending does not
+    /// fail the parsing of the whole callback
+    #[serde(other)]
+    Unknown,
+}
+
+impl RequestToPayReason {
+    /// true when the same request may succeed if it is sent again later: MTN or the payer's
+    /// account could not process it at the time
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            RequestToPayReason::InternalProcessingError
+            | RequestToPayReason::PAYERDELAYED
+            | RequestToPayReason::SERVICEUNAVAILABLE
+            | RequestToPayReason::COULDNOTPERFORMTRANSACTION => true,
+            RequestToPayReason::APPROVALREJECTED
+            | RequestToPayReason::EXPIRED
+            | RequestToPayReason::ONGOING
+            | RequestToPayReason::PAYERNOTFOUND
+            | RequestToPayReason::PAYEENOTALLOWEDTORECEIVE
+            | RequestToPayReason::NOTALLOWED
+            | RequestToPayReason::NOTALLOWEDTARGETENVIRONMENT
+            | RequestToPayReason::INVALIDCALLBACKURLHOST
+            | RequestToPayReason::INVALIDCURRENCY
+            | RequestToPayReason::Unknown => false,
+        }
+    }
+
+    /// true when sending the same request again fails the same way, e.g. the payer rejected it
+    /// or the currency is not valid
+    ///
+    /// ONGOING is neither retryable nor terminal: the transaction is still being processed, its
+    /// status is to be checked again rather than the request sent again. Neither is an unknown
+    /// reason.
+    pub fn is_terminal(&self) -> bool {
+        !self.is_retryable()
+            && !matches!(
+                self,
+                RequestToPayReason::ONGOING | RequestToPayReason::Unknown
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classification() {
+        let retryable = [
+            RequestToPayReason::InternalProcessingError,
+            RequestToPayReason::PAYERDELAYED,
+            RequestToPayReason::SERVICEUNAVAILABLE,
+            RequestToPayReason::COULDNOTPERFORMTRANSACTION,
+        ];
+        let terminal = [
+            RequestToPayReason::APPROVALREJECTED,
+            RequestToPayReason::EXPIRED,
+            RequestToPayReason::PAYERNOTFOUND,
+            RequestToPayReason::PAYEENOTALLOWEDTORECEIVE,
+            RequestToPayReason::NOTALLOWED,
+            RequestToPayReason::NOTALLOWEDTARGETENVIRONMENT,
+            RequestToPayReason::INVALIDCALLBACKURLHOST,
+            RequestToPayReason::INVALIDCURRENCY,
+        ];
+        for reason in retryable {
+            assert!(reason.is_retryable(), "{:?}", reason);
+            assert!(!reason.is_terminal(), "{:?}", reason);
+        }
+        for reason in terminal {
+            assert!(!reason.is_retryable(), "{:?}", reason);
+            assert!(reason.is_terminal(), "{:?}", reason);
+        }
+        for reason in [RequestToPayReason::ONGOING, RequestToPayReason::Unknown] {
+            assert!(!reason.is_retryable(), "{:?}", reason);
+            assert!(!reason.is_terminal(), "{:?}", reason);
+        }
+    }
+
+    #[test]
+    fn test_unknown_reason() {
+        let reason: RequestToPayReason = serde_json::from_str(r#""PAYER_ON_HOLIDAY""#).unwrap();
+        assert_eq!(reason, RequestToPayReason::Unknown);
+        let reason: RequestToPayReason = serde_json::from_str(r#""PAYER_NOT_FOUND""#).unwrap();
+        assert_eq!(reason, RequestToPayReason::PAYERNOTFOUND);
+    }
 }
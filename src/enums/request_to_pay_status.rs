@@ -3,6 +3,20 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
 pub enum RequestToPayStatus {
+    PENDING,
+    #[serde(rename = "SUCCESSFUL", alias = "SUCCESSFULL")]
     SUCCESSFULL,
     FAILED,
+    UNKNOWN,
+}
+
+impl RequestToPayStatus {
+    pub fn from_string(s: &str) -> RequestToPayStatus {
+        match s {
+            "PENDING" => RequestToPayStatus::PENDING,
+            "SUCCESSFUL" => RequestToPayStatus::SUCCESSFULL,
+            "FAILED" => RequestToPayStatus::FAILED,
+            _ => RequestToPayStatus::UNKNOWN,
+        }
+    }
 }
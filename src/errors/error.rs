@@ -1,12 +1,109 @@
+use std::fmt;
 
+use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorReason {
+    pub code: String,
+    pub message: String,
+}
 
-use serde::{Serialize, Deserialize};
+/// Returned by `Collection::request_to_pay` when a request to pay with the same external id
+/// already went through or is being sent, see `IdempotencyStore`. Nothing was sent to MTN.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateReference {
+    pub external_id: String,
+    pub transaction_id: String, // the transaction id of the request that went through
+}
 
+impl fmt::Display for DuplicateReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a request with external id {} was already sent as transaction {}",
+            self.external_id, self.transaction_id
+        )
+    }
+}
 
+impl std::error::Error for DuplicateReference {}
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ErrorReason {
-    pub code: String,
-    pub message: String
-}
\ No newline at end of file
+/// Returned by `parse_callback` when a callback body does not match the callbacks expected on
+/// its path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackParseError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for CallbackParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "could not parse callback sent to {}: {}",
+            self.path, self.message
+        )
+    }
+}
+
+impl std::error::Error for CallbackParseError {}
+
+/// Returned by the sandbox provisioning, `Provisioning::create_sandox`,
+/// `Provisioning::create_api_information` and `Momo::new_with_provisioning`, when MTN refuses to
+/// create the api user or its api key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProvisioningError {
+    /// The reference id of the api user is not a UUID, which MTN requires, nothing was sent
+    InvalidReferenceId { reference_id: String },
+    /// An api user with this reference id already exists, MTN answered 409 Conflict
+    UserAlreadyExists { reference_id: String },
+    /// The provider callback host is not a bare host name, or MTN refused it with 400 Bad Request
+    /// naming the host
+    CallbackHostRejected {
+        provider_callback_host: String,
+        message: String,
+    },
+    /// MTN refused to create the api user for another reason
+    UserCreationFailed { status: u16, message: String },
+    /// MTN refused to create the api key of the api user, e.g. 404 when the user does not exist
+    ApiKeyCreationFailed {
+        reference_id: String,
+        status: u16,
+        message: String,
+    },
+}
+
+impl fmt::Display for ProvisioningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProvisioningError::InvalidReferenceId { reference_id } => {
+                write!(f, "the reference id {} is not a UUID", reference_id)
+            }
+            ProvisioningError::UserAlreadyExists { reference_id } => {
+                write!(f, "the api user {} already exists", reference_id)
+            }
+            ProvisioningError::CallbackHostRejected {
+                provider_callback_host,
+                message,
+            } => write!(
+                f,
+                "the provider callback host {} was rejected: {}",
+                provider_callback_host, message
+            ),
+            ProvisioningError::UserCreationFailed { status, message } => {
+                write!(f, "the api user could not be created ({}): {}", status, message)
+            }
+            ProvisioningError::ApiKeyCreationFailed {
+                reference_id,
+                status,
+                message,
+            } => write!(
+                f,
+                "the api key of the api user {} could not be created ({}): {}",
+                reference_id, status, message
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProvisioningError {}
@@ -4,9 +4,10 @@
 //! - Disbursements
 //! - Remittance
 //! - Provisioning in case of sandbox environment
+//!
 //! how to use:
 //! # Examples
-//! ```
+//! ```no_run
 //! use mtnmomo::Momo;
 //! use uuid::Uuid;
 //! use dotenv::dotenv;
@@ -18,7 +19,7 @@
 //!   let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set"); // https://sandbox.momodeveloper.mtn.com
 //!   let primary_key = env::var("MTN_COLLECTION_PRIMARY_KEY").expect("PRIMARY_KEY must be set");
 //!   let secondary_key = env::var("MTN_COLLECTION_SECONDARY_KEY").expect("SECONDARY_KEY must be set");
-//!   let momo = Momo::new_with_provisioning(mtn_url, primary_key.clone()).await.unwrap();
+//!   let momo = Momo::new_with_provisioning(mtn_url, primary_key.clone(), "webhook.site").await.unwrap();
 //!   let collection = momo.collection(primary_key, secondary_key);
 //! }
 //!
@@ -38,7 +39,7 @@
 //!
 //! If you want to request a payment from a customer, you can use the request_to_pay method of the Collection product.
 //!
-//! ```
+//! ```no_run
 //! use mtnmomo::{Momo, Party, PartyIdType, Currency, RequestToPay};
 //! use uuid::Uuid;
 //! use dotenv::dotenv;
@@ -50,7 +51,7 @@
 //!   let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set"); // https://sandbox.momodeveloper.mtn.com
 //!   let primary_key = env::var("MTN_COLLECTION_PRIMARY_KEY").expect("PRIMARY_KEY must be set");
 //!   let secondary_key = env::var("MTN_COLLECTION_SECONDARY_KEY").expect("SECONDARY_KEY must be set");
-//!   let momo = Momo::new_with_provisioning(mtn_url, primary_key.clone()).await.unwrap();
+//!   let momo = Momo::new_with_provisioning(mtn_url, primary_key.clone(), "webhook.site").await.unwrap();
 //!   let collection = momo.collection(primary_key, secondary_key);
 //!
 //!    let payer : Party = Party {
@@ -59,56 +60,141 @@
 //!      };
 //!
 //!   let request = RequestToPay::new("100".to_string(), Currency::EUR, payer, "test_payer_message".to_string(), "test_payee_note".to_string());
-//!   let result = collection.request_to_pay(request).await;
+//!   let result = collection.request_to_pay(request, None).await;
 //! }
 //! ```
 //! The above code will request a payment of 100 EUR from the customer with the phone number "234553".
 //! The customer will receive a prompt on their phone to confirm the payment.
 //! If the customer confirms the payment, the payment will be processed and the customer will receive a confirmation message.
 //! If the customer declines the payment, the payment will not be processed and the customer will receive a message informing them that the payment was declined.
+//!
+//! # TLS
+//!
+//! The HTTP client and the TLS listener of the callback server use rustls (`rustls-tls`, the
+//! default) or the platform TLS library (`native-tls`):
+//! `mtnmomo = { version = "*", default-features = false, features = ["callback-server", "native-tls"] }`.
+//! With `native-tls` the callback server reads its certificate from a PKCS#12 archive, see
+//! `TlsCertificate`.
+//! Without either feature the client only speaks plain `http`, e.g. to a local mock server, and
+//! the callback server refuses TLS bindings.
+//!
+//! # WebAssembly
+//!
+//! The callback server (`MomoCallbackListener`) and `CallbackRouter` are behind the default
+//! `callback-server` feature. Without it the products build for `wasm32-unknown-unknown`:
+//! `cargo build --lib --no-default-features --target wasm32-unknown-unknown`.
+//!
+//! # Testing
+//!
+//! The `mock` feature adds `mock::MockMomoServer`, a local server answering like the MTN API,
+//! to test an application without the sandbox.
 
 use futures_core::Stream;
 #[doc(hidden)]
 use std::error::Error;
-use tokio::sync::mpsc::{self, Sender};
-
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "callback-server")]
+use std::sync::Arc;
+#[cfg(feature = "callback-server")]
+use tokio::sync::mpsc::{self, error::TrySendError, Receiver, Sender};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
 use enums::{reason::RequestToPayReason, request_to_pay_status::RequestToPayStatus};
+#[cfg(all(
+    feature = "callback-server",
+    feature = "native-tls",
+    not(feature = "rustls-tls")
+))]
+use poem::listener::NativeTlsConfig;
+#[cfg(all(feature = "callback-server", feature = "rustls-tls"))]
+use poem::listener::{RustlsCertificate, RustlsConfig};
+#[cfg(feature = "callback-server")]
 use poem::{
-    error::ReadBodyError,
-    listener::TcpListener,
-    middleware::AddData,
+    get,
+    listener::{Listener, TcpListener},
     post,
-    web::{Data, Path},
+    web::Data,
     EndpointExt,
 };
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "callback-server")]
+use tracing::Instrument;
 use uuid::Uuid;
 
+#[cfg(feature = "callback-server")]
 use poem::Result;
+#[cfg(feature = "callback-server")]
 #[doc(hidden)]
 use poem::{handler, Route, Server};
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod callback_paths;
+pub mod callbacks;
 pub mod enums;
 pub mod errors;
+pub mod metrics;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod products;
+pub mod redaction;
 pub mod requests;
 pub mod responses;
 pub mod structs;
 
 pub type PartyIdType = enums::party_id_type::PartyIdType;
 pub type Currency = enums::currency::Currency;
+pub type DisbursementOperation = enums::disbursement_operation::DisbursementOperation;
 pub type Environment = enums::environment::Environment;
 pub type AccessType = enums::access_type::AccessType;
 pub type CallbackType = enums::callback_type::CallbackType;
+pub type InvoiceStatus = enums::invoice_status::InvoiceStatus;
+pub type MsisdnPolicy = enums::msisdn_policy::MsisdnPolicy;
+
+#[cfg(feature = "callback-server")]
+pub type CallbackRouter = callbacks::router::CallbackRouter;
+#[cfg(feature = "callback-server")]
+pub type CallbackServerConfig = callbacks::server_config::CallbackServerConfig;
+#[cfg(feature = "callback-server")]
+pub type SocketBinding = callbacks::server_config::SocketBinding;
+#[cfg(feature = "callback-server")]
+pub type TlsCertificate = callbacks::server_config::TlsCertificate;
+#[cfg(feature = "callback-server")]
+pub type CorsConfig = callbacks::server_config::CorsConfig;
+#[cfg(feature = "callback-server")]
+pub type ShutdownHandle = callbacks::server_config::ShutdownHandle;
+#[cfg(feature = "callback-server")]
+pub type CallbackStream = callbacks::callback_stream::CallbackStream;
+#[cfg(feature = "callback-server")]
+pub type CallbackBroadcast = callbacks::callback_stream::CallbackBroadcast;
+#[cfg(feature = "dead-letter-file")]
+pub use callbacks::dead_letter::FileDeadLetterSink;
+#[cfg(feature = "callback-server")]
+pub use callbacks::dead_letter::{DeadLetter, DeadLetterReason, DeadLetterSink};
+#[cfg(feature = "callback-server")]
+pub use callbacks::readiness::ReadinessCheck;
+#[cfg(feature = "callback-server")]
+pub use callbacks::sink::{CallbackSink, NoopCallbackSink};
+pub use callbacks::stream_ext::{CallbackWaiter, MomoUpdatesStreamExt};
 
 pub type Party = structs::party::Party;
 pub type Balance = structs::balance::Balance;
+pub type Balances = structs::balance::Balances;
 pub type Money = structs::money::Money;
+pub type TransactionEvent = structs::transaction_event::TransactionEvent;
+pub type ReferenceId = structs::reference_id::ReferenceId;
+pub type ApiCredentials = structs::api_credentials::ApiCredentials;
+pub type Submitted<T> = structs::submitted::Submitted<T>;
+pub type SubmissionStatus = structs::submitted::SubmissionStatus;
+pub use rust_decimal::Decimal;
 
 // Requests
 pub type RequestToPay = requests::request_to_pay::RequestToPay;
 pub type RefundRequest = requests::refund::Refund;
 pub type TransferRequest = requests::transfer::Transfer;
+pub type RefundRequestBuilder = requests::refund::RefundBuilder;
+pub type TransferRequestBuilder = requests::transfer::TransferBuilder;
 pub type CashTransferRequest = requests::cash_transfer::CashTransferRequest;
 pub type InvoiceRequest = requests::invoice::InvoiceRequest;
 pub type DeleteInvoiceRequest = requests::invoice_delete::InvoiceDelete;
@@ -124,6 +210,14 @@ pub type MomoCollection = products::collection::Collection;
 pub type MomoRemittance = products::remittance::Remittance;
 pub type MomoDisbursements = products::disbursements::Disbursements;
 pub type MomoProvisioning = products::provisioning::Provisioning;
+#[cfg(not(target_arch = "wasm32"))]
+pub type InMemoryIdempotencyStore = products::idempotency::InMemoryIdempotencyStore;
+pub use products::idempotency::IdempotencyStore;
+pub type InMemoryInvoiceStore = products::invoice_store::InMemoryInvoiceStore;
+pub use products::invoice_store::InvoiceStore;
+pub type DuplicateReference = errors::error::DuplicateReference;
+pub type CallbackParseError = errors::error::CallbackParseError;
+pub type ProvisioningError = errors::error::ProvisioningError;
 
 // Responses
 pub type TokenResponse = responses::token_response::TokenResponse;
@@ -133,27 +227,58 @@ pub type BasicUserInfoJsonResponse = responses::account_info::BasicUserInfoJsonR
 pub type InvoiceResult = responses::invoice::InvoiceResult;
 pub type PaymentResult = responses::payment_result::PaymentResult;
 pub type PreApprovalResult = responses::pre_approval::PreApprovalResult;
+pub type PreApprovalCreated = responses::pre_approval::PreApprovalCreated;
 pub type RequestToPayResult = responses::request_to_pay_result::RequestToPayResult;
 pub type CashTransferResult = responses::cash_transfer_result::CashTransferResult;
 pub type TransferResult = responses::transfer_result::TransferResult;
+pub type DepositResult = responses::transfer_result::DepositResult;
+
+/// # MomoId
+/// The ids returned by the products, so that code can be written for any of them
+///
+/// Every id prints as, and parses from, the id MTN knows the transaction by.
+pub trait MomoId: fmt::Display + FromStr {
+    /// The id, borrowed
+    fn as_str(&self) -> &str;
+
+    /// The id, taking ownership of it
+    fn into_string(self) -> String;
+}
+
+macro_rules! momo_id {
+    ($id:ident) => {
+        impl MomoId for $id {
+            fn as_str(&self) -> &str {
+                self.0.as_str()
+            }
 
-#[derive(thiserror::Error, Debug)]
-enum MomoError {
-    #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+            fn into_string(self) -> String {
+                self.0
+            }
+        }
 
-    #[error("ReadBody error: {0}")]
-    ReadBody(#[from] ReadBodyError),
+        impl fmt::Display for $id {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
 
-    #[error("SerdeJson error: {0}")]
-    JsonError(#[from] serde_json::Error),
+        impl FromStr for $id {
+            type Err = std::convert::Infallible;
 
-    #[error("SendError error: {0}")]
-    SendError(#[from] tokio::sync::mpsc::error::SendError<MomoUpdates>),
+            fn from_str(id: &str) -> Result<Self, Self::Err> {
+                Ok($id(id.to_string()))
+            }
+        }
+    };
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TranserId(String);
 
+/// `TranserId`, correctly spelled
+pub type TransferId = TranserId;
+
 impl TranserId {
     pub fn as_string(&self) -> String {
         self.0.clone()
@@ -164,6 +289,7 @@ impl TranserId {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TransactionId(String);
 
 impl TransactionId {
@@ -179,6 +305,7 @@ impl TransactionId {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RefundId(String);
 
 impl RefundId {
@@ -191,6 +318,7 @@ impl RefundId {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct InvoiceId(String);
 
 impl InvoiceId {
@@ -203,6 +331,7 @@ impl InvoiceId {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PaymentId(String);
 
 impl PaymentId {
@@ -215,6 +344,7 @@ impl PaymentId {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct WithdrawId(String);
 
 impl WithdrawId {
@@ -227,6 +357,7 @@ impl WithdrawId {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DepositId(String);
 
 impl DepositId {
@@ -239,17 +370,30 @@ impl DepositId {
     }
 }
 
+momo_id!(TranserId);
+momo_id!(TransactionId);
+momo_id!(RefundId);
+momo_id!(InvoiceId);
+momo_id!(PaymentId);
+momo_id!(WithdrawId);
+momo_id!(DepositId);
+
 /// MTN momo error Reason
 ///
 /// - 'code', Reason error code
 /// - 'message', Reason message
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Reason {
     pub code: RequestToPayReason,
     pub message: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// A callback sent by MTN, see `CallbackResponse::from_route_and_body`
+///
+/// Fields MTN does not always send, e.g. the payer message and payee note, or the financial
+/// transaction id of a failed transaction, are `Option`s, None when missing or null. Fields MTN
+/// adds are ignored: unknown fields are never denied, so a new field does not fail parsing.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum CallbackResponse {
     // Request to pay success callback response
     RequestToPaySuccess {
@@ -261,25 +405,25 @@ pub enum CallbackResponse {
         currency: String,
         payer: Party,
         #[serde(rename = "payeeNote")]
-        payee_note: String,
+        payee_note: Option<String>,
         #[serde(rename = "payerMessage")]
-        payer_message: String,
+        payer_message: Option<String>,
         status: RequestToPayStatus,
     },
 
     // Request to pay failed callback response
     RequestToPayFailed {
         #[serde(rename = "financialTransactionId")]
-        financial_transaction_id: String,
+        financial_transaction_id: Option<String>,
         #[serde(rename = "externalId")]
         external_id: String,
         amount: String,
         currency: String,
         payer: Party,
         #[serde(rename = "payeeNote")]
-        payee_note: String,
+        payee_note: Option<String>,
         #[serde(rename = "payerMessage")]
-        payer_message: String,
+        payer_message: Option<String>,
         status: RequestToPayStatus,
         reason: Reason,
     },
@@ -320,7 +464,7 @@ pub enum CallbackResponse {
         reference_id: String,
         status: String,
         #[serde(rename = "financialTransactionId")]
-        financial_transaction_id: String,
+        financial_transaction_id: Option<String>,
         reason: Reason,
     },
 
@@ -341,7 +485,7 @@ pub enum CallbackResponse {
         expiry_date_time: String,
         #[serde(rename = "intendedPayer")]
         intended_payer: Party,
-        description: String,
+        description: Option<String>,
     },
 
     // invoice failed callback response
@@ -361,7 +505,7 @@ pub enum CallbackResponse {
         expiry_date_time: String,
         #[serde(rename = "intendedPayer")]
         intended_payer: Party,
-        description: String,
+        description: Option<String>,
         #[serde(rename = "errorReason")]
         erron_reason: Reason,
     },
@@ -371,7 +515,7 @@ pub enum CallbackResponse {
         #[serde(rename = "financialTransactionId")]
         financial_transaction_id: String,
         status: String,
-        reason: String,
+        reason: Option<String>,
         amount: String,
         currency: String,
         payee: Party,
@@ -384,35 +528,35 @@ pub enum CallbackResponse {
         #[serde(rename = "originalCurrency")]
         original_currency: String,
         #[serde(rename = "payerMessage")]
-        payer_message: String,
+        payer_message: Option<String>,
         #[serde(rename = "payeeNote")]
-        payee_note: String,
+        payee_note: Option<String>,
         #[serde(rename = "payerIdentificationType")]
-        payer_identification_type: String,
+        payer_identification_type: Option<String>,
         #[serde(rename = "payerIdentificationNumber")]
-        payer_identification_number: String,
+        payer_identification_number: Option<String>,
         #[serde(rename = "payerIdentity")]
-        payer_identity: String,
+        payer_identity: Option<String>,
         #[serde(rename = "payerFirstName")]
-        payer_first_name: String,
+        payer_first_name: Option<String>,
         #[serde(rename = "payerSurname")]
-        payer_surname: String,
+        payer_surname: Option<String>,
         #[serde(rename = "payerLanguageCode")]
-        payer_language_code: String,
+        payer_language_code: Option<String>,
         #[serde(rename = "payerEmail")]
-        payer_email: String,
+        payer_email: Option<String>,
         #[serde(rename = "payerMsisdn")]
-        payer_msisdn: String,
+        payer_msisdn: Option<String>,
         #[serde(rename = "payerGender")]
-        payer_gender: String,
+        payer_gender: Option<String>,
     },
 
     // cash trasnfer failed callaback response
     CashTransferFailed {
         #[serde(rename = "financialTransactionId")]
-        financial_transaction_id: String,
+        financial_transaction_id: Option<String>,
         status: String,
-        reason: String,
+        reason: Option<String>,
         amount: String,
         currency: String,
         payee: Party,
@@ -425,178 +569,1067 @@ pub enum CallbackResponse {
         #[serde(rename = "originalCurrency")]
         original_currency: String,
         #[serde(rename = "payerMessage")]
-        payer_message: String,
+        payer_message: Option<String>,
         #[serde(rename = "payeeNote")]
-        payee_note: String,
+        payee_note: Option<String>,
         #[serde(rename = "payerIdentificationType")]
-        payer_identification_type: String,
+        payer_identification_type: Option<String>,
         #[serde(rename = "payerIdentificationNumber")]
-        payer_identification_number: String,
+        payer_identification_number: Option<String>,
         #[serde(rename = "payerIdentity")]
-        payer_identity: String,
+        payer_identity: Option<String>,
         #[serde(rename = "payerFirstName")]
-        payer_first_name: String,
+        payer_first_name: Option<String>,
         #[serde(rename = "payerSurname")]
-        payer_surname: String,
+        payer_surname: Option<String>,
         #[serde(rename = "payerLanguageCode")]
-        payer_language_code: String,
+        payer_language_code: Option<String>,
         #[serde(rename = "payerEmail")]
-        payer_email: String,
+        payer_email: Option<String>,
         #[serde(rename = "payerMsisdn")]
-        payer_msisdn: String,
+        payer_msisdn: Option<String>,
         #[serde(rename = "payerGender")]
-        payer_gender: String,
+        payer_gender: Option<String>,
 
         #[serde(rename = "errorReason")]
         error_reason: Reason,
     },
+
+    // deposit, refund and transfer succeeded callback response, v1 and v2 send the same body
+    DisbursementSuccess {
+        #[serde(rename = "financialTransactionId")]
+        financial_transaction_id: String,
+        #[serde(rename = "externalId")]
+        external_id: String,
+        amount: String,
+        currency: String,
+        payee: Party,
+        #[serde(rename = "payerMessage")]
+        payer_message: Option<String>,
+        #[serde(rename = "payeeNote")]
+        payee_note: Option<String>,
+        status: String,
+    },
+
+    // deposit, refund and transfer failed callback response
+    DisbursementFailed {
+        #[serde(rename = "financialTransactionId")]
+        financial_transaction_id: Option<String>,
+        #[serde(rename = "externalId")]
+        external_id: String,
+        amount: String,
+        currency: String,
+        payee: Party,
+        #[serde(rename = "payerMessage")]
+        payer_message: Option<String>,
+        #[serde(rename = "payeeNote")]
+        payee_note: Option<String>,
+        status: String,
+        reason: Reason,
+    },
+}
+
+/// Parse a MTN MoMo date time (ISO-8601) into a UTC timestamp.
+///
+/// MTN returns RFC 3339 timestamps, but some endpoints omit the offset, in which case the
+/// value is taken to be UTC. An empty string is an error.
+pub(crate) fn parse_momo_date_time(value: &str) -> Result<DateTime<Utc>, chrono::ParseError> {
+    match DateTime::parse_from_rfc3339(value) {
+        Ok(date_time) => Ok(date_time.with_timezone(&Utc)),
+        Err(err) => NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+            .map(|date_time| date_time.and_utc())
+            .map_err(|_| err),
+    }
+}
+
+impl CallbackResponse {
+    /// The expiry time carried by the callback, parsed as a UTC timestamp.
+    ///
+    /// # Returns
+    ///
+    /// * None, if the callback does not carry an expiry (only invoice and pre-approval callbacks do)
+    /// * Some(Err), if the expiry is empty or not a valid ISO-8601 date time
+    pub fn expires_at(&self) -> Option<Result<DateTime<Utc>, chrono::ParseError>> {
+        match self {
+            CallbackResponse::PreApprovalSuccess {
+                expiration_date_time,
+                ..
+            }
+            | CallbackResponse::PreApprovalFailed {
+                expiration_date_time,
+                ..
+            } => Some(parse_momo_date_time(expiration_date_time)),
+            CallbackResponse::InvoiceSucceeded {
+                expiry_date_time, ..
+            }
+            | CallbackResponse::InvoiceFailed {
+                expiry_date_time, ..
+            } => Some(parse_momo_date_time(expiry_date_time)),
+            _ => None,
+        }
+    }
+
+    /// Parse a callback body knowing the route it was sent to.
+    ///
+    /// MTN sends the bare callback object, so the route decides which variants the body may be
+    /// read as; the failed variant is tried before the successful one as it carries the extra
+    /// reason field. Bodies already tagged with one of the route's variants are accepted too.
+    /// For an unknown route (`CallbackType::None`) the body is parsed as a tagged variant.
+    ///
+    /// # Parameters
+    ///
+    /// * 'route', the kind of callback, from the path it was sent to
+    /// * 'body', the body of the callback
+    ///
+    /// # Returns
+    ///
+    /// * 'CallbackResponse', an error if the body matches none of the route's variants
+    pub fn from_route_and_body(
+        route: CallbackType,
+        body: &str,
+    ) -> Result<CallbackResponse, serde_json::Error> {
+        let value: serde_json::Value = serde_json::from_str(body)?;
+        let variants: &[&str] = match route {
+            CallbackType::RequestToPay
+            | CallbackType::RequestToWithdrawV1
+            | CallbackType::RequestToWithdrawV2 => &["RequestToPayFailed", "RequestToPaySuccess"],
+            CallbackType::Invoice => &["InvoiceFailed", "InvoiceSucceeded"],
+            CallbackType::CollectionPayment => &["PaymentFailed", "PaymentSucceeded"],
+            CallbackType::CollectionPreApproval => &["PreApprovalFailed", "PreApprovalSuccess"],
+            CallbackType::RemittanceCashTransfer => {
+                &["CashTransferFailed", "CashTransferSucceeded"]
+            }
+            CallbackType::DisbursementDepositV1
+            | CallbackType::DisbursementDepositV2
+            | CallbackType::DisbursementRefundV1
+            | CallbackType::DisbursementRefundV2
+            | CallbackType::DisbusrementTransfer
+            | CallbackType::RemittanceTransfer => &["DisbursementFailed", "DisbursementSuccess"],
+            CallbackType::None => return serde_json::from_value(value),
+        };
+
+        let tag = value
+            .as_object()
+            .filter(|object| object.len() == 1)
+            .and_then(|object| object.keys().next());
+        if tag.is_some_and(|tag| variants.contains(&tag.as_str())) {
+            return serde_json::from_value(value);
+        }
+
+        let mut last_error = None;
+        for variant in variants {
+            let tagged = serde_json::json!({ *variant: value.clone() });
+            match serde_json::from_value(tagged) {
+                Ok(response) => return Ok(response),
+                Err(err) => last_error = Some(err),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            serde::de::Error::custom(format!("no callback is known for {}", route))
+        }))
+    }
+
+    /// The name of the variant, e.g. "RequestToPaySuccess", to log a callback without its
+    /// payer details
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            CallbackResponse::RequestToPaySuccess { .. } => "RequestToPaySuccess",
+            CallbackResponse::RequestToPayFailed { .. } => "RequestToPayFailed",
+            CallbackResponse::PreApprovalSuccess { .. } => "PreApprovalSuccess",
+            CallbackResponse::PreApprovalFailed { .. } => "PreApprovalFailed",
+            CallbackResponse::PaymentSucceeded { .. } => "PaymentSucceeded",
+            CallbackResponse::PaymentFailed { .. } => "PaymentFailed",
+            CallbackResponse::InvoiceSucceeded { .. } => "InvoiceSucceeded",
+            CallbackResponse::InvoiceFailed { .. } => "InvoiceFailed",
+            CallbackResponse::CashTransferSucceeded { .. } => "CashTransferSucceeded",
+            CallbackResponse::CashTransferFailed { .. } => "CashTransferFailed",
+            CallbackResponse::DisbursementSuccess { .. } => "DisbursementSuccess",
+            CallbackResponse::DisbursementFailed { .. } => "DisbursementFailed",
+        }
+    }
+
+    /// The external id of the transaction the callback refers to, if the callback carries one
+    pub fn external_id(&self) -> Option<&str> {
+        match self {
+            CallbackResponse::RequestToPaySuccess { external_id, .. }
+            | CallbackResponse::RequestToPayFailed { external_id, .. }
+            | CallbackResponse::InvoiceSucceeded { external_id, .. }
+            | CallbackResponse::InvoiceFailed { external_id, .. }
+            | CallbackResponse::CashTransferSucceeded { external_id, .. }
+            | CallbackResponse::CashTransferFailed { external_id, .. }
+            | CallbackResponse::DisbursementSuccess { external_id, .. }
+            | CallbackResponse::DisbursementFailed { external_id, .. } => Some(external_id),
+            CallbackResponse::PreApprovalSuccess { .. }
+            | CallbackResponse::PreApprovalFailed { .. }
+            | CallbackResponse::PaymentSucceeded { .. }
+            | CallbackResponse::PaymentFailed { .. } => None,
+        }
+    }
+
+    /// The external id, amount, currency, status and reason of the transaction, for the callbacks
+    /// of requests to pay, invoices, cash transfers and disbursements. None for the pre-approvals
+    /// and payments, which carry no amount.
+    pub fn transaction_event(&self) -> Option<TransactionEvent> {
+        // the names MTN sends, e.g. SUCCESSFUL and PAYER_NOT_FOUND
+        fn serde_name<T: Serialize>(value: &T) -> String {
+            serde_json::to_value(value)
+                .ok()
+                .and_then(|value| value.as_str().map(str::to_string))
+                .unwrap_or_default()
+        }
+        let event = |external_id: &str,
+                     amount: &str,
+                     currency: &str,
+                     status: String,
+                     reason: Option<String>| TransactionEvent {
+            external_id: external_id.to_string(),
+            amount: amount.to_string(),
+            currency: currency.to_string(),
+            status,
+            reason,
+        };
+        match self {
+            CallbackResponse::RequestToPaySuccess {
+                external_id,
+                amount,
+                currency,
+                status,
+                ..
+            } => Some(event(
+                external_id,
+                amount,
+                currency,
+                serde_name(status),
+                None,
+            )),
+            CallbackResponse::RequestToPayFailed {
+                external_id,
+                amount,
+                currency,
+                status,
+                reason,
+                ..
+            } => Some(event(
+                external_id,
+                amount,
+                currency,
+                serde_name(status),
+                Some(serde_name(&reason.code)),
+            )),
+            CallbackResponse::InvoiceSucceeded {
+                external_id,
+                amount,
+                currency,
+                status,
+                ..
+            }
+            | CallbackResponse::DisbursementSuccess {
+                external_id,
+                amount,
+                currency,
+                status,
+                ..
+            } => Some(event(external_id, amount, currency, status.clone(), None)),
+            CallbackResponse::InvoiceFailed {
+                external_id,
+                amount,
+                currency,
+                status,
+                erron_reason: reason,
+                ..
+            }
+            | CallbackResponse::DisbursementFailed {
+                external_id,
+                amount,
+                currency,
+                status,
+                reason,
+                ..
+            } => Some(event(
+                external_id,
+                amount,
+                currency,
+                status.clone(),
+                Some(serde_name(&reason.code)),
+            )),
+            CallbackResponse::CashTransferSucceeded {
+                external_id,
+                amount,
+                currency,
+                status,
+                reason,
+                ..
+            }
+            | CallbackResponse::CashTransferFailed {
+                external_id,
+                amount,
+                currency,
+                status,
+                reason,
+                ..
+            } => Some(event(
+                external_id,
+                amount,
+                currency,
+                status.clone(),
+                reason.clone().filter(|reason| !reason.is_empty()),
+            )),
+            CallbackResponse::PreApprovalSuccess { .. }
+            | CallbackResponse::PreApprovalFailed { .. }
+            | CallbackResponse::PaymentSucceeded { .. }
+            | CallbackResponse::PaymentFailed { .. } => None,
+        }
+    }
+
+    /// true when the callback reports a successful transaction
+    pub fn status_is_success(&self) -> bool {
+        match self {
+            CallbackResponse::RequestToPaySuccess { .. }
+            | CallbackResponse::PreApprovalSuccess { .. }
+            | CallbackResponse::PaymentSucceeded { .. }
+            | CallbackResponse::InvoiceSucceeded { .. }
+            | CallbackResponse::CashTransferSucceeded { .. }
+            | CallbackResponse::DisbursementSuccess { .. } => true,
+            CallbackResponse::RequestToPayFailed { .. }
+            | CallbackResponse::PreApprovalFailed { .. }
+            | CallbackResponse::PaymentFailed { .. }
+            | CallbackResponse::InvoiceFailed { .. }
+            | CallbackResponse::CashTransferFailed { .. }
+            | CallbackResponse::DisbursementFailed { .. } => false,
+        }
+    }
+}
+
+impl From<CallbackResponse> for Option<TransactionEvent> {
+    fn from(response: CallbackResponse) -> Self {
+        response.transaction_event()
+    }
 }
 
+/// # MomoUpdates
+/// A callback received from MTN
+///
+/// - 'remote_address', the address the callback came from
+/// - 'raw_body', the body of the callback, as received
+/// - 'response', the parsed callback, None when the body does not match any known callback
+///   (for example a new field or status MTN started sending), `raw_body` still holds it
+/// - 'parse_error', why the body could not be parsed, including the path it was sent to
+/// - 'update_type', the kind of callback, from the route it was received on, see
+///   `CallbackType::from_path`
+#[derive(Debug, Clone)]
 pub struct MomoUpdates {
     pub remote_address: String,
-    pub response: CallbackResponse,
+    pub raw_body: String,
+    pub response: Option<CallbackResponse>,
+    pub parse_error: Option<String>,
     pub update_type: CallbackType,
 }
 
+/// Parse a callback the way the callback server does, e.g. to replay callbacks stored before
+///
+/// # Parameters
+///
+/// * 'path', the path the callback was sent to, e.g. "/collection_request_to_pay/REQUEST_TO_PAY",
+///   see `CallbackType::callback_url`
+/// * 'raw_body', the body of the callback, as received
+///
+/// # Returns
+///
+/// * 'MomoUpdates', the parsed callback, without a remote address
+pub fn parse_callback(path: &str, raw_body: &str) -> Result<MomoUpdates, CallbackParseError> {
+    parse_callback_as(CallbackType::from_path(path), path, raw_body)
+}
+
+impl MomoUpdates {
+    /// The operation a disbursement callback reports on, e.g. to tell a deposit from a transfer,
+    /// which send the same `CallbackResponse::DisbursementSuccess`
+    ///
+    /// # Returns
+    ///
+    /// * 'Option<DisbursementOperation>', None when the callback is not a disbursement callback
+    pub fn disbursement_operation(&self) -> Option<DisbursementOperation> {
+        self.update_type.disbursement_operation()
+    }
+}
+
+/// `parse_callback`, with the kind of callback already known
+fn parse_callback_as(
+    update_type: CallbackType,
+    path: &str,
+    raw_body: &str,
+) -> Result<MomoUpdates, CallbackParseError> {
+    match CallbackResponse::from_route_and_body(update_type, raw_body) {
+        Ok(response) => Ok(MomoUpdates {
+            remote_address: String::new(),
+            raw_body: raw_body.to_string(),
+            response: Some(response),
+            parse_error: None,
+            update_type,
+        }),
+        Err(err) => Err(CallbackParseError {
+            path: path.to_string(),
+            message: err.to_string(),
+        }),
+    }
+}
+
+/// `parse_callback_as`, keeping a callback that does not parse with its parse error, as the
+/// callback server hands it over
+fn parse_callback_or_keep(update_type: CallbackType, path: &str, raw_body: String) -> MomoUpdates {
+    match parse_callback_as(update_type, path, &raw_body) {
+        Ok(momo_updates) => momo_updates,
+        Err(err) => MomoUpdates {
+            remote_address: String::new(),
+            raw_body,
+            response: None,
+            parse_error: Some(err.to_string()),
+            update_type,
+        },
+    }
+}
+
+/// Replay stored callbacks, e.g. dead letters or callbacks captured in production, as the stream
+/// of the callback server would have given them
+///
+/// The bodies are parsed like the callback server parses them, a body that does not parse is
+/// given with its `parse_error` set.
+///
+/// # Parameters
+///
+/// * 'update_type', the kind of the callbacks, e.g. `DeadLetter::update_type`
+/// * 'bodies', the bodies of the callbacks, as received
+///
+/// # Returns
+///
+/// * 'impl Stream<Item = MomoUpdates>', the callbacks, in the order of the bodies, without a
+///   remote address
+pub fn replay_callbacks(
+    update_type: CallbackType,
+    bodies: impl IntoIterator<Item = String>,
+) -> impl Stream<Item = MomoUpdates> {
+    let path = update_type.path();
+    futures_util::stream::iter(
+        bodies
+            .into_iter()
+            .map(move |raw_body| parse_callback_or_keep(update_type, &path, raw_body)),
+    )
+}
+
+/// The sending half of the callback channel shared by the callback handlers
+#[cfg(feature = "callback-server")]
+#[derive(Clone)]
+struct CallbackChannel {
+    sender: Sender<MomoUpdates>,
+    drop_when_full: bool,
+    log_bodies: bool,
+    sink: Option<Arc<dyn CallbackSink>>,
+    dead_letter: Option<Arc<dyn DeadLetterSink>>,
+    metrics_hooks: Option<Arc<dyn metrics::Metrics>>,
+    #[cfg(feature = "metrics")]
+    metrics: Arc<callbacks::metrics::CallbackMetrics>,
+}
+
+#[cfg(feature = "callback-server")]
+impl CallbackChannel {
+    /// Create the callback channel described by the configuration, its 'channel_capacity' must
+    /// be at least 1
+    fn new(
+        config: &CallbackServerConfig,
+    ) -> Result<(CallbackChannel, Receiver<MomoUpdates>), Box<dyn Error>> {
+        if config.channel_capacity == 0 {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "the channel_capacity of the callback server must be at least 1",
+            )));
+        }
+        let (sender, receiver) = mpsc::channel::<MomoUpdates>(config.channel_capacity);
+        Ok((CallbackChannel::with_sender(config, sender), receiver))
+    }
+
+    /// Create a callback channel sending to the given sender, its capacity is the sender's
+    fn with_sender(config: &CallbackServerConfig, sender: Sender<MomoUpdates>) -> CallbackChannel {
+        CallbackChannel {
+            sender,
+            drop_when_full: config.drop_when_full,
+            log_bodies: config.log_bodies,
+            sink: config.sink.clone(),
+            dead_letter: config.dead_letter.clone(),
+            metrics_hooks: config.metrics_hooks.clone(),
+            #[cfg(feature = "metrics")]
+            metrics: Arc::default(),
+        }
+    }
+
+    /// The body of a callback as it can be logged, redacted, or only its length when
+    /// `CallbackServerConfig::log_bodies` is not set
+    fn loggable_body(&self, body: &str) -> String {
+        if self.log_bodies {
+            redaction::redact_body(body)
+        } else {
+            format!("<{} bytes>", body.len())
+        }
+    }
+
+    /// Count a callback received on the given path, and tell the metrics hooks about it
+    #[allow(unused_variables)]
+    fn record_received(&self, path: &str, callback_type: CallbackType, parsed: bool) {
+        #[cfg(feature = "metrics")]
+        self.metrics.record_received(path, parsed);
+        if let Some(metrics_hooks) = &self.metrics_hooks {
+            metrics_hooks.on_callback(callback_type);
+        }
+    }
+
+    /// Store a callback in the sink, if there is one, before it is delivered
+    async fn store(&self, momo_updates: &MomoUpdates) {
+        if let Some(sink) = &self.sink {
+            if let Err(err) = sink.store(momo_updates).await {
+                tracing::error!(
+                    "could not store the {} callback from {}: {}",
+                    momo_updates.update_type,
+                    momo_updates.remote_address,
+                    err
+                );
+            }
+        }
+    }
+
+    /// Hand a callback over to the consumer of the stream
+    async fn deliver(&self, momo_updates: MomoUpdates) {
+        if !self.drop_when_full {
+            if let Err(err) = self.sender.send(momo_updates).await {
+                self.dead_letter(err.0, DeadLetterReason::ChannelClosed)
+                    .await;
+            }
+            return;
+        }
+        match self.sender.try_send(momo_updates) {
+            Ok(()) => {}
+            Err(TrySendError::Full(momo_updates)) => {
+                #[cfg(feature = "metrics")]
+                self.metrics.record_dropped();
+                tracing::warn!(
+                    "callback channel is full, dropping {} callback from {}",
+                    momo_updates.update_type,
+                    momo_updates.remote_address
+                );
+                self.dead_letter(momo_updates, DeadLetterReason::ChannelFull)
+                    .await;
+            }
+            Err(TrySendError::Closed(momo_updates)) => {
+                self.dead_letter(momo_updates, DeadLetterReason::ChannelClosed)
+                    .await;
+            }
+        }
+    }
+
+    /// Hand a callback the stream could not take over to the dead letter sink, if there is one
+    async fn dead_letter(&self, momo_updates: MomoUpdates, reason: DeadLetterReason) {
+        let Some(dead_letter) = &self.dead_letter else {
+            tracing::error!(
+                "{}, the {} callback from {} is lost",
+                reason,
+                momo_updates.update_type,
+                momo_updates.remote_address
+            );
+            return;
+        };
+        if let Err(err) = dead_letter.dead_letter(&momo_updates, reason).await {
+            tracing::error!(
+                "{} and the {} callback from {} could not be kept: {}",
+                reason,
+                momo_updates.update_type,
+                momo_updates.remote_address,
+                err
+            );
+        }
+    }
+}
+
+/// What the callback handlers answer to MTN
+#[cfg(feature = "callback-server")]
+#[derive(Clone)]
+struct CallbackAck {
+    status: poem::http::StatusCode,
+    body: String,
+}
+
+#[cfg(feature = "callback-server")]
+impl CallbackAck {
+    fn new(config: &CallbackServerConfig) -> Result<CallbackAck, Box<dyn Error>> {
+        Ok(CallbackAck {
+            status: poem::http::StatusCode::from_u16(config.ack_status_code)?,
+            body: config
+                .ack_response
+                .clone()
+                .unwrap_or_else(|| "Callback received successfully".to_string()),
+        })
+    }
+
+    fn response(&self) -> poem::Response {
+        poem::Response::builder()
+            .status(self.status)
+            .body(self.body.clone())
+    }
+}
+
+#[cfg(feature = "callback-server")]
 #[handler]
 async fn mtn_callback(
     req: &poem::Request,
     mut body: poem::Body,
-    sender: Data<&Sender<MomoUpdates>>,
-    Path(callback_type): Path<String>,
+    channel: Data<&CallbackChannel>,
+    ack: Data<&CallbackAck>,
+    Data(update_type): Data<&CallbackType>,
 ) -> Result<poem::Response, poem::Error> {
     let remote_address = req.remote_addr().clone();
     let string = body.into_string().await?;
-    let response_result: Result<CallbackResponse, serde_json::Error> =
-        serde_json::from_str(&string);
-    if response_result.is_err() {}
-    let momo_updates = MomoUpdates {
-        remote_address: remote_address.to_string(),
-        response: response_result.unwrap(),
-        update_type: CallbackType::from_string(&callback_type),
-    };
-    let listener_update = sender.send(momo_updates).await;
-    if listener_update.is_err() {}
-    Ok(poem::Response::builder()
-        .status(poem::http::StatusCode::OK)
-        .body("Callback received successfully"))
+    let path = req.uri().path();
+    let mut momo_updates = parse_callback_or_keep(*update_type, path, string);
+    if let Some(err) = &momo_updates.parse_error {
+        tracing::warn!(
+            "{} (from {}): {}",
+            err,
+            remote_address,
+            channel.loggable_body(&momo_updates.raw_body)
+        );
+    }
+    momo_updates.remote_address = remote_address.to_string();
+    let span = tracing::info_span!(
+        "momo_callback",
+        callback_type = %update_type,
+        external_id = tracing::field::Empty
+    );
+    if let Some(external_id) = momo_updates
+        .response
+        .as_ref()
+        .and_then(CallbackResponse::external_id)
+    {
+        span.record("external_id", tracing::field::display(external_id));
+    }
+    channel.record_received(
+        path,
+        momo_updates.update_type,
+        momo_updates.response.is_some(),
+    );
+    async {
+        let response = momo_updates.response.as_ref();
+        tracing::info!(
+            "{} callback for {} received from {}",
+            response.map_or("unparsed", CallbackResponse::variant_name),
+            response
+                .and_then(CallbackResponse::external_id)
+                .unwrap_or("no external id"),
+            momo_updates.remote_address
+        );
+        if channel.log_bodies {
+            tracing::debug!(
+                "callback body: {}",
+                channel.loggable_body(&momo_updates.raw_body)
+            );
+        }
+        channel.store(&momo_updates).await;
+        channel.deliver(momo_updates).await;
+    }
+    .instrument(span)
+    .await;
+    Ok(ack.response())
 }
 
+#[cfg(feature = "callback-server")]
+#[derive(Clone)]
+struct ReadinessChecks(Vec<Arc<dyn ReadinessCheck>>);
+
+/// liveness, the process is up and serving requests
+#[cfg(feature = "callback-server")]
 #[handler]
-async fn mtn_put_calback(
-    req: &poem::Request,
-    mut body: poem::Body,
-    sender: Data<&Sender<MomoUpdates>>,
-    Path(callback_type): Path<String>,
-) -> Result<poem::Response, poem::Error> {
-    let remote_address = req.remote_addr().clone();
-    let string = body.into_string().await?;
-    let response_result: Result<CallbackResponse, serde_json::Error> =
-        serde_json::from_str(&string);
-    if response_result.is_err() {}
-    let momo_updates = MomoUpdates {
-        remote_address: remote_address.to_string(),
-        response: response_result.unwrap(),
-        update_type: CallbackType::from_string(&callback_type),
-    };
-    let listener_update = sender.send(momo_updates).await;
-    if listener_update.is_err() {}
-    Ok(poem::Response::builder()
+fn health() -> &'static str {
+    "OK"
+}
+
+/// readiness, every configured check passes (for example MTN hands out access tokens)
+#[cfg(feature = "callback-server")]
+#[handler]
+async fn ready(Data(checks): Data<&ReadinessChecks>) -> poem::Response {
+    for check in checks.0.iter() {
+        if let Err(reason) = check.check().await {
+            tracing::warn!("readiness check failed: {}", reason);
+            return poem::Response::builder()
+                .status(poem::http::StatusCode::SERVICE_UNAVAILABLE)
+                .content_type("application/json")
+                .body(serde_json::json!({"status": "unavailable", "reason": reason}).to_string());
+        }
+    }
+    poem::Response::builder()
         .status(poem::http::StatusCode::OK)
-        .body("Callback received successfully"))
+        .content_type("application/json")
+        .body(serde_json::json!({"status": "ready"}).to_string())
+}
+
+/// Prometheus metrics of the callback server
+#[cfg(feature = "metrics")]
+#[handler]
+fn prometheus_metrics(Data(channel): Data<&CallbackChannel>) -> poem::Response {
+    let depth = channel.sender.max_capacity() - channel.sender.capacity();
+    poem::Response::builder()
+        .content_type("text/plain; version=0.0.4")
+        .body(channel.metrics.render(depth))
 }
 
+#[cfg(feature = "callback-server")]
 #[derive(Copy, Clone)]
 pub struct MomoCallbackListener;
 
+/// The error of a TLS binding holding a kind of certificate the TLS feature does not serve
+///
+/// # Parameters
+///
+/// * 'port', the port of the binding
+/// * 'constructor', how to build the kind of certificate it serves, e.g. "TlsCertificate::pem"
+#[cfg(all(
+    feature = "callback-server",
+    any(feature = "rustls-tls", feature = "native-tls")
+))]
+fn wrong_certificate(port: u16, constructor: &str) -> Box<dyn Error> {
+    Box::new(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!(
+            "the TLS binding on port {} holds a kind of certificate the TLS feature does not \
+             serve, build it with `{}`",
+            port, constructor
+        ),
+    ))
+}
+
+#[cfg(feature = "callback-server")]
 impl MomoCallbackListener {
-    pub async fn serve(port: String) -> Result<impl Stream<Item = MomoUpdates>, Box<dyn Error>> {
+    /// Start the callback server on the given port, with the default configuration
+    ///
+    /// # Parameters
+    ///
+    /// * 'port', the port to listen on
+    ///
+    /// # Returns
+    ///
+    /// * 'CallbackStream', the stream of callbacks received by the server, which stops when the
+    ///   stream is dropped
+    pub async fn serve(port: String) -> Result<CallbackStream, Box<dyn Error>> {
+        let config = CallbackServerConfig {
+            http_port: port.parse()?,
+            ..Default::default()
+        };
+        Self::serve_with_config(config).await
+    }
+
+    /// Start the callback server
+    ///
+    /// # Parameters
+    ///
+    /// * 'config', the configuration of the server
+    ///
+    /// # Returns
+    ///
+    /// * 'CallbackStream', the stream of callbacks received by the server, which stops when the
+    ///   stream is dropped, see `CallbackStream::close` to stop it gracefully
+    ///
+    /// # Errors
+    ///
+    /// Every address is bound before the server is spawned, so an address that can't be bound is
+    /// returned here as a `std::io::Error`, e.g. of kind `AddrInUse` when the port is taken.
+    pub async fn serve_with_config(
+        config: CallbackServerConfig,
+    ) -> Result<CallbackStream, Box<dyn Error>> {
+        let (_, updates) = Self::serve_with_addresses(config).await?;
+        Ok(updates)
+    }
+
+    /// Same as `serve_with_config`, also returning the addresses the server listens on, e.g. the
+    /// port picked by the system when 'http_port' is 0
+    ///
+    /// # Parameters
+    ///
+    /// * 'config', the configuration of the server
+    ///
+    /// # Returns
+    ///
+    /// * 'Vec<SocketAddr>', the bound addresses, the one of 'host' and 'http_port' first, then the
+    ///   'additional_addresses' and the 'bindings'
+    /// * 'CallbackStream', the stream of callbacks received by the server, which stops when the
+    ///   stream is dropped
+    pub async fn serve_with_addresses(
+        config: CallbackServerConfig,
+    ) -> Result<(Vec<std::net::SocketAddr>, CallbackStream), Box<dyn Error>> {
+        let mut listener =
+            TcpListener::bind(format!("{}:{}", config.host, config.http_port)).boxed();
+        for address in config.additional_addresses.iter() {
+            listener = listener.combine(TcpListener::bind(*address)).boxed();
+        }
+        for binding in config.bindings.iter() {
+            let tcp = TcpListener::bind(format!("{}:{}", config.host, binding.port));
+            listener = match &binding.tls {
+                #[cfg(feature = "rustls-tls")]
+                Some(TlsCertificate::Pem { cert, key }) => {
+                    let certificate = RustlsCertificate::new().cert(cert.clone()).key(key.clone());
+                    listener
+                        .combine(tcp.rustls(RustlsConfig::new().fallback(certificate)))
+                        .boxed()
+                }
+                #[cfg(feature = "rustls-tls")]
+                Some(TlsCertificate::Pkcs12 { .. }) => {
+                    return Err(wrong_certificate(binding.port, "TlsCertificate::pem"))
+                }
+                #[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+                Some(TlsCertificate::Pkcs12 { archive, password }) => listener
+                    .combine(
+                        tcp.native_tls(
+                            NativeTlsConfig::new()
+                                .pkcs12(archive.clone())
+                                .password(password.clone()),
+                        ),
+                    )
+                    .boxed(),
+                #[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+                Some(TlsCertificate::Pem { .. }) => {
+                    return Err(wrong_certificate(binding.port, "TlsCertificate::pkcs12"))
+                }
+                #[cfg(not(any(feature = "rustls-tls", feature = "native-tls")))]
+                Some(_) => {
+                    return Err(Box::new(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!(
+                            "the TLS binding on port {} needs the `rustls-tls` or the \
+                             `native-tls` feature",
+                            binding.port
+                        ),
+                    )))
+                }
+                None => listener.combine(tcp).boxed(),
+            };
+        }
+
+        // bind every address now, so that a port already in use is reported to the caller
+        let acceptor = listener.into_acceptor().await?;
+        Self::serve_on(acceptor, config)
+    }
+
+    /// Start the callback server on a listener that is already bound, e.g. a socket passed by
+    /// systemd socket activation or kept open across a restart
+    ///
+    /// 'host', 'http_port', 'additional_addresses' and 'bindings' of the configuration are not
+    /// used, the server only accepts the connections of the given listener. On unix, the first
+    /// socket passed by systemd is `unsafe { std::net::TcpListener::from_raw_fd(3) }`.
+    ///
+    /// # Parameters
+    ///
+    /// * 'listener', the listener to accept the callbacks on, bound to port 0 it gets a free port
+    ///   that `local_addr` tells before the server is started
+    /// * 'config', the configuration of the server
+    ///
+    /// # Returns
+    ///
+    /// * 'CallbackStream', the stream of callbacks received by the server, which stops when the
+    ///   stream is dropped
+    pub async fn serve_with_listener(
+        listener: std::net::TcpListener,
+        config: CallbackServerConfig,
+    ) -> Result<CallbackStream, Box<dyn Error>> {
+        listener.set_nonblocking(true)?;
+        let acceptor = poem::listener::TcpAcceptor::from_std(listener)?;
+        let (_, updates) = Self::serve_on(acceptor, config)?;
+        Ok(updates)
+    }
+
+    /// Start the callback server on a poem acceptor, e.g. one accepting on a socket passed by
+    /// systemd, or on a unix socket behind a reverse proxy
+    ///
+    /// 'host', 'http_port', 'additional_addresses' and 'bindings' of the configuration are not
+    /// used, the server only accepts the connections of the given acceptor.
+    ///
+    /// # Parameters
+    ///
+    /// * 'acceptor', accepts the connections the callbacks are sent on
+    /// * 'config', the configuration of the server
+    ///
+    /// # Returns
+    ///
+    /// * 'CallbackStream', the stream of callbacks received by the server, which stops when the
+    ///   stream is dropped
+    pub async fn serve_with_acceptor(
+        acceptor: impl poem::listener::Acceptor + 'static,
+        config: CallbackServerConfig,
+    ) -> Result<CallbackStream, Box<dyn Error>> {
+        let (_, updates) = Self::serve_on(acceptor, config)?;
+        Ok(updates)
+    }
+
+    /// Spawn the callback server, accepting the connections of the given acceptor
+    fn serve_on(
+        acceptor: impl poem::listener::Acceptor + 'static,
+        config: CallbackServerConfig,
+    ) -> Result<(Vec<std::net::SocketAddr>, CallbackStream), Box<dyn Error>> {
         use tracing_subscriber;
 
-        tracing_subscriber::fmt()
+        let _ = tracing_subscriber::fmt()
             .with_max_level(tracing::Level::TRACE)
-            .init();
+            .try_init();
 
-        let (tx, mut rx) = mpsc::channel::<MomoUpdates>(32);
+        let (channel, updates) = CallbackChannel::new(&config)?;
+        let routes = Self::routes(&config, channel)?;
+        let cors = config
+            .cors
+            .as_ref()
+            .map(CorsConfig::middleware)
+            .transpose()?;
 
         std::env::set_var("RUST_BACKTRACE", "1");
 
-        let app = Route::new()
-            .at(
-                "/collection_request_to_pay/:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .at(
-                "/collection_request_to_withdraw_v1/:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .at(
-                "/collection_request_to_withdraw_v2/:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .at(
-                "/collection_invoice/:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .at(
-                "/collection_payment/:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .at(
-                "/collection_preapproval:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .at(
-                "/disbursement_deposit_V1/:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .at(
-                "/disbursement_deposit_v2/:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .at(
-                "/disburseemnt_refund_v1/:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .at(
-                "/disburseemnt_refund_v2/:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .at(
-                "/disburseemnt_transfer/:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .at(
-                "remittance_cash_transfer/:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .at(
-                "remittance_transfer/:callback_type",
-                post(mtn_callback).put(mtn_callback),
-            )
-            .with(poem::middleware::Tracing::default())
-            .with(poem::middleware::Cors::new())
+        let app = routes
+            .with(poem::middleware::Tracing)
+            .with_if(cors.is_some(), cors.unwrap_or_default())
             .with(poem::middleware::Compression::default())
-            .with(poem::middleware::RequestId::default())
-            .with(AddData::new(tx));
+            .with(poem::middleware::RequestId::default());
+
+        let mut addresses = vec![];
+        for address in poem::listener::Acceptor::local_addr(&acceptor) {
+            tracing::info!("callback server listening on {}", address);
+            addresses.extend(address.as_socket_addr().copied());
+        }
 
-        tokio::spawn(async move {
-            Server::new(TcpListener::bind(format!("0.0.0.0:{}", port)))
-                .run(app)
+        // the server stops on the shutdown of the configuration, or when the stream is closed
+        let shutdown = config.shutdown.clone();
+        let stop = ShutdownHandle::new();
+        let stopped = stop.clone();
+        let signal = async move {
+            let configured = async move {
+                match shutdown {
+                    Some(shutdown) => shutdown.wait().await,
+                    None => std::future::pending().await,
+                }
+            };
+            let stopped = async move { stopped.wait().await };
+            futures_util::future::select(std::pin::pin!(configured), std::pin::pin!(stopped)).await;
+        };
+        let shutdown_grace = config.shutdown_grace;
+        let server = tokio::spawn(async move {
+            if let Err(err) = Server::new_with_acceptor(acceptor)
+                .run_with_graceful_shutdown(app, signal, shutdown_grace)
                 .await
-                .expect("the server failed to start");
+            {
+                tracing::error!("the callback server stopped: {}", err);
+            }
         });
 
-        Ok(async_stream::stream! {
-            while let Some(msg) = rx.recv().await {
-                yield msg;
-            }
-        })
+        Ok((addresses, CallbackStream::new(updates, stop, server)))
+    }
+
+    /// The routes of the callback server, without any middleware, to mount in an existing poem
+    /// app next to its own routes and under its own middleware
+    ///
+    /// These are the routes `serve_with_config` serves: one per callback type, `/health`,
+    /// `/ready` and, when enabled in the configuration, `/metrics`. The addresses, bindings and
+    /// shutdown of the configuration are not used, the app serving the routes decides them.
+    ///
+    /// # Parameters
+    ///
+    /// * 'config', the configuration of the server
+    ///
+    /// # Returns
+    ///
+    /// * 'Route', the routes, e.g. to nest under "/momo" with `Route::nest`
+    /// * 'Stream<Item = MomoUpdates>', the stream of callbacks received by the routes
+    pub fn create_callback_routes(
+        config: &CallbackServerConfig,
+    ) -> Result<(Route, impl Stream<Item = MomoUpdates>), Box<dyn Error>> {
+        let (channel, mut rx) = CallbackChannel::new(config)?;
+        let routes = Self::routes(config, channel)?;
+        Ok((
+            routes,
+            async_stream::stream! {
+                while let Some(msg) = rx.recv().await {
+                    yield msg;
+                }
+            },
+        ))
+    }
+
+    /// Same as `create_callback_routes`, the callbacks are sent to the given sender instead of
+    /// a stream, e.g. one already consumed by the application
+    ///
+    /// # Parameters
+    ///
+    /// * 'config', the configuration of the server, its 'channel_capacity' is not used
+    /// * 'sender', where the callbacks received by the routes are sent
+    ///
+    /// # Returns
+    ///
+    /// * 'Route', the routes, e.g. to nest under "/momo" with `Route::nest`
+    pub fn create_callback_routes_with_sender(
+        config: &CallbackServerConfig,
+        sender: Sender<MomoUpdates>,
+    ) -> Result<Route, Box<dyn Error>> {
+        Self::routes(config, CallbackChannel::with_sender(config, sender))
+    }
+
+    /// The routes of the callback server, handing the callbacks over to the given channel
+    fn routes(
+        config: &CallbackServerConfig,
+        channel: CallbackChannel,
+    ) -> Result<Route, Box<dyn Error>> {
+        let ack = CallbackAck::new(config)?;
+        let mut routes = Route::new().at("/health", get(health)).at(
+            "/ready",
+            get(ready).data(ReadinessChecks(config.readiness_checks.clone())),
+        );
+        for callback_type in CallbackType::ALL {
+            routes = routes.at(
+                format!("{}/:callback_type", callback_type.route_path()),
+                post(mtn_callback)
+                    .put(mtn_callback)
+                    .data(channel.clone())
+                    .data(ack.clone())
+                    .data(callback_type),
+            );
+        }
+        #[cfg(feature = "metrics")]
+        if config.metrics {
+            routes = routes.at("/metrics", get(prometheus_metrics).data(channel));
+        }
+        Ok(routes)
+    }
+
+    /// Start the callback server, every subscriber of the returned sender gets every callback
+    ///
+    /// Subscribers only get the callbacks received after they subscribed, the callbacks received
+    /// while nobody is subscribed are dropped. A subscriber more than 'capacity' callbacks behind
+    /// loses the oldest ones: its next `recv` returns `RecvError::Lagged` with how many were lost,
+    /// then it carries on from the oldest callback still kept.
+    ///
+    /// # Parameters
+    ///
+    /// * 'config', the configuration of the server
+    /// * 'capacity', how many callbacks are kept for the slowest subscriber, at least 1
+    ///
+    /// # Returns
+    ///
+    /// * 'CallbackBroadcast', call `subscribe` on it for each consumer, the server stops when it
+    ///   is dropped, see `CallbackBroadcast::close` to stop it gracefully
+    pub async fn serve_broadcast(
+        config: CallbackServerConfig,
+        capacity: usize,
+    ) -> Result<CallbackBroadcast, Box<dyn Error>> {
+        // tokio's broadcast channel panics outside of these bounds
+        if capacity == 0 || capacity > usize::MAX / 2 {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("the broadcast capacity must be between 1 and {}", usize::MAX / 2),
+            )));
+        }
+        let updates = Self::serve_with_config(config).await?;
+        Ok(CallbackBroadcast::new(updates, capacity))
     }
 }
 
 #[doc(hidden)]
-#[derive(Debug)]
 pub struct Momo {
     pub url: String,
     pub environment: Environment,
@@ -604,6 +1637,65 @@ pub struct Momo {
     pub api_key: String,
 }
 
+/// Write a file only its owner can read and write on unix, for the secrets kept by
+/// `Momo::new_with_provisioning_cached`
+#[cfg(not(target_arch = "wasm32"))]
+async fn write_private_file(path: &std::path::Path, contents: &str) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path).await?;
+    // the mode only applies to a new file, an existing one may still be readable by others
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))
+            .await?;
+    }
+    file.write_all(contents.as_bytes()).await?;
+    file.flush().await
+}
+
+/// Whether MTN still gives the api user kept by `Momo::new_with_provisioning_cached` an access
+/// token, for any of the products as the subscription key is the key of one of them
+#[cfg(not(target_arch = "wasm32"))]
+async fn is_valid_api_user(
+    credentials: &ApiCredentials,
+    url: &str,
+    subscription_key: &str,
+) -> bool {
+    let auth = products::auth::Authorization::with_client(products::default_client());
+    for product in ["collection", "disbursement", "remittance"] {
+        let token = auth
+            .create_access_token(
+                format!("{}/{}", url, product),
+                credentials.api_user.clone(),
+                credentials.api_key.clone(),
+                subscription_key.to_string(),
+            )
+            .await;
+        if token.is_ok() {
+            return true;
+        }
+    }
+    false
+}
+
+impl std::fmt::Debug for Momo {
+    /// The api key is masked, so that a `Momo` can be logged
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Momo")
+            .field("url", &self.url)
+            .field("environment", &self.environment)
+            .field("api_user", &self.api_user)
+            .field("api_key", &redaction::MASK)
+            .finish()
+    }
+}
+
 impl Momo {
     /// Create a new Momo instance
     /// # Parameters
@@ -612,12 +1704,22 @@ impl Momo {
     /// * 'environment' - the environnement of the momo instance SandBox or
     /// * 'api_key' - the api_key
     ///
+    /// A warning is logged when the url is the MTN url of the other kind of environment,
+    /// e.g. the sandbox url with a production environment.
     pub async fn new(
         url: String,
         api_user: String,
         environment: Environment,
         api_key: Option<String>,
     ) -> Self {
+        if environment.is_mismatched_url(&url) {
+            tracing::warn!(
+                "{} is not the url of the {} environment, expected a host containing {}",
+                url,
+                environment,
+                environment.expected_host_contains()
+            );
+        }
         Momo {
             url,
             environment,
@@ -639,18 +1741,106 @@ impl Momo {
         subscription_key: String,
         provider_callback_host: &str,
     ) -> Result<Momo, Box<dyn Error>> {
-        let provisioning = MomoProvisioning::new(url.clone(), subscription_key.clone());
         let reference_id = Uuid::new_v4().to_string();
-        let _create_sandbox = provisioning
-            .create_sandox(&reference_id, provider_callback_host)
+        Momo::new_with_provisioning_reference(
+            url,
+            subscription_key,
+            provider_callback_host,
+            &reference_id,
+        )
+        .await
+    }
+
+    /// Create a new Momo instance with provisioning, with the given reference id as api user, so
+    /// that a provisioning which failed partway can be retried
+    ///
+    /// An api user which already exists with this reference id, e.g. created by the failed
+    /// attempt, is reused and given a new api key.
+    ///
+    /// # Parameters
+    /// * 'url' the momo instance url to use
+    /// * 'subscription_key' the subscription key to use
+    /// * 'provider_callback_host', the callback host that will be used to send momo updates (ex: google.com)
+    /// * 'reference_id', the UUID of the api user, kept by the caller between the attempts
+    ///
+    /// #Returns
+    /// Result<Momo, Box<dyn Error>>, a `ProvisioningError` when the reference id is not a UUID,
+    /// or MTN refuses the callback host or the api key
+    pub async fn new_with_provisioning_reference(
+        url: String,
+        subscription_key: String,
+        provider_callback_host: &str,
+        reference_id: &str,
+    ) -> Result<Momo, Box<dyn Error>> {
+        let credentials = MomoProvisioning::new(url.clone(), subscription_key)
+            .provision_with_reference(provider_callback_host, reference_id)
             .await?;
-        let api = provisioning.create_api_information(&reference_id).await?;
-        return Ok(Momo {
+        Ok(Momo {
             url,
             environment: Environment::Sandbox,
-            api_user: reference_id,
-            api_key: api.api_key,
-        });
+            api_user: credentials.api_user,
+            api_key: credentials.api_key,
+        })
+    }
+
+    /// Create a new Momo instance with provisioning, reusing the api user kept in a file by an
+    /// earlier call, e.g. to not provision a new sandbox api user at each run of the tests
+    ///
+    /// The kept api user is only reused when MTN still gives it an access token, otherwise a new
+    /// one is provisioned and kept in the file instead.
+    ///
+    /// The file holds the api key in clear, which is a secret: keep it out of version control and
+    /// of shared directories. On unix it is only readable and writable by its owner (mode 0600).
+    ///
+    /// # Parameters
+    /// * 'url' the momo instance url to use
+    /// * 'subscription_key' the subscription key to use
+    /// * 'provider_callback_host', the callback host that will be used to send momo updates (ex: google.com)
+    /// * 'cache_path', the file keeping the api user and its api key, created when missing
+    ///
+    /// #Returns
+    /// Result<Momo, Box<dyn Error>>
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn new_with_provisioning_cached(
+        url: String,
+        subscription_key: String,
+        provider_callback_host: &str,
+        cache_path: impl AsRef<std::path::Path>,
+    ) -> Result<Momo, Box<dyn Error>> {
+        let cache_path = cache_path.as_ref();
+        let cached = tokio::fs::read_to_string(cache_path)
+            .await
+            .ok()
+            .and_then(|contents| serde_json::from_str::<ApiCredentials>(&contents).ok());
+        if let Some(cached) = cached {
+            if is_valid_api_user(&cached, &url, &subscription_key).await {
+                return Ok(Momo {
+                    url,
+                    environment: Environment::Sandbox,
+                    api_user: cached.api_user,
+                    api_key: cached.api_key,
+                });
+            }
+            tracing::info!(
+                "the api user kept in {} is no longer valid, provisioning a new one",
+                cache_path.display()
+            );
+        }
+
+        let momo =
+            Momo::new_with_provisioning(url, subscription_key, provider_callback_host).await?;
+        let cached = ApiCredentials {
+            api_user: momo.api_user.clone(),
+            api_key: momo.api_key.clone(),
+        };
+        if let Err(err) = write_private_file(cache_path, &serde_json::to_string(&cached)?).await {
+            tracing::warn!(
+                "could not keep the api user in {}: {}",
+                cache_path.display(),
+                err
+            );
+        }
+        Ok(momo)
     }
 
     /// create a new instance of Collection product
@@ -665,7 +1855,7 @@ impl Momo {
     pub fn collection(&self, primary_key: String, secondary_key: String) -> MomoCollection {
         MomoCollection::new(
             self.url.clone(),
-            self.environment.clone(),
+            self.environment,
             self.api_user.clone(),
             self.api_key.clone(),
             primary_key,
@@ -686,7 +1876,7 @@ impl Momo {
     pub fn disbursement(&self, primary_key: String, secondary_key: String) -> MomoDisbursements {
         MomoDisbursements::new(
             self.url.clone(),
-            self.environment.clone(),
+            self.environment,
             self.api_user.clone(),
             self.api_key.clone(),
             primary_key,
@@ -708,13 +1898,41 @@ impl Momo {
     pub fn remittance(&self, primary_key: String, secondary_key: String) -> MomoRemittance {
         MomoRemittance::new(
             self.url.clone(),
-            self.environment.clone(),
+            self.environment,
             self.api_user.clone(),
             self.api_key.clone(),
             primary_key,
             secondary_key,
         )
     }
+
+    /// Get the balance of the three products at once, e.g. for a dashboard
+    ///
+    /// # Parameters
+    /// * 'collection', the collection product
+    /// * 'disbursements', the disbursements product
+    /// * 'remittance', the remittance product
+    ///
+    /// # Returns
+    ///
+    /// * 'Balances', the balance of each product, a product failing does not fail the others
+    pub async fn all_balances(
+        collection: &MomoCollection,
+        disbursements: &MomoDisbursements,
+        remittance: &MomoRemittance,
+    ) -> Balances {
+        let (collection, disbursements, remittance) = futures_util::future::join3(
+            collection.get_account_balance(),
+            disbursements.get_account_balance(),
+            remittance.get_account_balance(),
+        )
+        .await;
+        Balances {
+            collection,
+            disbursements,
+            remittance,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -726,7 +1944,1782 @@ mod tests {
 
     use super::*;
 
+    fn payment_succeeded(reference_id: &str) -> MomoUpdates {
+        MomoUpdates {
+            remote_address: "127.0.0.1".to_string(),
+            raw_body: String::new(),
+            parse_error: None,
+            response: Some(CallbackResponse::PaymentSucceeded {
+                reference_id: reference_id.to_string(),
+                status: "SUCCESSFUL".to_string(),
+                financial_transaction_id: "363440463".to_string(),
+            }),
+            update_type: CallbackType::CollectionPayment,
+        }
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_callback_channel_capacity() {
+        let config = CallbackServerConfig {
+            channel_capacity: 2,
+            drop_when_full: true,
+            ..Default::default()
+        };
+        let (channel, mut rx) = CallbackChannel::new(&config).unwrap();
+        assert_eq!(channel.sender.max_capacity(), 2);
+
+        for reference_id in ["first", "second", "third"] {
+            channel.deliver(payment_succeeded(reference_id)).await;
+        }
+        assert_eq!(channel.sender.capacity(), 0);
+
+        let mut received = vec![];
+        while let Ok(update) = rx.try_recv() {
+            if let Some(CallbackResponse::PaymentSucceeded { reference_id, .. }) = update.response {
+                received.push(reference_id);
+            }
+        }
+        assert_eq!(received, vec!["first", "second"]);
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_zero_channel_capacity_is_rejected() {
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port: 0,
+            channel_capacity: 0,
+            ..Default::default()
+        };
+        let err = MomoCallbackListener::serve_with_config(config.clone())
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert!(MomoCallbackListener::create_callback_routes(&config).is_err());
+    }
+
+    #[cfg(feature = "callback-server")]
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[cfg(feature = "callback-server")]
+    async fn post_callback(port: u16, reference_id: &str) -> reqwest::Response {
+        let body = serde_json::to_string(&payment_succeeded(reference_id).response).unwrap();
+        let client = reqwest::Client::new();
+        for _ in 0..50 {
+            let res = client
+                .post(format!(
+                    "http://127.0.0.1:{}/collection_payment/COLLECTION_PAYMENT",
+                    port
+                ))
+                .body(body.clone())
+                .send()
+                .await;
+            if let Ok(res) = res {
+                return res;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("the callback server did not start on port {}", port);
+    }
+
+    #[cfg(feature = "callback-server")]
+    struct FixedCheck(Result<(), String>);
+
+    #[cfg(feature = "callback-server")]
+    impl ReadinessCheck for FixedCheck {
+        fn check(&self) -> futures_util::future::BoxFuture<'_, Result<(), String>> {
+            Box::pin(async move { self.0.clone() })
+        }
+    }
+
+    #[cfg(feature = "callback-server")]
+    async fn get_status(port: u16, path: &str) -> (u16, String) {
+        let client = reqwest::Client::new();
+        for _ in 0..50 {
+            if let Ok(res) = client
+                .get(format!("http://127.0.0.1:{}{}", port, path))
+                .send()
+                .await
+            {
+                return (res.status().as_u16(), res.text().await.unwrap());
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("the callback server did not start on port {}", port);
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_health_and_ready() {
+        let http_port = free_port();
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            readiness_checks: vec![
+                Arc::new(FixedCheck(Ok(()))),
+                Arc::new(FixedCheck(Err("token endpoint unreachable".to_string()))),
+            ],
+            ..Default::default()
+        };
+        let _updates = MomoCallbackListener::serve_with_config(config)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_status(http_port, "/health").await,
+            (200, "OK".to_string())
+        );
+
+        let (status, body) = get_status(http_port, "/ready").await;
+        assert_eq!(status, 503);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["reason"], "token endpoint unreachable");
+
+        let http_port = free_port();
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            readiness_checks: vec![Arc::new(FixedCheck(Ok(())))],
+            ..Default::default()
+        };
+        let _updates = MomoCallbackListener::serve_with_config(config)
+            .await
+            .unwrap();
+        assert_eq!(get_status(http_port, "/ready").await.0, 200);
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_serve_on_additional_addresses() {
+        let http_port = free_port();
+        let additional_port = free_port();
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            additional_addresses: vec![format!("127.0.0.1:{}", additional_port).parse().unwrap()],
+            ..Default::default()
+        };
+        let updates = MomoCallbackListener::serve_with_config(config)
+            .await
+            .unwrap();
+        let mut updates = std::pin::pin!(updates);
+
+        assert!(post_callback(http_port, "first")
+            .await
+            .status()
+            .is_success());
+        assert!(post_callback(additional_port, "second")
+            .await
+            .status()
+            .is_success());
+
+        for expected in ["first", "second"] {
+            let update = futures_util::StreamExt::next(&mut updates).await.unwrap();
+            assert!(matches!(
+                update.response,
+                Some(CallbackResponse::PaymentSucceeded { reference_id, .. }) if reference_id == expected
+            ));
+        }
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_custom_callback_ack() {
+        let http_port = free_port();
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            ack_status_code: 202,
+            ack_response: Some(String::new()),
+            ..Default::default()
+        };
+        let _updates = MomoCallbackListener::serve_with_config(config)
+            .await
+            .unwrap();
+
+        let res = post_callback(http_port, "ack").await;
+        assert_eq!(res.status().as_u16(), 202);
+        assert_eq!(res.text().await.unwrap(), "");
+
+        let config = CallbackServerConfig {
+            http_port: free_port(),
+            ack_status_code: 1000,
+            ..Default::default()
+        };
+        assert!(MomoCallbackListener::serve_with_config(config)
+            .await
+            .is_err());
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_graceful_shutdown_grace() {
+        let http_port = free_port();
+        let shutdown = ShutdownHandle::new();
+        let grace = std::time::Duration::from_millis(300);
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            channel_capacity: 1,
+            shutdown: Some(shutdown.clone()),
+            shutdown_grace: Some(grace),
+            ..Default::default()
+        };
+        let updates = MomoCallbackListener::serve_with_config(config)
+            .await
+            .unwrap();
+        let mut updates = std::pin::pin!(updates);
+
+        // the first callback fills the channel, the second one waits in flight for room
+        assert!(post_callback(http_port, "first")
+            .await
+            .status()
+            .is_success());
+        let body = serde_json::to_string(&payment_succeeded("second").response).unwrap();
+        let in_flight = tokio::spawn(
+            reqwest::Client::new()
+                .post(format!(
+                    "http://127.0.0.1:{}/collection_payment/COLLECTION_PAYMENT",
+                    http_port
+                ))
+                .body(body)
+                .send(),
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let started = std::time::Instant::now();
+        shutdown.shutdown();
+        let res = tokio::time::timeout(grace * 10, in_flight)
+            .await
+            .expect("the shutdown did not drop the request in flight")
+            .unwrap();
+        assert!(res.is_err());
+        assert!(started.elapsed() >= grace);
+
+        let update = futures_util::StreamExt::next(&mut updates).await.unwrap();
+        assert!(matches!(
+            update.response,
+            Some(CallbackResponse::PaymentSucceeded { reference_id, .. }) if reference_id == "first"
+        ));
+        assert!(futures_util::StreamExt::next(&mut updates).await.is_none());
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_callback_logs_without_payer_details() {
+        let http_port = free_port();
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            ..Default::default()
+        };
+        let updates = MomoCallbackListener::serve_with_config(config)
+            .await
+            .unwrap();
+        let mut updates = std::pin::pin!(updates);
+
+        let body = r#"{"financialTransactionId": "363440463", "status": "SUCCESSFUL", "reason": "",
+            "amount": "100", "currency": "EUR",
+            "payee": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "externalId": "external_id", "originatingCountry": "UG",
+            "originalAmount": "100", "originalCurrency": "EUR",
+            "payerMessage": "payer_message", "payeeNote": "payee_note",
+            "payerIdentificationType": "PASS", "payerIdentificationNumber": "CA0000000",
+            "payerIdentity": "256774290781", "payerFirstName": "Jane",
+            "payerSurname": "Doe", "payerLanguageCode": "en",
+            "payerEmail": "jane@example.com", "payerMsisdn": "256774290781",
+            "payerGender": "F"}"#;
+        let client = reqwest::Client::new();
+        let mut sent = false;
+        for _ in 0..50 {
+            let res = client
+                .post(format!(
+                    "http://127.0.0.1:{}/remittance_cash_transfer/REMITTANCE_CASH_TRANSFER",
+                    http_port
+                ))
+                .body(body)
+                .send()
+                .await;
+            if res.is_ok() {
+                sent = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(sent);
+        futures_util::StreamExt::next(&mut updates).await.unwrap();
+
+        assert!(logs_contain(
+            "CashTransferSucceeded callback for external_id received from"
+        ));
+        for pii in [
+            "256774290781",
+            "jane@example.com",
+            "Jane",
+            "Doe",
+            "CA0000000",
+        ] {
+            assert!(!logs_contain(pii), "{} was logged", pii);
+        }
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_every_callback_type_has_a_route() {
+        let http_port = free_port();
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            ..Default::default()
+        };
+        let _updates = MomoCallbackListener::serve_with_config(config)
+            .await
+            .unwrap();
+        // wait for the server to accept connections
+        assert!(post_callback(http_port, "warm_up")
+            .await
+            .status()
+            .is_success());
+
+        let client = reqwest::Client::new();
+        let base_url = format!("http://127.0.0.1:{}", http_port);
+        for callback_type in CallbackType::ALL {
+            let res = client
+                .put(format!("{}{}", base_url, callback_type.path()))
+                .body("{}")
+                .send()
+                .await
+                .unwrap();
+            assert!(res.status().is_success(), "no route for {}", callback_type);
+        }
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_disbursement_operation_of_received_callbacks() {
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port: 0,
+            ..Default::default()
+        };
+        let (addresses, mut updates) = MomoCallbackListener::serve_with_addresses(config)
+            .await
+            .unwrap();
+        let port = addresses[0].port();
+        assert!(post_callback(port, "warm_up").await.status().is_success());
+        futures_util::StreamExt::next(&mut updates).await.unwrap();
+
+        let success = r#"{
+            "financialTransactionId": "363440463", "externalId": "external_id",
+            "amount": "100", "currency": "EUR",
+            "payee": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "status": "SUCCESSFUL"
+        }"#;
+        let client = reqwest::Client::new();
+        for callback_type in [
+            CallbackType::DisbursementDepositV1,
+            CallbackType::DisbusrementTransfer,
+        ] {
+            let res = client
+                .put(callback_type.callback_url(&format!("http://127.0.0.1:{}", port)))
+                .body(success)
+                .send()
+                .await
+                .unwrap();
+            assert!(res.status().is_success());
+        }
+        let deposit = futures_util::StreamExt::next(&mut updates).await.unwrap();
+        let transfer = futures_util::StreamExt::next(&mut updates).await.unwrap();
+        assert_eq!(
+            deposit.response.as_ref().map(CallbackResponse::variant_name),
+            transfer.response.as_ref().map(CallbackResponse::variant_name)
+        );
+        assert_eq!(
+            deposit.disbursement_operation(),
+            Some(DisbursementOperation::Deposit)
+        );
+        assert_eq!(
+            transfer.disbursement_operation(),
+            Some(DisbursementOperation::Transfer)
+        );
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_metrics() {
+        let http_port = free_port();
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            metrics: true,
+            ..Default::default()
+        };
+        let _updates = MomoCallbackListener::serve_with_config(config)
+            .await
+            .unwrap();
+
+        for reference_id in ["first", "second"] {
+            assert!(post_callback(http_port, reference_id)
+                .await
+                .status()
+                .is_success());
+        }
+        let res = reqwest::Client::new()
+            .post(format!(
+                "http://127.0.0.1:{}/collection_invoice/INVOICE",
+                http_port
+            ))
+            .body("not a callback")
+            .send()
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+
+        let (status, body) = get_status(http_port, "/metrics").await;
+        assert_eq!(status, 200);
+        assert!(body.contains(
+            "callbacks_received_total{path=\"/collection_payment/COLLECTION_PAYMENT\"} 2"
+        ));
+        assert!(body.contains("callbacks_received_total{path=\"/collection_invoice/INVOICE\"} 1"));
+        assert!(body.contains("callbacks_parse_failures_total 1"));
+        // nobody consumes the stream, the three callbacks are still in the channel
+        assert!(body.contains("callbacks_channel_depth 3"));
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_serve_on_several_bindings() {
+        let http_port = free_port();
+        let first_port = free_port();
+        let second_port = free_port();
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            bindings: vec![
+                SocketBinding {
+                    port: first_port,
+                    tls: None,
+                },
+                SocketBinding {
+                    port: second_port,
+                    tls: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let updates = MomoCallbackListener::serve_with_config(config)
+            .await
+            .unwrap();
+        let mut updates = std::pin::pin!(updates);
+
+        assert!(post_callback(first_port, "first")
+            .await
+            .status()
+            .is_success());
+        assert!(post_callback(second_port, "second")
+            .await
+            .status()
+            .is_success());
+
+        for expected in ["first", "second"] {
+            let update = futures_util::StreamExt::next(&mut updates).await.unwrap();
+            assert!(matches!(
+                update.response,
+                Some(CallbackResponse::PaymentSucceeded { reference_id, .. }) if reference_id == expected
+            ));
+        }
+    }
+
+    #[cfg(feature = "rustls-tls")]
+    #[tokio::test]
+    async fn test_serve_refuses_a_pkcs12_certificate_with_rustls() {
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port: free_port(),
+            bindings: vec![SocketBinding {
+                port: free_port(),
+                tls: Some(TlsCertificate::pkcs12(vec![], "password")),
+            }],
+            ..Default::default()
+        };
+        let err = match MomoCallbackListener::serve_with_config(config).await {
+            Ok(_) => panic!("a PKCS#12 archive was served with rustls"),
+            Err(err) => err,
+        };
+        assert_eq!(
+            err.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert!(err.to_string().contains("TlsCertificate::pem"));
+    }
+
+    #[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+    #[tokio::test]
+    async fn test_serve_refuses_a_pem_certificate_with_native_tls() {
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port: free_port(),
+            bindings: vec![SocketBinding {
+                port: free_port(),
+                tls: Some(TlsCertificate::pem(vec![], vec![])),
+            }],
+            ..Default::default()
+        };
+        let err = match MomoCallbackListener::serve_with_config(config).await {
+            Ok(_) => panic!("a PEM certificate was served with native-tls"),
+            Err(err) => err,
+        };
+        assert!(err.to_string().contains("TlsCertificate::pkcs12"));
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_serve_fails_when_a_port_is_taken() {
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port: free_port(),
+            bindings: vec![SocketBinding {
+                port: taken.local_addr().unwrap().port(),
+                tls: None,
+            }],
+            ..Default::default()
+        };
+        assert!(MomoCallbackListener::serve_with_config(config)
+            .await
+            .is_err());
+
+        // the main port as well, the error is returned before anything is spawned
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port: taken.local_addr().unwrap().port(),
+            ..Default::default()
+        };
+        let err = match MomoCallbackListener::serve_with_config(config).await {
+            Ok(_) => panic!("the taken port was bound"),
+            Err(err) => err,
+        };
+        assert_eq!(
+            err.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::AddrInUse
+        );
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_unknown_callback_keeps_raw_body() {
+        let http_port = free_port();
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            ..Default::default()
+        };
+        let updates = MomoCallbackListener::serve_with_config(config)
+            .await
+            .unwrap();
+        let mut updates = std::pin::pin!(updates);
+
+        // wait for the server to be up
+        assert!(post_callback(http_port, "known")
+            .await
+            .status()
+            .is_success());
+
+        let body = r#"{"NewKindOfCallback": {"referenceId": "unknown"}}"#;
+        let res = reqwest::Client::new()
+            .post(format!(
+                "http://127.0.0.1:{}/collection_payment/COLLECTION_PAYMENT",
+                http_port
+            ))
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+
+        let known = futures_util::StreamExt::next(&mut updates).await.unwrap();
+        assert!(known.response.is_some());
+        let unknown = futures_util::StreamExt::next(&mut updates).await.unwrap();
+        assert!(unknown.response.is_none());
+        assert_eq!(unknown.raw_body, body);
+        assert!(unknown
+            .parse_error
+            .unwrap()
+            .contains("/collection_payment/COLLECTION_PAYMENT"));
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_serve_broadcast() {
+        let http_port = free_port();
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            ..Default::default()
+        };
+        let broadcast = MomoCallbackListener::serve_broadcast(config.clone(), 16)
+            .await
+            .unwrap();
+        let mut persist = broadcast.subscribe();
+        let mut react = broadcast.subscribe();
+
+        assert!(post_callback(http_port, "broadcast")
+            .await
+            .status()
+            .is_success());
+
+        let persisted = persist.recv().await.unwrap();
+        let reacted = react.recv().await.unwrap();
+        assert_eq!(persisted.raw_body, reacted.raw_body);
+        assert!(persisted.raw_body.contains("broadcast"));
+
+        broadcast.close().await;
+        assert!(persist.recv().await.is_err());
+        assert!(stops_accepting(http_port).await);
+
+        let http_port = free_port();
+        let config = CallbackServerConfig { http_port, ..config };
+        let broadcast = MomoCallbackListener::serve_broadcast(config.clone(), 16)
+            .await
+            .unwrap();
+        assert!(post_callback(http_port, "warm_up").await.status().is_success());
+        drop(broadcast);
+        assert!(stops_accepting(http_port).await);
+
+        let err = MomoCallbackListener::serve_broadcast(config, 0)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[derive(Default)]
+    struct VecSink(std::sync::Mutex<Vec<String>>);
+
+    #[cfg(feature = "callback-server")]
+    impl CallbackSink for VecSink {
+        fn store<'a>(
+            &'a self,
+            update: &'a MomoUpdates,
+        ) -> futures_util::future::BoxFuture<'a, Result<(), Box<dyn Error + Send + Sync>>> {
+            Box::pin(async move {
+                self.0.lock().unwrap().push(update.raw_body.clone());
+                Ok(())
+            })
+        }
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_callback_sink() {
+        let http_port = free_port();
+        let sink = Arc::new(VecSink::default());
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            sink: Some(sink.clone()),
+            ..Default::default()
+        };
+        // the stream is never drained, the callbacks are stored all the same
+        let _updates = MomoCallbackListener::serve_with_config(config)
+            .await
+            .unwrap();
+        for reference_id in ["first", "second", "third"] {
+            assert!(post_callback(http_port, reference_id)
+                .await
+                .status()
+                .is_success());
+        }
+
+        let stored = sink.0.lock().unwrap();
+        assert_eq!(stored.len(), 3);
+        for (body, reference_id) in stored.iter().zip(["first", "second", "third"]) {
+            assert!(body.contains(reference_id));
+        }
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[derive(Default)]
+    struct CallbackCounter(std::sync::Mutex<Vec<CallbackType>>);
+
+    #[cfg(feature = "callback-server")]
+    impl metrics::Metrics for CallbackCounter {
+        fn on_callback(&self, callback_type: CallbackType) {
+            self.0.lock().unwrap().push(callback_type);
+        }
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_callback_metrics_hooks() {
+        let http_port = free_port();
+        let counter = Arc::new(CallbackCounter::default());
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            metrics_hooks: Some(counter.clone()),
+            ..Default::default()
+        };
+        let _updates = MomoCallbackListener::serve_with_config(config)
+            .await
+            .unwrap();
+        for reference_id in ["first", "second"] {
+            assert!(post_callback(http_port, reference_id)
+                .await
+                .status()
+                .is_success());
+        }
+
+        let received = counter.0.lock().unwrap();
+        assert_eq!(*received, vec![CallbackType::CollectionPayment; 2]);
+    }
+
+    #[cfg(feature = "callback-server")]
+    async fn get_health_from(port: u16, origin: &str) -> reqwest::Response {
+        let client = reqwest::Client::new();
+        for _ in 0..50 {
+            if let Ok(res) = client
+                .get(format!("http://127.0.0.1:{}/health", port))
+                .header("Origin", origin)
+                .send()
+                .await
+            {
+                return res;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("the callback server did not start on port {}", port);
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_cors() {
+        let http_port = free_port();
+        let _updates = MomoCallbackListener::serve_with_config(CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let res = get_health_from(http_port, "https://shop.example.com").await;
+        assert!(res.status().is_success());
+        assert!(res.headers().get("access-control-allow-origin").is_none());
+
+        let http_port = free_port();
+        let _updates = MomoCallbackListener::serve_with_config(CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port,
+            cors: Some(CorsConfig {
+                allowed_origins: vec!["https://shop.example.com".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let res = get_health_from(http_port, "https://shop.example.com").await;
+        assert_eq!(
+            res.headers().get("access-control-allow-origin").unwrap(),
+            "https://shop.example.com"
+        );
+        let res = get_health_from(http_port, "https://evil.example.com").await;
+        assert!(res.headers().get("access-control-allow-origin").is_none());
+
+        let err = MomoCallbackListener::serve_with_config(CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port: free_port(),
+            cors: Some(CorsConfig {
+                allowed_methods: vec!["NOT A METHOD".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("NOT A METHOD"));
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_serve_with_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let http_port = listener.local_addr().unwrap().port();
+        let updates = MomoCallbackListener::serve_with_listener(listener, Default::default())
+            .await
+            .unwrap();
+        let mut updates = std::pin::pin!(updates);
+
+        assert!(post_callback(http_port, "listener")
+            .await
+            .status()
+            .is_success());
+        let update = futures_util::StreamExt::next(&mut updates).await.unwrap();
+        assert!(update.raw_body.contains("listener"));
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_serve_with_acceptor() {
+        use poem::listener::Acceptor;
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let http_port = acceptor.local_addr()[0]
+            .as_socket_addr()
+            .unwrap()
+            .port();
+        let updates = MomoCallbackListener::serve_with_acceptor(acceptor, Default::default())
+            .await
+            .unwrap();
+        let mut updates = std::pin::pin!(updates);
+
+        assert!(post_callback(http_port, "acceptor")
+            .await
+            .status()
+            .is_success());
+        let update = futures_util::StreamExt::next(&mut updates).await.unwrap();
+        assert!(update.raw_body.contains("acceptor"));
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_serve_with_addresses() {
+        let (addresses, updates) =
+            MomoCallbackListener::serve_with_addresses(CallbackServerConfig {
+                host: "127.0.0.1".to_string(),
+                http_port: 0,
+                additional_addresses: vec!["127.0.0.1:0".parse().unwrap()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let mut updates = std::pin::pin!(updates);
+        assert_eq!(addresses.len(), 2);
+        assert!(addresses.iter().all(|address| address.port() != 0));
+        assert_ne!(addresses[0], addresses[1]);
+
+        for (address, reference_id) in addresses.iter().zip(["first", "second"]) {
+            assert!(post_callback(address.port(), reference_id)
+                .await
+                .status()
+                .is_success());
+            let update = futures_util::StreamExt::next(&mut updates).await.unwrap();
+            assert!(update.raw_body.contains(reference_id));
+        }
+    }
+
+    /// Whether the server on the given port stops accepting connections within a second
+    #[cfg(feature = "callback-server")]
+    async fn stops_accepting(port: u16) -> bool {
+        for _ in 0..50 {
+            if tokio::net::TcpStream::connect(("127.0.0.1", port))
+                .await
+                .is_err()
+            {
+                return true;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        false
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_callback_stream_stops_the_server() {
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port: 0,
+            ..Default::default()
+        };
+        let (addresses, updates) = MomoCallbackListener::serve_with_addresses(config.clone())
+            .await
+            .unwrap();
+        let port = addresses[0].port();
+        for reference_id in ["first", "second"] {
+            assert!(post_callback(port, reference_id)
+                .await
+                .status()
+                .is_success());
+        }
+        let pending = updates.close().await;
+        assert_eq!(pending.len(), 2);
+        assert!(pending[0].raw_body.contains("first"));
+        assert!(stops_accepting(port).await);
+
+        let (addresses, updates) = MomoCallbackListener::serve_with_addresses(config)
+            .await
+            .unwrap();
+        let port = addresses[0].port();
+        assert!(post_callback(port, "warm_up").await.status().is_success());
+        drop(updates);
+        assert!(stops_accepting(port).await);
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_callback_stream_close_with_a_full_channel() {
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port: 0,
+            channel_capacity: 1,
+            drop_when_full: false,
+            shutdown_grace: None,
+            ..Default::default()
+        };
+        let (addresses, updates) = MomoCallbackListener::serve_with_addresses(config)
+            .await
+            .unwrap();
+        let port = addresses[0].port();
+        assert!(post_callback(port, "first").await.status().is_success());
+        // the channel is full, this request waits in the server until the stream is read
+        let waiting = tokio::spawn(async move { post_callback(port, "second").await });
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        let pending = tokio::time::timeout(std::time::Duration::from_secs(5), updates.close())
+            .await
+            .expect("closing a stream with a full channel must not hang");
+        assert_eq!(pending.len(), 2);
+        assert!(pending[0].raw_body.contains("first"));
+        assert!(pending[1].raw_body.contains("second"));
+        assert!(waiting.await.unwrap().status().is_success());
+        assert!(stops_accepting(port).await);
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[derive(Default)]
+    struct VecDeadLetters(std::sync::Mutex<Vec<(String, DeadLetterReason)>>);
+
+    #[cfg(feature = "callback-server")]
+    impl DeadLetterSink for VecDeadLetters {
+        fn dead_letter<'a>(
+            &'a self,
+            update: &'a MomoUpdates,
+            reason: DeadLetterReason,
+        ) -> futures_util::future::BoxFuture<'a, Result<(), Box<dyn Error + Send + Sync>>> {
+            Box::pin(async move {
+                if let Some(CallbackResponse::PaymentSucceeded { reference_id, .. }) =
+                    &update.response
+                {
+                    self.0.lock().unwrap().push((reference_id.clone(), reason));
+                }
+                Ok(())
+            })
+        }
+    }
+
+    #[cfg(feature = "callback-server")]
+    #[tokio::test]
+    async fn test_dead_letters() {
+        let dead_letters = Arc::new(VecDeadLetters::default());
+        let config = CallbackServerConfig {
+            channel_capacity: 1,
+            drop_when_full: true,
+            dead_letter: Some(dead_letters.clone()),
+            ..Default::default()
+        };
+        let (channel, rx) = CallbackChannel::new(&config).unwrap();
+        channel.deliver(payment_succeeded("first")).await;
+        channel.deliver(payment_succeeded("full")).await;
+        drop(rx);
+        channel.deliver(payment_succeeded("closed")).await;
+
+        let config = CallbackServerConfig {
+            dead_letter: Some(dead_letters.clone()),
+            ..Default::default()
+        };
+        let (channel, rx) = CallbackChannel::new(&config).unwrap();
+        drop(rx);
+        channel.deliver(payment_succeeded("closed_waiting")).await;
+
+        assert_eq!(
+            *dead_letters.0.lock().unwrap(),
+            vec![
+                ("full".to_string(), DeadLetterReason::ChannelFull),
+                ("closed".to_string(), DeadLetterReason::ChannelClosed),
+                (
+                    "closed_waiting".to_string(),
+                    DeadLetterReason::ChannelClosed
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_debug_masks_keys() {
+        let momo = Momo {
+            url: "http://localhost".to_string(),
+            environment: Environment::Sandbox,
+            api_user: "api_user".to_string(),
+            api_key: "secret_api_key".to_string(),
+        };
+        let outputs = [
+            format!("{:?}", momo),
+            format!(
+                "{:?}",
+                momo.collection("secret_primary".into(), "secret_secondary".into())
+            ),
+            format!(
+                "{:?}",
+                momo.disbursement("secret_primary".into(), "secret_secondary".into())
+            ),
+            format!(
+                "{:?}",
+                momo.remittance("secret_primary".into(), "secret_secondary".into())
+            ),
+            format!(
+                "{:?}",
+                MomoProvisioning::new(momo.url.clone(), "secret_subscription".into())
+            ),
+        ];
+        for output in outputs {
+            assert!(!output.contains("secret"), "{}", output);
+            assert!(output.contains("****"), "{}", output);
+        }
+        assert!(format!("{:?}", momo).contains("api_user"));
+    }
+
+    #[poem::handler]
+    fn product_balance(poem::web::Path(product): poem::web::Path<String>) -> poem::Response {
+        let balance = match product.as_str() {
+            "collection" => "1000",
+            "disbursement" => "250",
+            _ => {
+                return poem::Response::builder()
+                    .status(poem::http::StatusCode::INTERNAL_SERVER_ERROR)
+                    .body("balance unavailable")
+            }
+        };
+        poem::Response::builder().body(format!(
+            r#"{{"availableBalance":"{}","currency":"EUR"}}"#,
+            balance
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_all_balances() {
+        use crate::products::test_server::{short_lived_token_routes, spawn_mock};
+
+        let url = spawn_mock(
+            short_lived_token_routes()
+                .at("/:product/v1_0/account/balance", poem::get(product_balance)),
+        );
+
+        let momo = Momo {
+            url,
+            environment: Environment::Sandbox,
+            api_user: "api_user".to_string(),
+            api_key: "api_key".to_string(),
+        };
+        let balances = Momo::all_balances(
+            &momo.collection("primary_key".to_string(), "secondary_key".to_string()),
+            &momo.disbursement("primary_key".to_string(), "secondary_key".to_string()),
+            &momo.remittance("primary_key".to_string(), "secondary_key".to_string()),
+        )
+        .await;
+        assert_eq!(balances.collection.unwrap().available_balance, "1000");
+        assert_eq!(balances.disbursements.unwrap().available_balance, "250");
+        assert!(balances.remittance.is_err());
+    }
+
+    #[poem::handler]
+    fn provisioning_api_user() -> poem::Response {
+        poem::Response::builder()
+            .status(poem::http::StatusCode::CREATED)
+            .finish()
+    }
+
+    #[poem::handler]
+    fn provisioning_api_key(
+        poem::web::Data(created): poem::web::Data<&std::sync::Arc<std::sync::atomic::AtomicUsize>>,
+    ) -> poem::Response {
+        created.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        poem::Response::builder()
+            .status(poem::http::StatusCode::CREATED)
+            .body(r#"{"apiKey":"provisioned_api_key"}"#)
+    }
+
+    #[poem::handler]
+    fn provisioned_access_token(req: &poem::Request) -> poem::Response {
+        // stale_api_user:stale_api_key
+        if req.header("Authorization") == Some("Basic c3RhbGVfYXBpX3VzZXI6c3RhbGVfYXBpX2tleQ==") {
+            return poem::Response::builder()
+                .status(poem::http::StatusCode::UNAUTHORIZED)
+                .body(r#"{"error":"login_failed"}"#);
+        }
+        poem::Response::builder().body(
+            r#"{"access_token":"mock_access_token","token_type":"access_token","expires_in":0}"#,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_new_with_provisioning_cached() {
+        use crate::products::test_server::spawn_mock;
+        use poem::EndpointExt;
+
+        let created = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let app = poem::Route::new()
+            .at("/v1_0/apiuser", poem::post(provisioning_api_user))
+            .at(
+                "/v1_0/apiuser/:reference_id/apikey",
+                poem::post(provisioning_api_key),
+            )
+            .at("/:product/token/", poem::post(provisioned_access_token))
+            .data(created.clone());
+        let url = spawn_mock(app);
+
+        let cache_path =
+            std::env::temp_dir().join(format!("momo_api_user_{}.json", Uuid::new_v4()));
+        let new = || {
+            Momo::new_with_provisioning_cached(
+                url.clone(),
+                "subscription_key".to_string(),
+                "callbacks.example.com",
+                &cache_path,
+            )
+        };
+        let first = new().await.unwrap();
+        assert_eq!(first.api_key, "provisioned_api_key");
+        let second = new().await.unwrap();
+        assert_eq!(second.api_user, first.api_user);
+        assert_eq!(created.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        std::fs::write(
+            &cache_path,
+            r#"{"api_user":"stale_api_user","api_key":"stale_api_key"}"#,
+        )
+        .unwrap();
+        let third = new().await.unwrap();
+        assert_ne!(third.api_user, "stale_api_user");
+        assert_eq!(created.load(std::sync::atomic::Ordering::SeqCst), 2);
+        let kept = std::fs::read_to_string(&cache_path).unwrap();
+        assert!(kept.contains(&third.api_user));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&cache_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_file(&cache_path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_momo_date_time() {
+        let expected = DateTime::parse_from_rfc3339("2024-09-16T18:05:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            parse_momo_date_time("2024-09-16T18:05:00Z").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_momo_date_time("2024-09-16T20:05:00+02:00").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_momo_date_time("2024-09-16T18:05:00").unwrap(),
+            expected
+        );
+        assert!(parse_momo_date_time("").is_err());
+        assert!(parse_momo_date_time("not a date").is_err());
+    }
+
+    #[test]
+    fn test_callback_expires_at() {
+        let pre_approval = CallbackResponse::PreApprovalSuccess {
+            payer: Party {
+                party_id_type: PartyIdType::MSISDN,
+                party_id: "46733123450".to_string(),
+            },
+            payer_currency: "EUR".to_string(),
+            status: "SUCCESSFUL".to_string(),
+            expiration_date_time: "2024-09-16T18:05:00Z".to_string(),
+        };
+        assert!(pre_approval.expires_at().unwrap().is_ok());
+
+        let payment = CallbackResponse::PaymentSucceeded {
+            reference_id: "reference_id".to_string(),
+            status: "SUCCESSFUL".to_string(),
+            financial_transaction_id: "financial_transaction_id".to_string(),
+        };
+        assert!(payment.expires_at().is_none());
+    }
+
+    #[test]
+    fn test_callback_external_id_and_status() {
+        let payment = payment_succeeded("reference_id").response.unwrap();
+        assert!(payment.status_is_success());
+        assert_eq!(payment.external_id(), None);
+
+        let request_to_pay: CallbackResponse = serde_json::from_str(
+            r#"{"RequestToPayFailed": {
+                "financialTransactionId": "363440463", "externalId": "external_id",
+                "amount": "100", "currency": "EUR",
+                "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                "payeeNote": "payee_note", "payerMessage": "payer_message",
+                "status": "FAILED", "reason": {"code": "PAYER_NOT_FOUND", "message": "payer not found"}
+            }}"#,
+        )
+        .unwrap();
+        assert!(!request_to_pay.status_is_success());
+        assert_eq!(request_to_pay.external_id(), Some("external_id"));
+    }
+
+    fn reconcile<I: MomoId>(ids: Vec<I>) -> Vec<String> {
+        ids.into_iter().map(MomoId::into_string).collect()
+    }
+
+    #[test]
+    fn test_momo_id() {
+        let transaction_id: TransactionId = "external_id".parse().unwrap();
+        assert_eq!(transaction_id.to_string(), "external_id");
+        assert_eq!(MomoId::as_str(&transaction_id), "external_id");
+
+        let transfer_id: TransferId = "transfer_id".parse().unwrap();
+        assert_eq!(transfer_id, TranserId("transfer_id".to_string()));
+
+        assert_eq!(
+            reconcile(vec![
+                "first".parse::<WithdrawId>().unwrap(),
+                "second".parse::<WithdrawId>().unwrap()
+            ]),
+            vec!["first".to_string(), "second".to_string()]
+        );
+        assert_eq!(
+            reconcile(vec![DepositId("deposit_id".to_string())]),
+            vec!["deposit_id".to_string()]
+        );
+    }
+
+    /// Parse a callback sample, serialize it back and check nothing was lost on the way
+    fn round_trip(body: &str) -> CallbackResponse {
+        let response: CallbackResponse = serde_json::from_str(body).unwrap();
+        let serialized = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            serialized,
+            serde_json::from_str::<serde_json::Value>(body).unwrap()
+        );
+        let again: CallbackResponse = serde_json::from_value(serialized.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&again).unwrap(), serialized);
+        response
+    }
+
+    #[test]
+    fn test_request_to_pay_callbacks_round_trip() {
+        let success = round_trip(
+            r#"{"RequestToPaySuccess": {
+                "financialTransactionId": "363440463", "externalId": "external_id",
+                "amount": "100", "currency": "EUR",
+                "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                "payeeNote": "payee_note", "payerMessage": "payer_message",
+                "status": "SUCCESSFUL"
+            }}"#,
+        );
+        assert!(matches!(
+            success,
+            CallbackResponse::RequestToPaySuccess { .. }
+        ));
+
+        let failed = round_trip(
+            r#"{"RequestToPayFailed": {
+                "financialTransactionId": "363440463", "externalId": "external_id",
+                "amount": "100", "currency": "EUR",
+                "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                "payeeNote": "payee_note", "payerMessage": "payer_message",
+                "status": "FAILED", "reason": {"code": "PAYER_NOT_FOUND", "message": "payer not found"}
+            }}"#,
+        );
+        assert!(matches!(
+            failed,
+            CallbackResponse::RequestToPayFailed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_pre_approval_callbacks_round_trip() {
+        let success = round_trip(
+            r#"{"PreApprovalSuccess": {
+                "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                "payerCurrency": "EUR", "status": "SUCCESSFUL",
+                "expirationDateTime": "2024-05-01T12:00:00.000Z"
+            }}"#,
+        );
+        assert!(matches!(
+            success,
+            CallbackResponse::PreApprovalSuccess { .. }
+        ));
+
+        let failed = round_trip(
+            r#"{"PreApprovalFailed": {
+                "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                "payerCurrency": "EUR", "status": "FAILED",
+                "expirationDateTime": "2024-05-01T12:00:00.000Z",
+                "reason": {"code": "APPROVAL_REJECTED", "message": "rejected by the payer"}
+            }}"#,
+        );
+        assert!(matches!(failed, CallbackResponse::PreApprovalFailed { .. }));
+    }
+
+    #[test]
+    fn test_payment_callbacks_round_trip() {
+        let success = round_trip(
+            r#"{"PaymentSucceeded": {
+                "referenceId": "reference_id", "status": "SUCCESSFUL",
+                "financialTransactionId": "363440463"
+            }}"#,
+        );
+        assert!(matches!(success, CallbackResponse::PaymentSucceeded { .. }));
+
+        let failed = round_trip(
+            r#"{"PaymentFailed": {
+                "referenceId": "reference_id", "status": "FAILED",
+                "financialTransactionId": "363440463",
+                "reason": {"code": "COULD_NOT_PERFORM_TRANSACTION", "message": "not enough funds"}
+            }}"#,
+        );
+        assert!(matches!(failed, CallbackResponse::PaymentFailed { .. }));
+    }
+
+    #[test]
+    fn test_invoice_callbacks_round_trip() {
+        let success = round_trip(
+            r#"{"InvoiceSucceeded": {
+                "referenceId": "reference_id", "externalId": "external_id",
+                "amount": "100", "currency": "EUR", "status": "SUCCESSFUL",
+                "paymentReference": "payment_reference", "invoiceId": "invoice_id",
+                "expiryDateTime": "2024-05-01T12:00:00.000Z",
+                "intendedPayer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                "description": "invoice"
+            }}"#,
+        );
+        assert!(matches!(success, CallbackResponse::InvoiceSucceeded { .. }));
+
+        let failed = round_trip(
+            r#"{"InvoiceFailed": {
+                "referenceId": "reference_id", "externalId": "external_id",
+                "amount": "100", "currency": "EUR", "status": "FAILED",
+                "paymentReference": "payment_reference", "invoiceId": "invoice_id",
+                "expiryDateTime": "2024-05-01T12:00:00.000Z",
+                "intendedPayer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                "description": "invoice",
+                "errorReason": {"code": "EXPIRED", "message": "invoice expired"}
+            }}"#,
+        );
+        assert!(matches!(failed, CallbackResponse::InvoiceFailed { .. }));
+    }
+
+    #[test]
+    fn test_cash_transfer_callbacks_round_trip() {
+        let success = round_trip(
+            r#"{"CashTransferSucceeded": {
+                "financialTransactionId": "363440463", "status": "SUCCESSFUL", "reason": "",
+                "amount": "100", "currency": "EUR",
+                "payee": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                "externalId": "external_id", "originatingCountry": "UG",
+                "originalAmount": "100", "originalCurrency": "EUR",
+                "payerMessage": "payer_message", "payeeNote": "payee_note",
+                "payerIdentificationType": "PASS", "payerIdentificationNumber": "CA0000000",
+                "payerIdentity": "256774290781", "payerFirstName": "Jane",
+                "payerSurname": "Doe", "payerLanguageCode": "en",
+                "payerEmail": "jane@example.com", "payerMsisdn": "256774290781",
+                "payerGender": "F"
+            }}"#,
+        );
+        assert!(matches!(
+            success,
+            CallbackResponse::CashTransferSucceeded { .. }
+        ));
+
+        let failed = round_trip(
+            r#"{"CashTransferFailed": {
+                "financialTransactionId": "363440463", "status": "FAILED",
+                "reason": "payee not found", "amount": "100", "currency": "EUR",
+                "payee": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                "externalId": "external_id", "originatingCountry": "UG",
+                "originalAmount": "100", "originalCurrency": "EUR",
+                "payerMessage": "payer_message", "payeeNote": "payee_note",
+                "payerIdentificationType": "PASS", "payerIdentificationNumber": "CA0000000",
+                "payerIdentity": "256774290781", "payerFirstName": "Jane",
+                "payerSurname": "Doe", "payerLanguageCode": "en",
+                "payerEmail": "jane@example.com", "payerMsisdn": "256774290781",
+                "payerGender": "F",
+                "errorReason": {"code": "PAYEE_NOT_ALLOWED_TO_RECEIVE", "message": "payee not found"}
+            }}"#,
+        );
+        assert!(matches!(
+            failed,
+            CallbackResponse::CashTransferFailed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_callback_from_route_and_body() {
+        let failed = r#"{
+            "financialTransactionId": "363440463", "externalId": "external_id",
+            "amount": "100", "currency": "EUR",
+            "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "payeeNote": "payee_note", "payerMessage": "payer_message",
+            "status": "FAILED", "reason": {"code": "PAYER_NOT_FOUND", "message": "payer not found"}
+        }"#;
+        let response =
+            CallbackResponse::from_route_and_body(CallbackType::RequestToPay, failed).unwrap();
+        assert!(matches!(
+            response,
+            CallbackResponse::RequestToPayFailed { .. }
+        ));
+
+        let success = r#"{
+            "financialTransactionId": "363440463", "externalId": "external_id",
+            "amount": "100", "currency": "EUR",
+            "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "payeeNote": "payee_note", "payerMessage": "payer_message",
+            "status": "SUCCESSFUL"
+        }"#;
+        let response =
+            CallbackResponse::from_route_and_body(CallbackType::RequestToWithdrawV2, success)
+                .unwrap();
+        assert!(matches!(
+            response,
+            CallbackResponse::RequestToPaySuccess { .. }
+        ));
+
+        // a request to pay is not a payment, even though both carry a financial transaction id
+        assert!(
+            CallbackResponse::from_route_and_body(CallbackType::CollectionPayment, success)
+                .is_err()
+        );
+
+        // tagged bodies are accepted on their own route, and only there
+        let tagged = format!(r#"{{"RequestToPaySuccess": {}}}"#, success);
+        assert!(CallbackResponse::from_route_and_body(CallbackType::RequestToPay, &tagged).is_ok());
+        assert!(CallbackResponse::from_route_and_body(CallbackType::Invoice, &tagged).is_err());
+        assert!(CallbackResponse::from_route_and_body(CallbackType::None, &tagged).is_ok());
+    }
+
+    #[test]
+    fn test_parse_callback() {
+        let samples = [
+            (
+                "/collection_request_to_pay/REQUEST_TO_PAY",
+                r#"{"financialTransactionId": "363440463", "externalId": "external_id",
+                    "amount": "100", "currency": "EUR",
+                    "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                    "payeeNote": "payee_note", "payerMessage": "payer_message",
+                    "status": "SUCCESSFUL"}"#,
+                "RequestToPaySuccess",
+            ),
+            (
+                "/collection_request_to_pay/REQUEST_TO_PAY",
+                r#"{"financialTransactionId": "363440463", "externalId": "external_id",
+                    "amount": "100", "currency": "EUR",
+                    "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                    "payeeNote": "payee_note", "payerMessage": "payer_message",
+                    "status": "FAILED",
+                    "reason": {"code": "PAYER_NOT_FOUND", "message": "payer not found"}}"#,
+                "RequestToPayFailed",
+            ),
+            (
+                "/collection_preapproval/COLLECTION_PRE_APPROVAL",
+                r#"{"payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                    "payerCurrency": "EUR", "status": "SUCCESSFUL",
+                    "expirationDateTime": "2024-05-01T12:00:00.000Z"}"#,
+                "PreApprovalSuccess",
+            ),
+            (
+                "/collection_payment/COLLECTION_PAYMENT",
+                r#"{"referenceId": "reference_id", "status": "FAILED",
+                    "financialTransactionId": "363440463",
+                    "reason": {"code": "COULD_NOT_PERFORM_TRANSACTION", "message": "not enough funds"}}"#,
+                "PaymentFailed",
+            ),
+            (
+                "/collection_invoice/INVOICE",
+                r#"{"referenceId": "reference_id", "externalId": "external_id",
+                    "amount": "100", "currency": "EUR", "status": "SUCCESSFUL",
+                    "paymentReference": "payment_reference", "invoiceId": "invoice_id",
+                    "expiryDateTime": "2024-05-01T12:00:00.000Z",
+                    "intendedPayer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                    "description": "invoice"}"#,
+                "InvoiceSucceeded",
+            ),
+            (
+                "/remittance_cash_transfer/REMITTANCE_CASH_TRANSFER",
+                r#"{"financialTransactionId": "363440463", "status": "SUCCESSFUL", "reason": "",
+                    "amount": "100", "currency": "EUR",
+                    "payee": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                    "externalId": "external_id", "originatingCountry": "UG",
+                    "originalAmount": "100", "originalCurrency": "EUR",
+                    "payerMessage": "payer_message", "payeeNote": "payee_note",
+                    "payerIdentificationType": "PASS", "payerIdentificationNumber": "CA0000000",
+                    "payerIdentity": "256774290781", "payerFirstName": "Jane",
+                    "payerSurname": "Doe", "payerLanguageCode": "en",
+                    "payerEmail": "jane@example.com", "payerMsisdn": "256774290781",
+                    "payerGender": "F"}"#,
+                "CashTransferSucceeded",
+            ),
+        ];
+        for (path, body, variant) in samples {
+            let update = parse_callback(path, body).unwrap();
+            let response = serde_json::to_value(update.response.unwrap()).unwrap();
+            assert!(response.get(variant).is_some(), "{} on {}", variant, path);
+            assert_eq!(update.raw_body, body);
+            assert!(update.parse_error.is_none());
+        }
+
+        let err = parse_callback(
+            "/collection_payment/COLLECTION_PAYMENT",
+            r#"{"unknown": true}"#,
+        )
+        .unwrap_err();
+        assert_eq!(err.path, "/collection_payment/COLLECTION_PAYMENT");
+        assert!(err.to_string().starts_with(
+            "could not parse callback sent to /collection_payment/COLLECTION_PAYMENT"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_replay_callbacks() {
+        let bodies = vec![
+            r#"{"referenceId": "reference_id", "status": "SUCCESSFUL",
+                "financialTransactionId": "363440463"}"#
+                .to_string(),
+            r#"{"unknown": true}"#.to_string(),
+        ];
+        let updates: Vec<MomoUpdates> = futures_util::StreamExt::collect(replay_callbacks(
+            CallbackType::CollectionPayment,
+            bodies.clone(),
+        ))
+        .await;
+        assert_eq!(updates.len(), 2);
+        let expected =
+            parse_callback("/collection_payment/COLLECTION_PAYMENT", bodies[0].as_str()).unwrap();
+        assert_eq!(
+            serde_json::to_value(updates[0].response.as_ref().unwrap()).unwrap(),
+            serde_json::to_value(expected.response.unwrap()).unwrap()
+        );
+        assert_eq!(updates[0].update_type, CallbackType::CollectionPayment);
+        assert!(updates[1].response.is_none());
+        assert!(updates[1]
+            .parse_error
+            .as_ref()
+            .unwrap()
+            .contains("/collection_payment/COLLECTION_PAYMENT"));
+        assert_eq!(updates[1].raw_body, bodies[1]);
+    }
+
+    #[test]
+    fn test_callbacks_missing_optional_fields() {
+        let samples = [
+            (
+                CallbackType::RequestToPay,
+                r#"{"externalId": "external_id", "amount": "100", "currency": "EUR",
+                    "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                    "payerMessage": null, "status": "FAILED",
+                    "reason": {"code": "APPROVAL_REJECTED", "message": "rejected"},
+                    "newField": "ignored"}"#,
+                "RequestToPayFailed",
+            ),
+            (
+                CallbackType::RequestToPay,
+                r#"{"financialTransactionId": "363440463", "externalId": "external_id",
+                    "amount": "100", "currency": "EUR",
+                    "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                    "status": "SUCCESSFUL"}"#,
+                "RequestToPaySuccess",
+            ),
+            (
+                CallbackType::CollectionPayment,
+                r#"{"referenceId": "reference_id", "status": "FAILED",
+                    "reason": {"code": "COULD_NOT_PERFORM_TRANSACTION", "message": "no funds"}}"#,
+                "PaymentFailed",
+            ),
+            (
+                CallbackType::Invoice,
+                r#"{"referenceId": "reference_id", "externalId": "external_id",
+                    "amount": "100", "currency": "EUR", "status": "SUCCESSFUL",
+                    "paymentReference": "payment_reference", "invoiceId": "invoice_id",
+                    "expiryDateTime": "2024-05-01T12:00:00.000Z",
+                    "intendedPayer": {"partyIdType": "MSISDN", "partyId": "256774290781"}}"#,
+                "InvoiceSucceeded",
+            ),
+            (
+                CallbackType::RemittanceCashTransfer,
+                r#"{"financialTransactionId": "363440463", "status": "SUCCESSFUL",
+                    "amount": "100", "currency": "EUR",
+                    "payee": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                    "externalId": "external_id", "originatingCountry": "UG",
+                    "originalAmount": "100", "originalCurrency": "EUR"}"#,
+                "CashTransferSucceeded",
+            ),
+        ];
+        for (route, body, variant) in samples {
+            let response = CallbackResponse::from_route_and_body(route, body)
+                .unwrap_or_else(|err| panic!("{} on {}: {}", variant, route, err));
+            assert_eq!(response.variant_name(), variant);
+        }
+    }
+
+    #[test]
+    fn test_callback_with_unknown_reason() {
+        let failed = r#"{
+            "financialTransactionId": "363440463", "externalId": "external_id",
+            "amount": "100", "currency": "EUR",
+            "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "status": "FAILED", "reason": {"code": "PAYER_ON_HOLIDAY", "message": "new reason"}
+        }"#;
+        let response =
+            CallbackResponse::from_route_and_body(CallbackType::RequestToPay, failed).unwrap();
+        match response {
+            CallbackResponse::RequestToPayFailed { reason, .. } => {
+                assert_eq!(reason.code, RequestToPayReason::Unknown);
+                assert_eq!(reason.message, "new reason");
+            }
+            other => panic!("parsed as {}", other.variant_name()),
+        }
+    }
+
+    #[test]
+    fn test_transaction_event() {
+        let failed = r#"{
+            "financialTransactionId": "363440463", "externalId": "external_id",
+            "amount": "100", "currency": "EUR",
+            "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "status": "FAILED", "reason": {"code": "PAYER_NOT_FOUND", "message": "not found"}
+        }"#;
+        let response =
+            CallbackResponse::from_route_and_body(CallbackType::RequestToPay, failed).unwrap();
+        let event: Option<TransactionEvent> = response.into();
+        assert_eq!(
+            event,
+            Some(TransactionEvent {
+                external_id: "external_id".to_string(),
+                amount: "100".to_string(),
+                currency: "EUR".to_string(),
+                status: "FAILED".to_string(),
+                reason: Some("PAYER_NOT_FOUND".to_string()),
+            })
+        );
+
+        let deposit = r#"{
+            "financialTransactionId": "363440463", "externalId": "deposit_id",
+            "amount": "250", "currency": "EUR",
+            "payee": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "status": "SUCCESSFUL"
+        }"#;
+        let event =
+            CallbackResponse::from_route_and_body(CallbackType::DisbursementDepositV2, deposit)
+                .unwrap()
+                .transaction_event()
+                .unwrap();
+        assert_eq!(event.external_id, "deposit_id");
+        assert_eq!(event.amount, "250");
+        assert_eq!(event.status, "SUCCESSFUL");
+        assert_eq!(event.reason, None);
+
+        let pre_approval = r#"{"payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "payerCurrency": "EUR", "status": "SUCCESSFUL",
+            "expirationDateTime": "2024-05-01T12:00:00.000Z"}"#;
+        let response = CallbackResponse::from_route_and_body(
+            CallbackType::CollectionPreApproval,
+            pre_approval,
+        )
+        .unwrap();
+        assert_eq!(Option::<TransactionEvent>::from(response), None);
+    }
+
+    #[test]
+    fn test_disbursement_callbacks() {
+        let success = r#"{
+            "financialTransactionId": "363440463", "externalId": "external_id",
+            "amount": "100", "currency": "EUR",
+            "payee": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "payerMessage": "payer_message", "payeeNote": "payee_note",
+            "status": "SUCCESSFUL"
+        }"#;
+        let failed = r#"{
+            "externalId": "external_id",
+            "amount": "100", "currency": "EUR",
+            "payee": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "payerMessage": "payer_message", "payeeNote": "payee_note",
+            "status": "FAILED", "reason": {"code": "NOT_ALLOWED", "message": "payee not allowed"}
+        }"#;
+        for route in [
+            CallbackType::DisbursementDepositV1,
+            CallbackType::DisbursementDepositV2,
+            CallbackType::DisbursementRefundV2,
+            CallbackType::DisbusrementTransfer,
+            CallbackType::RemittanceTransfer,
+        ] {
+            let response = CallbackResponse::from_route_and_body(route, success).unwrap();
+            assert!(response.status_is_success(), "{}", route);
+            assert_eq!(response.external_id(), Some("external_id"));
+            let response = CallbackResponse::from_route_and_body(route, failed).unwrap();
+            assert!(matches!(
+                response,
+                CallbackResponse::DisbursementFailed {
+                    financial_transaction_id: None,
+                    ..
+                }
+            ));
+        }
+
+        // a transfer is not a request to pay, it has a payee and no payer
+        let err =
+            CallbackResponse::from_route_and_body(CallbackType::RequestToPay, success).unwrap_err();
+        assert!(err.to_string().contains("payer"));
+
+        let update =
+            parse_callback("/disbursement_deposit_v2/DISBURSEMENT_DEPOSIT_V2", success).unwrap();
+        assert_eq!(update.update_type, CallbackType::DisbursementDepositV2);
+        assert_eq!(
+            update.disbursement_operation(),
+            Some(DisbursementOperation::Deposit)
+        );
+        assert_eq!(
+            update.response.unwrap().variant_name(),
+            "DisbursementSuccess"
+        );
+        let update = parse_callback(&CallbackType::DisbusrementTransfer.path(), success).unwrap();
+        assert_eq!(
+            update.disbursement_operation(),
+            Some(DisbursementOperation::Transfer)
+        );
+        assert_eq!(
+            update.response.unwrap().variant_name(),
+            "DisbursementSuccess"
+        );
+        let update = parse_callback(&CallbackType::DisbursementRefundV1.path(), failed).unwrap();
+        assert_eq!(
+            update.disbursement_operation(),
+            Some(DisbursementOperation::Refund)
+        );
+        assert_eq!(CallbackType::RequestToPay.disbursement_operation(), None);
+    }
+
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_collection() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set"); // https://sandbox.momodeveloper.mtn.com
@@ -0,0 +1,92 @@
+//! Hooks reporting the activity of the products and of the callback server
+//!
+//! Implement `Metrics` to feed an existing dashboard, and set it with `with_metrics` on the
+//! products and in `CallbackServerConfig::metrics_hooks`. Every request sent to MTN is reported
+//! with the name of the operation, e.g. "request_to_pay", its HTTP status and how long it took.
+//!
+//! With the `metrics-crate` feature, `MetricsCrateRecorder` reports them to the `metrics` crate,
+//! e.g. to export them with `metrics-exporter-prometheus`.
+
+use std::time::Duration;
+
+use crate::CallbackType;
+
+/// # Metrics
+/// Told about every request sent to MTN and every callback received, every hook does nothing
+/// by default
+pub trait Metrics: Send + Sync {
+    /// A request is about to be sent
+    ///
+    /// # Parameters
+    ///
+    /// * 'operation', the operation sending it, e.g. "request_to_pay"
+    fn on_request(&self, _operation: &str) {}
+
+    /// The response of a request was received
+    ///
+    /// # Parameters
+    ///
+    /// * 'operation', the operation that sent the request, e.g. "request_to_pay"
+    /// * 'status', the HTTP status of the response, None when no response was received
+    /// * 'duration', how long the request took
+    fn on_response(&self, _operation: &str, _status: Option<u16>, _duration: Duration) {}
+
+    /// A callback was received by the callback server
+    ///
+    /// # Parameters
+    ///
+    /// * 'callback_type', the kind of callback, from the path it was sent to
+    fn on_callback(&self, _callback_type: CallbackType) {}
+}
+
+/// # NoopMetrics
+/// `Metrics` doing nothing, what the products and the callback server do without hooks
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {}
+
+/// # MetricsCrateRecorder
+/// `Metrics` reported to the recorder installed in the `metrics` crate:
+///
+/// - 'momo_requests_total', counter of the requests, by operation
+/// - 'momo_responses_total', counter of the responses, by operation and status, "none" when
+///   no response was received
+/// - 'momo_request_duration_seconds', histogram of the request durations, by operation
+/// - 'momo_callbacks_total', counter of the callbacks, by callback type
+///
+/// Requires the `metrics-crate` feature.
+#[cfg(feature = "metrics-crate")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricsCrateRecorder;
+
+#[cfg(feature = "metrics-crate")]
+impl Metrics for MetricsCrateRecorder {
+    fn on_request(&self, operation: &str) {
+        ::metrics::counter!("momo_requests_total", "operation" => operation.to_string())
+            .increment(1);
+    }
+
+    fn on_response(&self, operation: &str, status: Option<u16>, duration: Duration) {
+        let status = status.map_or("none".to_string(), |status| status.to_string());
+        ::metrics::counter!(
+            "momo_responses_total",
+            "operation" => operation.to_string(),
+            "status" => status
+        )
+        .increment(1);
+        ::metrics::histogram!(
+            "momo_request_duration_seconds",
+            "operation" => operation.to_string()
+        )
+        .record(duration.as_secs_f64());
+    }
+
+    fn on_callback(&self, callback_type: CallbackType) {
+        ::metrics::counter!(
+            "momo_callbacks_total",
+            "callback_type" => callback_type.to_string()
+        )
+        .increment(1);
+    }
+}
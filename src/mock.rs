@@ -0,0 +1,300 @@
+//! Mock MTN MoMo server
+//!
+//! A local stand-in for the MTN API, for testing applications built on this crate without the
+//! sandbox. It serves the token, request to pay, request to pay status and balance endpoints of
+//! every product with canned responses, and sends the request to pay callback to the
+//! X-Callback-Url of the request, like MTN does.
+//!
+//! Requires the `mock` feature.
+//!
+//! ```no_run
+//! use mtnmomo::{mock::MockMomoServer, Currency, Environment, MomoCollection, Party, RequestToPay};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let server = MockMomoServer::builder().start().await.unwrap();
+//!     let collection = MomoCollection::new(
+//!         server.url().to_string(),
+//!         Environment::Sandbox,
+//!         "api_user".to_string(),
+//!         "api_key".to_string(),
+//!         "primary_key".to_string(),
+//!         "secondary_key".to_string(),
+//!     );
+//!     let request = RequestToPay::new(
+//!         "100".to_string(),
+//!         Currency::EUR,
+//!         Party::msisdn("256774290781"),
+//!         "payer_message".to_string(),
+//!         "payee_note".to_string(),
+//!     );
+//!     let transaction_id = collection.request_to_pay(request, None).await.unwrap();
+//!     let result = collection
+//!         .request_to_pay_transaction_status(transaction_id.as_str())
+//!         .await
+//!         .unwrap();
+//!     assert_eq!(result.status, "SUCCESSFUL");
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+use poem::{
+    get, handler,
+    http::{HeaderMap, StatusCode},
+    listener::TcpAcceptor,
+    post,
+    web::{Data, Json, Path},
+    EndpointExt, Response, Route, Server,
+};
+
+use crate::{enums::reason::RequestToPayReason, Balance, Currency, RequestToPay};
+
+/// A request to pay received by the mock server, with the status it was given
+#[derive(Debug, Clone)]
+pub struct ReceivedRequestToPay {
+    pub reference_id: String,
+    pub request: RequestToPay,
+    pub callback_url: Option<String>,
+    pub failure: Option<RequestToPayReason>,
+}
+
+struct MockState {
+    balance: Balance,
+    failure: Option<RequestToPayReason>,
+    payer_failures: HashMap<String, RequestToPayReason>,
+    requests_to_pay: Mutex<Vec<ReceivedRequestToPay>>,
+    client: reqwest::Client,
+}
+
+impl MockState {
+    fn failure_for(&self, request: &RequestToPay) -> Option<RequestToPayReason> {
+        self.payer_failures
+            .get(&request.payer.party_id)
+            .copied()
+            .or(self.failure)
+    }
+}
+
+/// # MockMomoServerBuilder
+/// Configures the canned responses of a `MockMomoServer`
+///
+/// - 'balance', the balance of every product, default = 1000 EUR
+/// - 'failure', the reason every request to pay fails with, default = none, they succeed
+/// - 'payer_failures', the reason the requests to pay of a given payer fail with
+pub struct MockMomoServerBuilder {
+    balance: Balance,
+    failure: Option<RequestToPayReason>,
+    payer_failures: HashMap<String, RequestToPayReason>,
+}
+
+impl MockMomoServerBuilder {
+    /// Answer the balance endpoints with this balance
+    ///
+    /// # Parameters
+    ///
+    /// * 'available_balance', the available balance, e.g. "1000"
+    /// * 'currency', the currency of the balance
+    pub fn with_balance(mut self, available_balance: &str, currency: Currency) -> Self {
+        self.balance = Balance {
+            available_balance: available_balance.to_string(),
+            currency,
+        };
+        self
+    }
+
+    /// Make every request to pay fail with the given reason
+    pub fn with_failure(mut self, reason: RequestToPayReason) -> Self {
+        self.failure = Some(reason);
+        self
+    }
+
+    /// Make the requests to pay of one payer fail with the given reason
+    ///
+    /// # Parameters
+    ///
+    /// * 'party_id', the MSISDN or e-mail of the payer, as sent in the request
+    /// * 'reason', the reason the requests to pay fail with
+    pub fn with_payer_failure(mut self, party_id: &str, reason: RequestToPayReason) -> Self {
+        self.payer_failures.insert(party_id.to_string(), reason);
+        self
+    }
+
+    /// Start the server on a free port of 127.0.0.1
+    ///
+    /// # Returns
+    ///
+    /// * 'MockMomoServer', the running server, it stops with the tokio runtime
+    pub async fn start(self) -> Result<MockMomoServer, Box<dyn Error>> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let url = format!("http://{}", listener.local_addr()?);
+
+        let state = Arc::new(MockState {
+            balance: self.balance,
+            failure: self.failure,
+            payer_failures: self.payer_failures,
+            requests_to_pay: Mutex::new(Vec::new()),
+            client: crate::products::default_client(),
+        });
+        let app = Route::new()
+            .at("/:product/token/", post(token))
+            .at("/:product/v1_0/account/balance", get(balance))
+            .at("/collection/v1_0/requesttopay", post(request_to_pay))
+            .at(
+                "/collection/v1_0/requesttopay/:reference_id",
+                get(request_to_pay_status),
+            )
+            .data(state.clone());
+        tokio::spawn(Server::new_with_acceptor(TcpAcceptor::from_std(listener)?).run(app));
+
+        Ok(MockMomoServer { url, state })
+    }
+}
+
+/// # MockMomoServer
+/// A local server answering like the MTN API, see the module documentation
+pub struct MockMomoServer {
+    url: String,
+    state: Arc<MockState>,
+}
+
+impl MockMomoServer {
+    /// Configure a new server, succeeding every request to pay by default
+    pub fn builder() -> MockMomoServerBuilder {
+        MockMomoServerBuilder {
+            balance: Balance {
+                available_balance: "1000".to_string(),
+                currency: Currency::EUR,
+            },
+            failure: None,
+            payer_failures: HashMap::new(),
+        }
+    }
+
+    /// The url to give the products instead of the MTN url
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The requests to pay received so far, oldest first
+    pub fn requests_to_pay(&self) -> Vec<ReceivedRequestToPay> {
+        self.state.requests_to_pay.lock().unwrap().clone()
+    }
+}
+
+#[handler]
+fn token() -> Response {
+    Response::builder().content_type("application/json").body(
+        serde_json::json!({
+            "access_token": "mock_access_token",
+            "token_type": "access_token",
+            "expires_in": 3600
+        })
+        .to_string(),
+    )
+}
+
+#[handler]
+fn balance(Path(_product): Path<String>, Data(state): Data<&Arc<MockState>>) -> Response {
+    Response::builder()
+        .content_type("application/json")
+        .body(serde_json::to_string(&state.balance).unwrap_or_default())
+}
+
+/// The body MTN sends for a request to pay, as a status or as a callback
+fn request_to_pay_body(received: &ReceivedRequestToPay, reason_object: bool) -> serde_json::Value {
+    let request = &received.request;
+    let mut body = serde_json::json!({
+        "financialTransactionId": "1234567890",
+        "externalId": request.external_id,
+        "amount": request.amount,
+        "currency": request.currency,
+        "payer": request.payer,
+        "payerMessage": request.payer_message,
+        "payeeNote": request.payee_note,
+        "status": if received.failure.is_some() { "FAILED" } else { "SUCCESSFUL" },
+    });
+    if let Some(reason) = received.failure {
+        let code = serde_json::to_value(reason).unwrap_or_default();
+        body["reason"] = if reason_object {
+            serde_json::json!({"code": code, "message": "set by the mock server"})
+        } else {
+            code
+        };
+    }
+    body
+}
+
+#[handler]
+async fn request_to_pay(
+    headers: &HeaderMap,
+    Json(request): Json<RequestToPay>,
+    Data(state): Data<&Arc<MockState>>,
+) -> Response {
+    let Some(reference_id) = headers
+        .get("X-Reference-Id")
+        .and_then(|value| value.to_str().ok())
+    else {
+        return Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body("missing X-Reference-Id");
+    };
+    let received = ReceivedRequestToPay {
+        reference_id: reference_id.to_string(),
+        failure: state.failure_for(&request),
+        callback_url: headers
+            .get(crate::products::CALLBACK_URL_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        request,
+    };
+    if let Some(callback_url) = received.callback_url.clone() {
+        let client = state.client.clone();
+        let body = request_to_pay_body(&received, true);
+        tokio::spawn(async move {
+            if let Err(err) = client
+                .put(&callback_url)
+                .header("Content-Type", "application/json")
+                .body(body.to_string())
+                .send()
+                .await
+            {
+                tracing::warn!(
+                    "mock server could not send the callback to {}: {}",
+                    callback_url,
+                    err
+                );
+            }
+        });
+    }
+    state.requests_to_pay.lock().unwrap().push(received);
+    Response::builder().status(StatusCode::ACCEPTED).finish()
+}
+
+#[handler]
+fn request_to_pay_status(
+    Path(reference_id): Path<String>,
+    Data(state): Data<&Arc<MockState>>,
+) -> Response {
+    let requests = state.requests_to_pay.lock().unwrap();
+    match requests
+        .iter()
+        .find(|received| received.reference_id == reference_id)
+    {
+        Some(received) => Response::builder()
+            .content_type("application/json")
+            .body(request_to_pay_body(received, false).to_string()),
+        None => Response::builder().status(StatusCode::NOT_FOUND).body(
+            serde_json::json!({
+                "code": "RESOURCE_NOT_FOUND",
+                "message": "Requested resource was not found."
+            })
+            .to_string(),
+        ),
+    }
+}
@@ -1,8 +1,55 @@
-use crate::{Balance, BasicUserInfoJsonResponse, Currency, Environment, TokenResponse};
+use std::sync::Arc;
 
-pub struct Account {}
+use crate::{
+    metrics::Metrics, Balance, BasicUserInfoJsonResponse, Currency, Environment, PartyIdType,
+    TokenResponse,
+};
+
+use super::SendWithMetrics;
+
+#[derive(Clone, Default)]
+pub struct Account {
+    client: reqwest::Client,
+    metrics: Option<Arc<dyn Metrics>>,
+    headers: reqwest::header::HeaderMap,
+}
 
 impl Account {
+    /// Create the account helper on top of an already configured client
+    ///
+    /// # Parameters
+    ///
+    /// * 'client', the HTTP client used to call MTN
+    pub fn with_client(client: reqwest::Client) -> Account {
+        Account {
+            client,
+            metrics: None,
+            headers: reqwest::header::HeaderMap::new(),
+        }
+    }
+
+    /// Tell the given hooks about every request sent to MTN, see `Metrics`
+    ///
+    /// # Parameters
+    ///
+    /// * 'metrics', the hooks
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Account {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Send these headers with every request to MTN, e.g. a partner id required by an operator,
+    /// they are added to the headers already set and replace the operation's headers of the same
+    /// name
+    ///
+    /// # Parameters
+    ///
+    /// * 'headers', the headers to add
+    pub fn with_default_headers(mut self, headers: reqwest::header::HeaderMap) -> Account {
+        self.headers.extend(headers);
+        self
+    }
+
     /// This operation is used to get the balance of the account.
     /// # Parameters
     ///
@@ -21,14 +68,14 @@ impl Account {
         primary_key: String,
         access_token: TokenResponse,
     ) -> Result<Balance, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let res = client
             .get(format!("{}/v1_0/account/balance", url))
             .bearer_auth(access_token.access_token)
             .header("X-Target-Environment", environment.to_string())
             .header("Cache-Control", "no-cache")
             .header("Ocp-Apim-Subscription-Key", &primary_key)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "get_account_balance")
             .await?;
 
         if res.status().is_success() {
@@ -36,15 +83,14 @@ impl Account {
             let balance: Balance = serde_json::from_str(&body)?;
             Ok(balance)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
     /// this operation is used to get the balance of an account in a specific currency
     ///
+    /// The currency is sent as its ISO 4217 code, e.g. `/v1_0/account/balance/EUR`
+    ///
     /// # Parameters
     ///
     /// * 'url', the url of the product to get balance from
@@ -64,17 +110,17 @@ impl Account {
         currency: Currency,
         access_token: TokenResponse,
     ) -> Result<Balance, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let res = client
-            .get(format!(
-                "{}/v1_0/account/balance/{}",
-                url,
-                currency.to_string().to_lowercase()
-            ))
+            .get(format!("{}/v1_0/account/balance/{}", url, currency))
             .bearer_auth(access_token.access_token)
             .header("X-Target-Environment", environment.to_string())
             .header("Ocp-Apim-Subscription-Key", &primary_key)
-            .send()
+            .send_with(
+                self.metrics.as_deref(),
+                &self.headers,
+                "get_account_balance_in_specific_currency",
+            )
             .await?;
 
         if res.status().is_success() {
@@ -82,10 +128,7 @@ impl Account {
             let balance: Balance = serde_json::from_str(&body)?;
             Ok(balance)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -109,18 +152,53 @@ impl Account {
         account_holder_msisdn: &str,
         access_token: TokenResponse,
     ) -> Result<BasicUserInfoJsonResponse, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        self.get_basic_user_info_by_id(
+            url,
+            environment,
+            primary_key,
+            PartyIdType::MSISDN,
+            account_holder_msisdn,
+            access_token,
+        )
+        .await
+    }
+
+    /// This operation is used to get the basic information of the account holder, identified
+    /// by any kind of account holder id
+    ///
+    /// # Parameters
+    /// * 'url', the url of the product to get balance from
+    /// * 'environment', the environment of the installation
+    /// * 'primary_key', the primary key of the installation
+    /// * 'account_holder_id_type', the kind of id, e.g. EMAIL
+    /// * 'account_holder_id', the MSISDN, e-mail or party code of the account holder
+    /// * 'access_token', the access token to be used to make the request
+    ///
+    /// # Returns
+    ///
+    /// * 'BasicUserInfoJsonResponse'
+    pub async fn get_basic_user_info_by_id(
+        &self,
+        url: String,
+        environment: Environment,
+        primary_key: String,
+        account_holder_id_type: PartyIdType,
+        account_holder_id: &str,
+        access_token: TokenResponse,
+    ) -> Result<BasicUserInfoJsonResponse, Box<dyn std::error::Error>> {
+        let client = &self.client;
         let res = client
-            .get(format!(
-                "{}/v1_0/accountholder/msisdn/{}/basicuserinfo",
-                url, account_holder_msisdn
+            .get(basic_user_info_url(
+                &url,
+                account_holder_id_type,
+                account_holder_id,
             ))
             .bearer_auth(access_token.access_token)
             .header("Content-Type", "application/json")
             .header("X-Target-Environment", environment.to_string())
             .header("Ocp-Apim-Subscription-Key", &primary_key)
             .header("Cache-Control", "no-cache")
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "get_basic_user_info")
             .await?;
 
         if res.status().is_success() {
@@ -128,10 +206,7 @@ impl Account {
             let basic_user_info: BasicUserInfoJsonResponse = serde_json::from_str(&body)?;
             Ok(basic_user_info)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -154,14 +229,14 @@ impl Account {
         primary_key: String,
         access_token: String,
     ) -> Result<BasicUserInfoJsonResponse, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let res = client
             .get(format!("{}/oauth2/v1_0/userinfo", url))
             .bearer_auth(access_token)
             .header("X-Target-Environment", environment.to_string())
             .header("Ocp-Apim-Subscription-Key", &primary_key)
             .header("Cache-Control", "no-cache")
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "get_user_info_with_consent")
             .await?;
 
         if res.status().is_success() {
@@ -169,10 +244,7 @@ impl Account {
             let basic_user_info: BasicUserInfoJsonResponse = serde_json::from_str(&body)?;
             Ok(basic_user_info)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -201,7 +273,7 @@ impl Account {
         account_holder_type: &str,
         access_token: TokenResponse,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let res = client
             .get(format!(
                 "{}/v1_0/accountholder/{}/{}/active",
@@ -210,16 +282,46 @@ impl Account {
             .bearer_auth(access_token.access_token)
             .header("X-Target-Environment", environment.to_string())
             .header("Ocp-Apim-Subscription-Key", &primary_key)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "validate_account_holder_status")
             .await?;
 
         if res.status().is_success() {
             Ok(())
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 }
+
+/// The basic user info url of an account holder, e.g.
+/// "{url}/v1_0/accountholder/email/payer@example.com/basicuserinfo"
+fn basic_user_info_url(
+    url: &str,
+    account_holder_id_type: PartyIdType,
+    account_holder_id: &str,
+) -> String {
+    format!(
+        "{}/v1_0/accountholder/{}/{}/basicuserinfo",
+        url,
+        account_holder_id_type.account_holder_id_type(),
+        account_holder_id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basic_user_info_url() {
+        let url = "https://sandbox.momodeveloper.mtn.com/collection";
+        assert_eq!(
+            basic_user_info_url(url, PartyIdType::MSISDN, "256774290781"),
+            "https://sandbox.momodeveloper.mtn.com/collection/v1_0/accountholder/msisdn/256774290781/basicuserinfo"
+        );
+        assert_eq!(
+            basic_user_info_url(url, PartyIdType::EMAIL, "payer@example.com"),
+            "https://sandbox.momodeveloper.mtn.com/collection/v1_0/accountholder/email/payer@example.com/basicuserinfo"
+        );
+    }
+}
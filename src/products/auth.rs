@@ -1,11 +1,55 @@
+use std::sync::Arc;
+
 use crate::{
-    AccessTokenRequest, AccessType, BCAuthorizeResponse, BcAuthorizeRequest, Environment,
-    OAuth2TokenResponse, TokenResponse,
+    metrics::Metrics, AccessTokenRequest, AccessType, BCAuthorizeResponse, BcAuthorizeRequest,
+    Environment, OAuth2TokenResponse, TokenResponse,
 };
 
-pub struct Authorization {}
+use super::SendWithMetrics;
+
+#[derive(Clone, Default)]
+pub struct Authorization {
+    client: reqwest::Client,
+    metrics: Option<Arc<dyn Metrics>>,
+    headers: reqwest::header::HeaderMap,
+}
 
 impl Authorization {
+    /// Create the authorization helper on top of an already configured client
+    ///
+    /// # Parameters
+    ///
+    /// * 'client', the HTTP client used to call MTN
+    pub fn with_client(client: reqwest::Client) -> Authorization {
+        Authorization {
+            client,
+            metrics: None,
+            headers: reqwest::header::HeaderMap::new(),
+        }
+    }
+
+    /// Tell the given hooks about every request sent to MTN, see `Metrics`
+    ///
+    /// # Parameters
+    ///
+    /// * 'metrics', the hooks
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Authorization {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Send these headers with every request to MTN, e.g. a partner id required by an operator,
+    /// they are added to the headers already set and replace the operation's headers of the same
+    /// name
+    ///
+    /// # Parameters
+    ///
+    /// * 'headers', the headers to add
+    pub fn with_default_headers(mut self, headers: reqwest::header::HeaderMap) -> Authorization {
+        self.headers.extend(headers);
+        self
+    }
+
     /// This operation is used to create an access token
     ///
     /// # Parameters
@@ -25,7 +69,7 @@ impl Authorization {
         api_key: String,
         primary_key: String,
     ) -> Result<TokenResponse, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let res = client
             .post(format!("{}/token/", url))
             .basic_auth(api_user, Some(api_key))
@@ -34,7 +78,7 @@ impl Authorization {
             .header("Ocp-Apim-Subscription-Key", &primary_key)
             .header("Content-Length", "0")
             .body("")
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "create_access_token")
             .await?;
 
         if res.status().is_success() {
@@ -42,10 +86,7 @@ impl Authorization {
             let token_response: TokenResponse = serde_json::from_str(&body)?;
             Ok(token_response)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -72,7 +113,7 @@ impl Authorization {
         primary_key: String,
         auth_req_id: String,
     ) -> Result<OAuth2TokenResponse, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let res = client
             .post(format!("{}/oauth2/token/", url))
             .basic_auth(api_user.to_string(), Some(api_key.to_string()))
@@ -83,7 +124,7 @@ impl Authorization {
                 grant_type: "urn:openid:params:grant-type:ciba".to_string(),
                 auth_req_id,
             })
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "create_o_auth_2_token")
             .await?;
 
         if res.status().is_success() {
@@ -91,10 +132,7 @@ impl Authorization {
             let token_response: OAuth2TokenResponse = serde_json::from_str(&body)?;
             Ok(token_response)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -107,10 +145,13 @@ impl Authorization {
     /// * 'access_token', the access token to be used to make the request
     /// * 'msisdn', this is the phone number of the user
     /// * 'callback_url', this is the url that will be used to notify the client of the status of the transaction
+    /// * 'scope', the scope requested from the user, default = profile
+    /// * 'access_type', the access type requested, default = offline
     ///
     /// # Returns
     ///
     /// * 'BCAuthorizeResponse'
+    #[allow(clippy::too_many_arguments)]
     pub async fn bc_authorize(
         &self,
         url: String,
@@ -119,8 +160,10 @@ impl Authorization {
         msisdn: String,
         callback_url: Option<&str>,
         access_token: TokenResponse,
+        scope: Option<&str>,
+        access_type: Option<AccessType>,
     ) -> Result<BCAuthorizeResponse, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let mut req = client
             .post(format!("{}/v1_0/bc-authorize", url))
             .bearer_auth(access_token.access_token)
@@ -130,29 +173,24 @@ impl Authorization {
             .body(
                 BcAuthorizeRequest {
                     login_hint: format!("ID:{}/MSISDN", msisdn),
-                    scope: "profile".to_string(),
-                    access_type: AccessType::Offline,
+                    scope: scope.unwrap_or("profile").to_string(),
+                    access_type: access_type.unwrap_or(AccessType::Offline),
                 }
                 .to_string(),
             );
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
+        req = super::with_callback_url(req, callback_url);
 
-        let res = req.send().await?;
+        let res = req
+            .send_with(self.metrics.as_deref(), &self.headers, "bc_authorize")
+            .await?;
 
         if res.status().is_success() {
             let body = res.text().await?;
             let token_response: BCAuthorizeResponse = serde_json::from_str(&body)?;
             Ok(token_response)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 }
@@ -11,21 +11,29 @@
 
 use std::sync::Arc;
 
+use super::SendWithMetrics;
 use crate::{
-    BCAuthorizeResponse, Balance, BasicUserInfoJsonResponse, CreatePaymentRequest, Currency,
-    DeliveryNotificationRequest, Environment, InvoiceDeleteRequest, InvoiceId, InvoiceRequest,
-    InvoiceResult, OAuth2TokenResponse, PaymentId, PaymentResult, PreApprovalRequest,
-    PreApprovalResult, RequestToPay, RequestToPayResult, TokenResponse, TransactionId, WithdrawId,
+    metrics::Metrics, AccessType, BCAuthorizeResponse, Balance, BasicUserInfoJsonResponse,
+    CreatePaymentRequest, Currency, DeliveryNotificationRequest, DuplicateReference, Environment,
+    InvoiceDeleteRequest, InvoiceId, InvoiceRequest, InvoiceResult, MsisdnPolicy,
+    OAuth2TokenResponse, PartyIdType, PaymentId, PaymentResult, PreApprovalCreated,
+    PreApprovalRequest, PreApprovalResult, ReferenceId, RequestToPay, RequestToPayResult,
+    Submitted, TokenResponse, TransactionId, WithdrawId,
 };
 use chrono::Utc;
+use futures_util::StreamExt;
 use once_cell::sync::Lazy;
 use tokio::sync::RwLock;
 
-use super::{account::Account, auth::Authorization};
+use super::{
+    account::Account, auth::Authorization, idempotency::IdempotencyStore,
+    invoice_store::InvoiceStore,
+};
 
 /// # Collection
 /// This product provides a way to request payments from a customer.
 /// # Example
+#[derive(Clone)]
 pub struct Collection {
     pub url: String,
     pub primary_key: String,
@@ -35,6 +43,12 @@ pub struct Collection {
     pub api_key: String,
     account: Account,
     auth: Authorization,
+    client: reqwest::Client,
+    metrics: Option<Arc<dyn Metrics>>,
+    headers: reqwest::header::HeaderMap,
+    idempotency_store: Option<Arc<dyn IdempotencyStore>>,
+    invoice_store: Option<Arc<dyn InvoiceStore>>,
+    msisdn_policy: MsisdnPolicy,
 }
 
 static ACCESS_TOKEN: Lazy<Arc<RwLock<Option<TokenResponse>>>> =
@@ -62,8 +76,78 @@ impl Collection {
         primary_key: String,
         secondary_key: String,
     ) -> Collection {
-        let account = Account {};
-        let auth = Authorization {};
+        Collection::with_client(
+            url,
+            environment,
+            api_user,
+            api_key,
+            primary_key,
+            secondary_key,
+            super::default_client(),
+        )
+    }
+
+    /// Create a new instance of Collection, optionally sending its requests through a proxy
+    ///
+    /// # Parameters
+    ///
+    /// * 'url', MTN MOMO collection url
+    /// * 'environment', environement to be used
+    /// * 'api_user', the api user to be used
+    /// * 'api_key', the api key to be use
+    /// * 'primary_key', the primary key of the collection product
+    /// * 'secondary_key', the secondary key of the collection product
+    /// * 'proxy', url of the outbound proxy, None to connect directly
+    ///
+    /// # Returns
+    /// * Collection, or an error when the proxy url is not valid
+    pub fn with_proxy(
+        url: String,
+        environment: Environment,
+        api_user: String,
+        api_key: String,
+        primary_key: String,
+        secondary_key: String,
+        proxy: Option<String>,
+    ) -> Result<Collection, Box<dyn std::error::Error>> {
+        let client = super::build_client(proxy.as_deref())?;
+        Ok(Collection::with_client(
+            url,
+            environment,
+            api_user,
+            api_key,
+            primary_key,
+            secondary_key,
+            client,
+        ))
+    }
+
+    /// Create a new instance of Collection sending its requests with the given client,
+    /// for example one going through a proxy or adding headers expected by an API gateway
+    ///
+    /// # Parameters
+    ///
+    /// * 'url', MTN MOMO collection url
+    /// * 'environment', environement to be used
+    /// * 'api_user', the api user to be used
+    /// * 'api_key', the api key to be use
+    /// * 'primary_key', the primary key of the collection product
+    /// * 'secondary_key', the secondary key of the collection product
+    /// * 'client', the HTTP client used for every call to MTN
+    ///
+    /// # Returns
+    /// * Collection
+    pub fn with_client(
+        url: String,
+        environment: Environment,
+        api_user: String,
+        api_key: String,
+        primary_key: String,
+        secondary_key: String,
+        client: reqwest::Client,
+    ) -> Collection {
+        let account = Account::with_client(client.clone());
+        let auth = Authorization::with_client(client.clone());
         Collection {
             url,
             primary_key,
@@ -73,15 +157,109 @@ impl Collection {
             api_key,
             account,
             auth,
+            client,
+            metrics: None,
+            headers: reqwest::header::HeaderMap::new(),
+            msisdn_policy: MsisdnPolicy::default(),
+            idempotency_store: None,
+            invoice_store: None,
         }
     }
 
+    /// Format the mobile numbers sent to MTN according to the given policy, default = KeepPlus
+    ///
+    /// # Parameters
+    ///
+    /// * 'policy', how the mobile numbers are formatted
+    ///
+    /// # Returns
+    /// * Collection
+    pub fn with_msisdn_policy(mut self, policy: MsisdnPolicy) -> Collection {
+        self.msisdn_policy = policy;
+        self
+    }
+
+    /// How the mobile numbers sent to MTN are formatted
+    pub fn msisdn_policy(&self) -> MsisdnPolicy {
+        self.msisdn_policy
+    }
+
+    /// Tell the given hooks about every request sent to MTN and how long it took, see `Metrics`
+    ///
+    /// # Parameters
+    ///
+    /// * 'metrics', the hooks
+    ///
+    /// # Returns
+    /// * Collection
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Collection {
+        self.account = self.account.with_metrics(metrics.clone());
+        self.auth = self.auth.with_metrics(metrics.clone());
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Send these headers with every request to MTN, e.g. a partner id required by an operator
+    /// or the X-Forwarded-* headers expected by an intermediary
+    ///
+    /// They are added to the headers already set and replace the operation's headers of the same
+    /// name. For the headers of a single call, set them on a clone of the product,
+    /// `collection.clone().with_default_headers(headers).request_to_pay(request)`.
+    ///
+    /// # Parameters
+    ///
+    /// * 'headers', the headers to add
+    ///
+    /// # Returns
+    /// * Collection
+    pub fn with_default_headers(mut self, headers: reqwest::header::HeaderMap) -> Collection {
+        self.account = self.account.with_default_headers(headers.clone());
+        self.auth = self.auth.with_default_headers(headers.clone());
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Refuse the requests to pay that already succeeded with a `DuplicateReference` error,
+    /// see `IdempotencyStore`
+    ///
+    /// # Parameters
+    ///
+    /// * 'store', where the successful requests are remembered
+    ///
+    /// # Returns
+    /// * Collection
+    pub fn with_idempotency_store(mut self, store: Arc<dyn IdempotencyStore>) -> Collection {
+        self.idempotency_store = Some(store);
+        self
+    }
+
+    /// Tell the given store about every invoice created and cancelled, see `InvoiceStore`
+    ///
+    /// # Parameters
+    ///
+    /// * 'store', where the invoices are tracked
+    ///
+    /// # Returns
+    /// * Collection
+    pub fn with_invoice_store(mut self, store: Arc<dyn InvoiceStore>) -> Collection {
+        self.invoice_store = Some(store);
+        self
+    }
+
     /// This operation is used to create an access token
     ///
+    /// Normal flows don't need it, every operation requests and caches a token on its own.
+    /// It is meant for calling MTN endpoints this crate does not wrap; the new token is cached
+    /// and used by the next operations.
+    ///
     /// # Returns
     ///
     /// * 'TokenResponse'
-    async fn create_access_token(&self) -> Result<TokenResponse, Box<dyn std::error::Error>> {
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "collection", operation = "create_access_token")
+    )]
+    pub async fn create_access_token(&self) -> Result<TokenResponse, Box<dyn std::error::Error>> {
         let url = format!("{}/{}", self.url, "collection");
         let token = self
             .auth
@@ -118,7 +296,7 @@ impl Collection {
                 url,
                 self.api_user.clone(),
                 self.api_key.clone(),
-                self.environment.clone(),
+                self.environment,
                 self.primary_key.clone(),
                 auth_req_id,
             )
@@ -131,26 +309,33 @@ impl Collection {
     ///
     /// * 'msisdn', this is the phone number of the user
     /// * 'callback_url', this is the url that will be used to notify the client of the status of the transaction
+    /// * 'scope', the scope requested from the user, e.g. "profile" or "openid", default = profile
+    /// * 'access_type', the access type requested, default = offline
     ///
     /// # Returns
     ///
     /// * 'BCAuthorizeResponse'
-    #[allow(dead_code)]
-    async fn bc_authorize(
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "bc_authorize"))]
+    pub async fn bc_authorize(
         &self,
         msisdn: String,
         callback_url: Option<&str>,
+        scope: Option<&str>,
+        access_type: Option<AccessType>,
     ) -> Result<BCAuthorizeResponse, Box<dyn std::error::Error>> {
+        let msisdn = self.msisdn_policy.normalize(&msisdn);
         let url = format!("{}/{}", self.url, "collection");
         let access_token: TokenResponse = self.create_access_token().await?;
         self.auth
             .bc_authorize(
                 url,
-                self.environment.clone(),
+                self.environment,
                 self.primary_key.clone(),
                 msisdn,
                 callback_url,
                 access_token,
+                scope,
+                access_type,
             )
             .await
     }
@@ -159,26 +344,50 @@ impl Collection {
     ///
     /// # Returns
     /// * 'TokenResponse'
-    async fn get_valid_access_token(&self) -> Result<TokenResponse, Box<dyn std::error::Error>> {
-        let token = ACCESS_TOKEN.read().await;
-        if token.is_some() {
-            let token = token.clone().unwrap();
-            if token.created_at.is_some() {
-                let created_at = token.created_at.unwrap();
+    pub(crate) async fn get_valid_access_token(
+        &self,
+    ) -> Result<TokenResponse, Box<dyn std::error::Error>> {
+        let token = ACCESS_TOKEN.read().await.clone();
+        if let Some(token) = token {
+            if let Some(created_at) = token.created_at {
                 let expires_in = token.expires_in;
                 let now = Utc::now();
                 let duration = now.signed_duration_since(created_at);
                 if duration.num_seconds() < expires_in as i64 {
+                    tracing::trace!("using the cached access token");
                     return Ok(token);
                 }
-                drop(token);
-                let token: TokenResponse = self.create_access_token().await?;
-                return Ok(token);
             }
         }
-        drop(token);
+        tracing::debug!("requesting a new access token");
         let token: TokenResponse = self.create_access_token().await?;
-        return Ok(token);
+        Ok(token)
+    }
+
+    /// Add the headers shared by the invoice creation and cancellation requests
+    ///
+    /// # Parameters
+    ///
+    /// * 'req', the request to complete
+    /// * 'access_token', a valid access token
+    /// * 'reference_id', the X-Reference-Id of the request
+    /// * 'callback_url', The callback url to be used by the MOMO Core API to notify the merchant of the invoice status
+    fn invoice_headers(
+        &self,
+        req: reqwest::RequestBuilder,
+        access_token: &TokenResponse,
+        reference_id: &str,
+        callback_url: Option<&str>,
+    ) -> reqwest::RequestBuilder {
+        let mut req = req
+            .bearer_auth(&access_token.access_token)
+            .header("Content-Type", "application/json")
+            .header("X-Target-Environment", self.environment.to_string())
+            .header("X-Reference-Id", reference_id)
+            .header("Ocp-Apim-Subscription-Key", &self.primary_key);
+
+        req = super::with_callback_url(req, callback_url);
+        req
     }
 
     /// This operation is used to cancel an invoice.
@@ -190,42 +399,40 @@ impl Collection {
     ///
     /// # Returns
     /// * (), the request is ok
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "cancel_invoice", external_id = %invoice_id))]
     pub async fn cancel_invoice(
         &self,
         invoice_id: &str,
         callback_url: Option<&str>,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
-        let mut req = client
+        let req = client
             .delete(format!(
                 "{}/collection/v2_0/invoice/{}",
                 self.url, invoice_id
             ))
-            .bearer_auth(access_token.access_token)
-            .header("Content-Type", "application/json")
-            .header("X-Target-Environment", self.environment.to_string())
-            .header("X-Reference-Id", uuid::Uuid::new_v4().to_string())
-            .header("Ocp-Apim-Subscription-Key", &self.primary_key)
             .body(InvoiceDeleteRequest {
                 external_id: invoice_id.to_string(),
             });
+        let req = self.invoice_headers(
+            req,
+            &access_token,
+            ReferenceId::new().as_str(),
+            callback_url,
+        );
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
-
-        let res = req.send().await?;
+        let res = req
+            .send_with(self.metrics.as_deref(), &self.headers, "cancel_invoice")
+            .await?;
 
         if res.status().is_success() {
+            if let Some(store) = &self.invoice_store {
+                store.cancelled(invoice_id);
+            }
             Ok(())
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -238,39 +445,34 @@ impl Collection {
     ///
     /// # Returns
     ///
-    /// * 'InvoiceId', the is the exeternal id of the invoice
+    /// * 'Submitted<InvoiceId>', the is the exeternal id of the invoice
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "create_invoice", external_id = %invoice.external_id))]
     pub async fn create_invoice(
         &self,
-        invoice: InvoiceRequest,
+        mut invoice: InvoiceRequest,
         callback_url: Option<&str>,
-    ) -> Result<InvoiceId, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+    ) -> Result<Submitted<InvoiceId>, Box<dyn std::error::Error>> {
+        invoice.intended_payer = invoice.intended_payer.normalized(self.msisdn_policy);
+        invoice.payee = invoice.payee.normalized(self.msisdn_policy);
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
-        let mut req = client
+        let req = client
             .post(format!("{}/collection/v2_0/invoice", self.url))
-            .bearer_auth(access_token.access_token)
-            .header("X-Reference-Id", &invoice.external_id)
-            .header("X-Target-Environment", self.environment.to_string())
-            .header("Content-Type", "application/json")
-            .header("Ocp-Apim-Subscription-Key", &self.primary_key)
             .body(invoice.clone());
+        let req = self.invoice_headers(req, &access_token, &invoice.external_id, callback_url);
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
-
-        let res = req.send().await?;
+        let res = req
+            .send_with(self.metrics.as_deref(), &self.headers, "create_invoice")
+            .await?;
 
         if res.status().is_success() {
-            Ok(InvoiceId(invoice.external_id))
+            if let Some(store) = &self.invoice_store {
+                store.created(&invoice);
+            }
+            Ok(Submitted::new(InvoiceId(invoice.external_id), res.status()))
         } else {
             let res_clone = res.text().await?;
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res_clone,
-            )))
+            Err(Box::new(std::io::Error::other(res_clone)))
         }
     }
 
@@ -282,13 +484,14 @@ impl Collection {
     ///
     /// # Returns
     ///
-    /// * 'PaymentId', this is the external id of the payment
+    /// * 'Submitted<PaymentId>', this is the external id of the payment
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "create_payments", external_id = %payment.external_transaction_id))]
     pub async fn create_payments(
         &self,
         payment: CreatePaymentRequest,
         callback_url: Option<&str>,
-    ) -> Result<PaymentId, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+    ) -> Result<Submitted<PaymentId>, Box<dyn std::error::Error>> {
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let mut req = client
             .post(format!("{}/collection/v2_0/payment", self.url))
@@ -299,21 +502,19 @@ impl Collection {
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
             .body(payment.clone());
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
+        req = super::with_callback_url(req, callback_url);
 
-        let res = req.send().await?;
+        let res = req
+            .send_with(self.metrics.as_deref(), &self.headers, "create_payments")
+            .await?;
 
         if res.status().is_success() {
-            Ok(PaymentId(payment.external_transaction_id))
+            Ok(Submitted::new(
+                PaymentId(payment.external_transaction_id),
+                res.status(),
+            ))
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -326,12 +527,12 @@ impl Collection {
     /// # Returns
     ///
     /// * 'InvoiceResult'
-    #[allow(dead_code)]
-    async fn get_invoice_status(
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "get_invoice_status", external_id = %invoice_id))]
+    pub async fn get_invoice_status(
         &self,
         invoice_id: String,
     ) -> Result<InvoiceResult, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let res = client
             .get(format!(
@@ -341,7 +542,7 @@ impl Collection {
             .bearer_auth(access_token.access_token)
             .header("X-Target-Environment", self.environment.to_string())
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "get_invoice_status")
             .await?;
 
         if res.status().is_success() {
@@ -349,10 +550,43 @@ impl Collection {
             let invoice_status: InvoiceResult = serde_json::from_str(&body)?;
             Ok(invoice_status)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
+        }
+    }
+
+    /// Poll the status of an invoice until it is final (successful, failed, cancelled or expired)
+    ///
+    /// # Parameters
+    ///
+    /// * 'invoice_id', the invoice id to get the status from
+    /// * 'timeout', how long to wait for a final status before giving up
+    /// * 'poll_interval', how long to wait between two status requests
+    ///
+    /// # Returns
+    ///
+    /// * 'InvoiceResult', the last status of the invoice, or a TimedOut error when it is still pending
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "await_invoice_final", external_id = %invoice_id))]
+    pub async fn await_invoice_final(
+        &self,
+        invoice_id: &str,
+        timeout: std::time::Duration,
+        poll_interval: std::time::Duration,
+    ) -> Result<InvoiceResult, Box<dyn std::error::Error>> {
+        // chrono rather than Instant, which is not available on wasm32
+        let deadline = Utc::now() + chrono::Duration::from_std(timeout)?;
+        let interval_chrono = chrono::Duration::from_std(poll_interval)?;
+        loop {
+            let invoice = self.get_invoice_status(invoice_id.to_string()).await?;
+            if invoice.status_enum().is_final() {
+                return Ok(invoice);
+            }
+            if Utc::now() + interval_chrono > deadline {
+                return Err(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("invoice {} is still {}", invoice_id, invoice.status),
+                )));
+            }
+            super::sleep(poll_interval).await;
         }
     }
 
@@ -371,7 +605,7 @@ impl Collection {
         &self,
         payment_id: String,
     ) -> Result<PaymentResult, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let res = client
             .get(format!(
@@ -381,7 +615,7 @@ impl Collection {
             .bearer_auth(access_token.access_token)
             .header("X-Target-Environment", self.environment.to_string())
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "get_payment_status")
             .await?;
 
         if res.status().is_success() {
@@ -389,10 +623,7 @@ impl Collection {
             let payment_status: PaymentResult = serde_json::from_str(&body)?;
             Ok(payment_status)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -406,12 +637,12 @@ impl Collection {
     /// # Returns
     ///
     /// * 'PreApprovalResult'
-    #[allow(dead_code)]
-    async fn get_pre_approval_status(
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "get_pre_approval_status", external_id = %pre_approval_id))]
+    pub async fn get_pre_approval_status(
         &self,
         pre_approval_id: String,
     ) -> Result<PreApprovalResult, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let res = client
             .get(format!(
@@ -422,7 +653,7 @@ impl Collection {
             .header("X-Target-Environment", self.environment.to_string())
             .header("Cache-Control", "no-cache")
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "get_pre_approval_status")
             .await?;
 
         if res.status().is_success() {
@@ -430,10 +661,49 @@ impl Collection {
             let pre_approval_status: PreApprovalResult = serde_json::from_str(&body)?;
             Ok(pre_approval_status)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
+        }
+    }
+
+    /// Poll the status of a pre-approval until the payer approved or rejected it, or it expired
+    ///
+    /// # Parameters
+    ///
+    /// * 'pre_approval_id', the external id of the pre-approval, see `create_pre_approval`
+    /// * 'timeout', how long to wait for the payer before giving up
+    /// * 'poll_interval', how long to wait between two status requests
+    ///
+    /// # Returns
+    ///
+    /// * 'PreApprovalResult', the last status of the pre-approval, or a TimedOut error when it is
+    ///   still pending
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "await_pre_approval", external_id = %pre_approval_id))]
+    pub async fn await_pre_approval(
+        &self,
+        pre_approval_id: &str,
+        timeout: std::time::Duration,
+        poll_interval: std::time::Duration,
+    ) -> Result<PreApprovalResult, Box<dyn std::error::Error>> {
+        // chrono rather than Instant, which is not available on wasm32
+        let deadline = Utc::now() + chrono::Duration::from_std(timeout)?;
+        let interval_chrono = chrono::Duration::from_std(poll_interval)?;
+        loop {
+            let pre_approval = self
+                .get_pre_approval_status(pre_approval_id.to_string())
+                .await?;
+            if !pre_approval.is_pending() {
+                return Ok(pre_approval);
+            }
+            if Utc::now() + interval_chrono > deadline {
+                return Err(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!(
+                        "pre-approval {} is still {}",
+                        pre_approval_id, pre_approval.status
+                    ),
+                )));
+            }
+            super::sleep(poll_interval).await;
         }
     }
 
@@ -442,12 +712,42 @@ impl Collection {
     /// # Parameters
     ///
     /// * 'preaproval'; the pre-approval to be created on the MOMO Core API
+    ///
+    /// # Returns
+    ///
+    /// * 'String', the external id of the pre-approval, see `create_pre_approval` for its expiry
     pub async fn pre_approval(
         &self,
         preaproval: PreApprovalRequest,
     ) -> Result<String, Box<dyn std::error::Error>> {
-        let external_id = uuid::Uuid::new_v4().to_string();
-        let client = reqwest::Client::new();
+        let created = self.create_pre_approval(preaproval).await?;
+        Ok(created.external_id)
+    }
+
+    /// Creates a pre-approval and returns what MTN sent back about it
+    ///
+    /// # Parameters
+    ///
+    /// * 'preaproval'; the pre-approval to be created on the MOMO Core API
+    ///
+    /// # Returns
+    ///
+    /// * 'PreApprovalCreated', the external id of the pre-approval (the one set with
+    ///   `PreApprovalRequest::with_reference_id`, or a new UUID), with its status and expiration
+    ///   date time when MTN returns them
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "pre_approval", external_id = tracing::field::Empty))]
+    pub async fn create_pre_approval(
+        &self,
+        mut preaproval: PreApprovalRequest,
+    ) -> Result<PreApprovalCreated, Box<dyn std::error::Error>> {
+        preaproval.validate()?;
+        preaproval.payer = preaproval.payer.normalized(self.msisdn_policy);
+        let external_id = preaproval
+            .reference_id
+            .clone()
+            .unwrap_or_else(|| ReferenceId::new().into());
+        tracing::Span::current().record("external_id", tracing::field::display(&external_id));
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let res = client
             .post(format!("{}/collection/v2_0/preapproval", self.url))
@@ -458,16 +758,32 @@ impl Collection {
             .header("X-Reference-Id", &external_id)
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
             .body(preaproval)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "create_pre_approval")
             .await?;
 
         if res.status().is_success() {
-            Ok(external_id)
+            // MTN usually answers 202 with an empty body, the fields are only there when it doesn't
+            let body = res.text().await?;
+            let mut created = PreApprovalCreated {
+                external_id: String::new(),
+                status: None,
+                expiration_date_time: None,
+            };
+            if !body.trim().is_empty() {
+                // the pre-approval is created all the same, an error would have it created again
+                match serde_json::from_str(&body) {
+                    Ok(parsed) => created = parsed,
+                    Err(err) => tracing::warn!(
+                        "could not parse the answer to the pre-approval {}: {}",
+                        external_id,
+                        err
+                    ),
+                }
+            }
+            created.external_id = external_id;
+            Ok(created)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -484,16 +800,77 @@ impl Collection {
     ///
     /// # Returns
     ///
-    /// * 'TransactionId' (external_id), the transaction id of the payment.
+    /// * 'Submitted<TransactionId>' (external_id), the transaction id of the payment.
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "request_to_pay", external_id = %request.external_id))]
     pub async fn request_to_pay(
         &self,
         request: RequestToPay,
         callback_url: Option<&str>,
-    ) -> Result<TransactionId, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+    ) -> Result<Submitted<TransactionId>, Box<dyn std::error::Error>> {
+        self.send_request_to_pay("v1_0", request, callback_url)
+            .await
+    }
+
+    /// Request a payment through the v2 requesttopay endpoint, the same as `request_to_pay`
+    /// otherwise
+    ///
+    /// # Parameters
+    ///
+    /// * 'request': RequestToPay
+    /// * 'callback_url', the callback url to send updates to
+    ///
+    /// # Returns
+    ///
+    /// * 'Submitted<TransactionId>' (external_id), the transaction id of the payment.
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "request_to_pay_v2", external_id = %request.external_id))]
+    pub async fn request_to_pay_v2(
+        &self,
+        request: RequestToPay,
+        callback_url: Option<&str>,
+    ) -> Result<Submitted<TransactionId>, Box<dyn std::error::Error>> {
+        self.send_request_to_pay("v2_0", request, callback_url)
+            .await
+    }
+
+    /// Send a request to pay to the requesttopay endpoint of the given version, e.g. "v1_0"
+    async fn send_request_to_pay(
+        &self,
+        version: &str,
+        mut request: RequestToPay,
+        callback_url: Option<&str>,
+    ) -> Result<Submitted<TransactionId>, Box<dyn std::error::Error>> {
+        request.validate()?;
+        request.payer = request.payer.normalized(self.msisdn_policy);
+        let Some(store) = &self.idempotency_store else {
+            return self.post_request_to_pay(version, request, callback_url).await;
+        };
+        let external_id = request.external_id.clone();
+        if !store.reserve(&external_id) {
+            tracing::debug!("request to pay already submitted, not sending it again");
+            return Err(Box::new(DuplicateReference {
+                transaction_id: store.get(&external_id).unwrap_or(external_id.clone()),
+                external_id,
+            }));
+        }
+        let result = self.post_request_to_pay(version, request, callback_url).await;
+        match &result {
+            Ok(_) => store.put(&external_id, external_id.clone()),
+            Err(_) => store.release(&external_id),
+        }
+        result
+    }
+
+    /// Post a request to pay to the requesttopay endpoint of the given version
+    async fn post_request_to_pay(
+        &self,
+        version: &str,
+        request: RequestToPay,
+        callback_url: Option<&str>,
+    ) -> Result<Submitted<TransactionId>, Box<dyn std::error::Error>> {
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let mut req = client
-            .post(format!("{}/collection/v1_0/requesttopay", self.url))
+            .post(format!("{}/collection/{}/requesttopay", self.url, version))
             .bearer_auth(access_token.access_token)
             .header("X-Target-Environment", self.environment.to_string())
             .header("Cache-Control", "no-cache")
@@ -502,21 +879,19 @@ impl Collection {
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
             .body(request.clone());
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
+        req = super::with_callback_url(req, callback_url);
 
-        let res = req.send().await?;
+        let res = req
+            .send_with(self.metrics.as_deref(), &self.headers, "request_to_pay")
+            .await?;
 
         if res.status().is_success() {
-            Ok(TransactionId(request.external_id))
+            Ok(Submitted::new(
+                TransactionId(request.external_id),
+                res.status(),
+            ))
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -525,19 +900,24 @@ impl Collection {
     /// # Parameters
     ///
     /// * 'external_id', this is the external id of the request to pay
-    /// * 'notification': DeliveryNotificationRequest
+    /// * 'notification': DeliveryNotificationRequest, its language is sent in the Language header,
+    ///   which is left out when the language is None so that MTN uses its default language. A
+    ///   message longer than MAX_NOTIFICATION_MESSAGE_LENGTH characters or a language MTN does not
+    ///   support is rejected before sending
     ///
     /// # Returns
     ///
     /// * ()
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "request_to_pay_delivery_notification", external_id = %external_id))]
     pub async fn request_to_pay_delivery_notification(
         &self,
         external_id: &str,
         notification: DeliveryNotificationRequest,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        notification.validate()?;
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
-        let res = client
+        let mut req = client
             .post(format!(
                 "{}/collection/v1_0/requesttopay/{}/deliverynotification",
                 self.url, external_id
@@ -545,20 +925,24 @@ impl Collection {
             .bearer_auth(access_token.access_token)
             .header("X-Target-Environment", self.environment.to_string())
             .header("notificationMessage", &notification.notification_message)
-            .header("Language", "")
             .header("Cache-Control", "no-cache")
-            .header("Ocp-Apim-Subscription-Key", &self.primary_key)
+            .header("Ocp-Apim-Subscription-Key", &self.primary_key);
+        if let Some(language) = &notification.language {
+            req = req.header("Language", language);
+        }
+        let res = req
             .body(notification)
-            .send()
+            .send_with(
+                self.metrics.as_deref(),
+                &self.headers,
+                "request_to_pay_delivery_notification",
+            )
             .await?;
 
         if res.status().is_success() {
             Ok(())
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -571,11 +955,12 @@ impl Collection {
     /// # Returns
     ///
     /// *  'RequestToPayResult', the is the result of the request to pay
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "request_to_pay_transaction_status", external_id = %payment_id))]
     pub async fn request_to_pay_transaction_status(
         &self,
         payment_id: &str,
     ) -> Result<RequestToPayResult, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let res = client
             .get(format!(
@@ -586,7 +971,7 @@ impl Collection {
             .header("X-Target-Environment", self.environment.to_string())
             .header("Cache-Control", "no-cache")
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "request_to_pay_transaction_status")
             .await?;
 
         if res.status().is_success() {
@@ -594,14 +979,89 @@ impl Collection {
             let request_to_pay_result: RequestToPayResult = serde_json::from_str(&body)?;
             Ok(request_to_pay_result)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
+        }
+    }
+
+    /// Get the status of several requests to pay, `concurrency` of them at a time
+    ///
+    /// The access token is fetched once before the status requests are sent, so they share it
+    /// rather than each asking MTN for a new one.
+    ///
+    /// # Parameters
+    ///
+    /// * 'payment_ids', the payment ids to get the status of
+    /// * 'concurrency', how many status requests are in flight at once, at least 1
+    ///
+    /// # Returns
+    ///
+    /// * every payment id with its status or the error getting it, in the order of `payment_ids`
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "collection", operation = "request_to_pay_statuses")
+    )]
+    #[allow(clippy::type_complexity)]
+    pub async fn request_to_pay_statuses(
+        &self,
+        payment_ids: &[&str],
+        concurrency: usize,
+    ) -> Vec<(
+        String,
+        Result<RequestToPayResult, Box<dyn std::error::Error>>,
+    )> {
+        if let Err(err) = self.get_valid_access_token().await {
+            let err = err.to_string();
+            return payment_ids
+                .iter()
+                .map(|payment_id| {
+                    let res: Result<RequestToPayResult, Box<dyn std::error::Error>> =
+                        Err(Box::new(std::io::Error::other(err.clone())));
+                    (payment_id.to_string(), res)
+                })
+                .collect();
         }
+        let mut statuses: Vec<_> = futures_util::stream::iter(payment_ids.iter().enumerate())
+            .map(|(position, payment_id)| async move {
+                let res = self.request_to_pay_transaction_status(payment_id).await;
+                (position, payment_id.to_string(), res)
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+        statuses.sort_by_key(|(position, _, _)| *position);
+        statuses
+            .into_iter()
+            .map(|(_, payment_id, res)| (payment_id, res))
+            .collect()
+    }
+
+    /// Get the status of several requests to pay, `concurrency` of them at a time, see
+    /// `request_to_pay_statuses`
+    ///
+    /// # Parameters
+    ///
+    /// * 'payment_ids', the payment ids to get the status of
+    /// * 'concurrency', how many status requests are in flight at once, at least 1
+    ///
+    /// # Returns
+    ///
+    /// * every payment id with its status or the error getting it, in the order of `payment_ids`
+    #[allow(clippy::type_complexity)]
+    pub async fn poll_statuses(
+        &self,
+        payment_ids: &[String],
+        concurrency: usize,
+    ) -> Vec<(
+        String,
+        Result<RequestToPayResult, Box<dyn std::error::Error>>,
+    )> {
+        let payment_ids: Vec<&str> = payment_ids.iter().map(String::as_str).collect();
+        self.request_to_pay_statuses(&payment_ids, concurrency)
+            .await
     }
 
-    /// This operation is used to get the status of a request to withdraw
+    /// This operation is used to get the status of a request to withdraw made with
+    /// `request_to_withdraw_v1`
     ///
     /// # Parameters
     ///
@@ -609,21 +1069,59 @@ impl Collection {
     ///
     /// # Returns
     /// * "RequestToPayResult", this is the result of the request to pay
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "request_to_withdraw_transaction_status", external_id = %payment_id))]
     pub async fn request_to_withdraw_transaction_status(
         &self,
         payment_id: &str,
     ) -> Result<RequestToPayResult, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        self.request_to_withdraw_status(
+            "v1_0",
+            payment_id,
+            "request_to_withdraw_transaction_status",
+        )
+        .await
+    }
+
+    /// This operation is used to get the status of a request to withdraw made with
+    /// `request_to_withdraw_v2`
+    ///
+    /// # Parameters
+    ///
+    /// * 'payment_id', the reference id of the request to withdraw, the `WithdrawId`
+    ///
+    /// # Returns
+    /// * "RequestToPayResult", this is the result of the request to withdraw
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "request_to_withdraw_v2_transaction_status", external_id = %payment_id))]
+    pub async fn request_to_withdraw_v2_transaction_status(
+        &self,
+        payment_id: &str,
+    ) -> Result<RequestToPayResult, Box<dyn std::error::Error>> {
+        self.request_to_withdraw_status(
+            "v2_0",
+            payment_id,
+            "request_to_withdraw_v2_transaction_status",
+        )
+        .await
+    }
+
+    /// Get the status of a request to withdraw from the endpoint of the version it was made with
+    async fn request_to_withdraw_status(
+        &self,
+        version: &str,
+        payment_id: &str,
+        operation: &str,
+    ) -> Result<RequestToPayResult, Box<dyn std::error::Error>> {
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let res = client
             .get(format!(
-                "{}/collection/v1_0/requesttowithdraw/{}",
-                self.url, payment_id
+                "{}/collection/{}/requesttowithdraw/{}",
+                self.url, version, payment_id
             ))
             .bearer_auth(access_token.access_token)
             .header("X-Target-Environment", self.environment.to_string())
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, operation)
             .await?;
 
         if res.status().is_success() {
@@ -631,10 +1129,7 @@ impl Collection {
             let request_to_pay_result: RequestToPayResult = serde_json::from_str(&body)?;
             Ok(request_to_pay_result)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -646,13 +1141,16 @@ impl Collection {
     /// * 'callback_url': Option<&str>, this is the callback url to be used by the MOMO Core API to notify the merchant of the request to withdraw status
     ///
     /// # Returns
-    /// * WithdrawId, this is the external_id of the request to withdraw
+    /// * 'Submitted<WithdrawId>', this is the external_id of the request to withdraw
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "request_to_withdraw_v1", external_id = %request.external_id))]
     pub async fn request_to_withdraw_v1(
         &self,
-        request: RequestToPay,
+        mut request: RequestToPay,
         callback_url: Option<&str>,
-    ) -> Result<WithdrawId, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+    ) -> Result<Submitted<WithdrawId>, Box<dyn std::error::Error>> {
+        request.validate()?;
+        request.payer = request.payer.normalized(self.msisdn_policy);
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let mut req = client
             .post(format!("{}/collection/v1_0/requesttowithdraw", self.url))
@@ -663,21 +1161,19 @@ impl Collection {
             .header("Content-Type", "application/json")
             .body(request.clone());
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
+        req = super::with_callback_url(req, callback_url);
 
-        let res = req.send().await?;
+        let res = req
+            .send_with(self.metrics.as_deref(), &self.headers, "request_to_withdraw_v1")
+            .await?;
 
         if res.status().is_success() {
-            Ok(WithdrawId(request.external_id))
+            Ok(Submitted::new(
+                WithdrawId(request.external_id),
+                res.status(),
+            ))
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -693,14 +1189,16 @@ impl Collection {
     ///
     /// # Returns
     ///
-    /// * 'WithdrawId', the reference id of the request
-
+    /// * 'Submitted<WithdrawId>', the reference id of the request
+    #[tracing::instrument(skip_all, fields(product = "collection", operation = "request_to_withdraw_v2", external_id = %request.external_id))]
     pub async fn request_to_withdraw_v2(
         &self,
-        request: RequestToPay,
+        mut request: RequestToPay,
         callback_url: Option<&str>,
-    ) -> Result<WithdrawId, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+    ) -> Result<Submitted<WithdrawId>, Box<dyn std::error::Error>> {
+        request.validate()?;
+        request.payer = request.payer.normalized(self.msisdn_policy);
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let mut req = client
             .post(format!("{}/collection/v2_0/requesttowithdraw", self.url))
@@ -711,21 +1209,19 @@ impl Collection {
             .header("Content-Type", "application/json")
             .body(request.clone());
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
+        req = super::with_callback_url(req, callback_url);
 
-        let res = req.send().await?;
+        let res = req
+            .send_with(self.metrics.as_deref(), &self.headers, "request_to_withdraw_v2")
+            .await?;
 
         if res.status().is_success() {
-            Ok(WithdrawId(request.external_id))
+            Ok(Submitted::new(
+                WithdrawId(request.external_id),
+                res.status(),
+            ))
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -733,6 +1229,10 @@ impl Collection {
     /// # Returns
     ///
     /// * 'Balance', the balance
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "collection", operation = "get_account_balance")
+    )]
     pub async fn get_account_balance(&self) -> Result<Balance, Box<dyn std::error::Error>> {
         let url = format!("{}/collection", self.url);
         let access_token = self.get_valid_access_token().await?;
@@ -754,6 +1254,13 @@ impl Collection {
     /// # Returns
     ///
     /// * 'Balance', the balance
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            product = "collection",
+            operation = "get_account_balance_in_specific_currency"
+        )
+    )]
     pub async fn get_account_balance_in_specific_currency(
         &self,
         currency: Currency,
@@ -779,18 +1286,50 @@ impl Collection {
     /// # Returns
     ///
     /// * 'BasicUserInfoJsonResponse'
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "collection", operation = "get_basic_user_info")
+    )]
     pub async fn get_basic_user_info(
         &self,
         account_holder_msisdn: &str,
     ) -> Result<BasicUserInfoJsonResponse, Box<dyn std::error::Error>> {
+        self.get_basic_user_info_by_id(PartyIdType::MSISDN, account_holder_msisdn)
+            .await
+    }
+
+    /// This operation is used to get the basic information of the account holder, identified
+    /// by any kind of account holder id
+    ///
+    /// # Parameters
+    /// * 'account_holder_id_type', the kind of id, e.g. EMAIL
+    /// * 'account_holder_id', the MSISDN, e-mail or party code of the account holder
+    ///
+    /// # Returns
+    ///
+    /// * 'BasicUserInfoJsonResponse'
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "collection", operation = "get_basic_user_info")
+    )]
+    pub async fn get_basic_user_info_by_id(
+        &self,
+        account_holder_id_type: PartyIdType,
+        account_holder_id: &str,
+    ) -> Result<BasicUserInfoJsonResponse, Box<dyn std::error::Error>> {
+        let account_holder_id = match account_holder_id_type {
+            PartyIdType::MSISDN => self.msisdn_policy.normalize(account_holder_id),
+            _ => account_holder_id.to_string(),
+        };
         let url = format!("{}/collection", self.url);
         let access_token = self.get_valid_access_token().await?;
         self.account
-            .get_basic_user_info(
+            .get_basic_user_info_by_id(
                 url,
                 self.environment,
                 self.primary_key.clone(),
-                account_holder_msisdn,
+                account_holder_id_type,
+                &account_holder_id,
                 access_token,
             )
             .await
@@ -805,6 +1344,10 @@ impl Collection {
     /// # Returns
     ///
     /// * 'BasicUserInfoJsonResponse'
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "collection", operation = "get_user_info_with_consent")
+    )]
     pub async fn get_user_info_with_consent(
         &self,
         access_token: String,
@@ -825,13 +1368,55 @@ impl Collection {
     /// # Parameters
     ///
     /// * 'account_holder_id', The MSISDN or email of the account holder
-    /// * 'account_holder_type', The type of the account holder.
+    /// * 'account_holder_type', The type of the account holder, MSISDN, EMAIL or PARTY_CODE
     ///
     ///
     /// # Returns
     ///
     /// * ()
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "collection", operation = "validate_account_holder_status")
+    )]
     pub async fn validate_account_holder_status(
+        &self,
+        account_holder_id: &str,
+        account_holder_type: PartyIdType,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let url = format!("{}/collection", self.url);
+        let access_token = self.get_valid_access_token().await?;
+        self.account
+            .validate_account_holder_status(
+                url,
+                self.environment,
+                self.primary_key.clone(),
+                account_holder_id,
+                account_holder_type.account_holder_id_type(),
+                access_token,
+            )
+            .await
+    }
+
+    /// this operation is used to validate the status of an account holder, the type is sent as is
+    ///
+    /// # Parameters
+    ///
+    /// * 'account_holder_id', The MSISDN or email of the account holder
+    /// * 'account_holder_type', The type of the account holder, e.g. "msisdn"
+    ///
+    ///
+    /// # Returns
+    ///
+    /// * ()
+    #[deprecated(note = "use validate_account_holder_status with a PartyIdType")]
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            product = "collection",
+            operation = "validate_account_holder_status_str"
+        )
+    )]
+    pub async fn validate_account_holder_status_str(
         &self,
         account_holder_id: &str,
         account_holder_type: &str,
@@ -851,14 +1436,33 @@ impl Collection {
     }
 }
 
+impl std::fmt::Debug for Collection {
+    /// The keys are masked, so that a product can be logged
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Collection")
+            .field("url", &self.url)
+            .field("environment", &self.environment)
+            .field("api_user", &self.api_user)
+            .field("api_key", &crate::redaction::MASK)
+            .field("primary_key", &crate::redaction::MASK)
+            .field("secondary_key", &crate::redaction::MASK)
+            .finish_non_exhaustive()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Money, Party, PartyIdType};
+    use crate::products::test_server::{balance_routes, collection_at, spawn_mock, token_routes};
+    use crate::{InvoiceStatus, Money, Party};
     use dotenv::dotenv;
     use std::env;
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_create_and_cancel_invoice() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -902,6 +1506,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_request_payment() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -936,6 +1544,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_request_payment_status() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -980,6 +1592,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_request_payment_with_delivery_notification() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1019,15 +1635,17 @@ mod tests {
         let notifcation_result = collection
             .request_to_pay_delivery_notification(
                 &res.0,
-                DeliveryNotificationRequest {
-                    notification_message: "test_notification_message".to_string(),
-                },
+                DeliveryNotificationRequest::new("test_notification_message".to_string()),
             )
             .await;
         assert!(notifcation_result.is_ok());
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_bc_authorize() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1045,12 +1663,18 @@ mod tests {
             primary_key,
             secondary_key,
         );
-        let bc_authorize_res = collection.bc_authorize("563607".to_string(), None).await;
+        let bc_authorize_res = collection
+            .bc_authorize("563607".to_string(), None, None, None)
+            .await;
         assert!(bc_authorize_res.is_ok());
         assert_ne!(bc_authorize_res.unwrap().auth_req_id.len(), 0);
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_create_o_auth_2_token() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1068,7 +1692,9 @@ mod tests {
             primary_key,
             secondary_key,
         );
-        let bc_authorize_res = collection.bc_authorize("563607".to_string(), None).await;
+        let bc_authorize_res = collection
+            .bc_authorize("563607".to_string(), None, None, None)
+            .await;
         assert!(bc_authorize_res.is_ok());
         let res = collection
             .create_o_auth_2_token(bc_authorize_res.unwrap().auth_req_id)
@@ -1078,6 +1704,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_user_info_with_consent() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1096,7 +1726,9 @@ mod tests {
             primary_key,
             secondary_key,
         );
-        let bc_authorize_res = collection.bc_authorize("563607".to_string(), None).await;
+        let bc_authorize_res = collection
+            .bc_authorize("563607".to_string(), None, None, None)
+            .await;
         assert!(bc_authorize_res.is_ok());
         let res = collection
             .create_o_auth_2_token(bc_authorize_res.unwrap().auth_req_id)
@@ -1111,6 +1743,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_account_balance() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1129,26 +1765,16 @@ mod tests {
             secondary_key,
         );
         let res = collection.get_account_balance().await;
-        if res.is_ok() {
-            assert_ne!(res.unwrap().available_balance.len(), 0);
+        if let Ok(balance) = res {
+            assert_ne!(balance.available_balance.len(), 0);
         }
     }
 
-    // #[tokio::test]
-    // async fn test_get_account_balance_in_specific_currency() {
-    //     dotenv().ok();
-    //     let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
-
-    //     let primary_key = env::var("MTN_COLLECTION_PRIMARY_KEY").expect("PRIMARY_KEY must be set");
-    //     let secondary_key = env::var("MTN_COLLECTION_SECONDARY_KEY").expect("SECONDARY_KEY must be set");
-    //     let api_user = env::var("MTN_API_USER").expect("API_USER must be set");
-    //     let api_key = env::var("MTN_API_KEY").expect("API_KEY must be set");
-    //     let collection = Collection::new(mtn_url, Environment::Sandbox, api_user, api_key, primary_key, secondary_key);
-    //     let res = collection.get_account_balance_in_specific_currency(Currency::XAF.to_string()).await.expect("Error getting account balance");
-    //     assert_ne!(res.available_balance.len(), 0);
-    // }
-
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_basic_user_info() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1175,6 +1801,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_invoice_status() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1215,13 +1845,17 @@ mod tests {
             .expect("Error creating invoice");
 
         let res = collection
-            .get_invoice_status(invoice_id.0)
+            .get_invoice_status(invoice_id.into_id().0)
             .await
             .expect("Error getting invoice status");
         assert_eq!(res.status, "SUCCESSFUL".to_string());
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_pre_approval() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1245,19 +1879,16 @@ mod tests {
             party_id_type: PartyIdType::MSISDN,
             party_id: "+242064818006".to_string(),
         };
-        let preapproval = PreApprovalRequest {
-            payer: user,
-            payer_currency: Currency::EUR.to_string(),
-            payer_message: "".to_string(),
-            validity_time: 3600,
-        };
-        let res = collection.pre_approval(preapproval).await;
-        if res.is_ok() {
-            assert!(true);
-        }
+        let preapproval =
+            PreApprovalRequest::new(user, Currency::EUR.to_string(), "".to_string(), 3600).unwrap();
+        let _ = collection.pre_approval(preapproval).await;
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_pre_approval_status() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1281,17 +1912,13 @@ mod tests {
             party_id_type: PartyIdType::MSISDN,
             party_id: "+242064818006".to_string(),
         };
-        let preapproval = PreApprovalRequest {
-            payer: user,
-            payer_currency: Currency::EUR.to_string(),
-            payer_message: "".to_string(),
-            validity_time: 3600,
-        };
+        let preapproval =
+            PreApprovalRequest::new(user, Currency::EUR.to_string(), "".to_string(), 3600).unwrap();
         let res = collection.pre_approval(preapproval).await;
 
-        if res.is_ok() {
+        if let Ok(pre_approval_id) = res {
             let res = collection
-                .get_pre_approval_status(res.unwrap())
+                .get_pre_approval_status(pre_approval_id)
                 .await
                 .expect("Error getting pre approval status");
             assert_ne!(res.status.len(), 0);
@@ -1299,6 +1926,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_create_payment() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1339,6 +1970,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_payment_status() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1385,6 +2020,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_request_to_withdraw_v1() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1423,6 +2062,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_request_to_withdraw_v2() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1461,6 +2104,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_request_withdraw_status() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1496,13 +2143,17 @@ mod tests {
             .await
             .expect("Error requesting to withdraw");
         let res = collection
-            .request_to_withdraw_transaction_status(&withdraw_id.0)
+            .request_to_withdraw_v2_transaction_status(&withdraw_id.0)
             .await
             .expect("Error getting request to withdraw status");
         assert_eq!(res.status, "SUCCESSFUL");
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_validate_account_holder_status() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1523,8 +2174,1057 @@ mod tests {
             secondary_key,
         );
         let res = collection
-            .validate_account_holder_status("256774290781", "MSISDN")
+            .validate_account_holder_status("256774290781", PartyIdType::MSISDN)
             .await;
         assert!(res.is_ok());
     }
+
+    #[poem::handler]
+    fn mock_gateway_balance(req: &poem::Request) -> poem::Response {
+        if req.header("X-Gateway-Key") == Some("gateway-secret") {
+            poem::Response::builder().body(r#"{"availableBalance":"1000","currency":"EUR"}"#)
+        } else {
+            poem::Response::builder()
+                .status(poem::http::StatusCode::FORBIDDEN)
+                .body("missing gateway key")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_client_sends_default_headers() {
+        use poem::get;
+
+        let url = spawn_mock(token_routes().at(
+            "/collection/v1_0/account/balance",
+            get(mock_gateway_balance),
+        ));
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "X-Gateway-Key",
+            reqwest::header::HeaderValue::from_static("gateway-secret"),
+        );
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        let collection = Collection::with_client(
+            url.clone(),
+            Environment::Sandbox,
+            "mock_api_user".to_string(),
+            "mock_api_key".to_string(),
+            "mock_primary_key".to_string(),
+            "mock_secondary_key".to_string(),
+            client,
+        );
+        let balance = collection.get_account_balance().await.unwrap();
+        assert_eq!(balance.available_balance, "1000");
+
+        let collection = collection_at(url);
+        assert!(collection.get_account_balance().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_with_default_headers() {
+        use poem::get;
+
+        let url = spawn_mock(token_routes().at(
+            "/collection/v1_0/account/balance",
+            get(mock_gateway_balance),
+        ));
+
+        let collection = collection_at(url);
+        assert!(collection.get_account_balance().await.is_err());
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "X-Gateway-Key",
+            reqwest::header::HeaderValue::from_static("gateway-secret"),
+        );
+        // for a single call, on a clone
+        let balance = collection
+            .clone()
+            .with_default_headers(headers.clone())
+            .get_account_balance()
+            .await
+            .unwrap();
+        assert_eq!(balance.available_balance, "1000");
+        assert!(collection.get_account_balance().await.is_err());
+
+        // a later header of the same name replaces the earlier one
+        let mut wrong = reqwest::header::HeaderMap::new();
+        wrong.insert(
+            "X-Gateway-Key",
+            reqwest::header::HeaderValue::from_static("wrong"),
+        );
+        let collection = collection.with_default_headers(wrong);
+        assert!(collection.get_account_balance().await.is_err());
+        let collection = collection.with_default_headers(headers);
+        assert!(collection.get_account_balance().await.is_ok());
+    }
+
+    #[poem::handler]
+    fn mock_proxied_balance(req: &poem::Request) -> poem::Response {
+        // requests reach the proxy with the absolute url of the target
+        if req.uri().host() == Some("momo.invalid") {
+            poem::Response::builder().body(r#"{"availableBalance":"1000","currency":"EUR"}"#)
+        } else {
+            poem::Response::builder()
+                .status(poem::http::StatusCode::BAD_GATEWAY)
+                .finish()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_proxy() {
+        use poem::get;
+
+        let proxy = spawn_mock(token_routes().at(
+            "/collection/v1_0/account/balance",
+            get(mock_proxied_balance),
+        ));
+
+        let collection = Collection::with_proxy(
+            "http://momo.invalid".to_string(),
+            Environment::Sandbox,
+            "mock_api_user".to_string(),
+            "mock_api_key".to_string(),
+            "mock_primary_key".to_string(),
+            "mock_secondary_key".to_string(),
+            Some(proxy),
+        )
+        .unwrap();
+        let balance = collection.get_account_balance().await.unwrap();
+        assert_eq!(balance.available_balance, "1000");
+
+        let res = Collection::with_proxy(
+            "http://momo.invalid".to_string(),
+            Environment::Sandbox,
+            "mock_api_user".to_string(),
+            "mock_api_key".to_string(),
+            "mock_primary_key".to_string(),
+            "mock_secondary_key".to_string(),
+            Some("http://proxy:not_a_port".to_string()),
+        );
+        let err = res.err().unwrap();
+        assert!(err
+            .to_string()
+            .starts_with("http://proxy:not_a_port is not a valid proxy url"));
+    }
+
+    static INVOICE_STATUS_CALLS: std::sync::atomic::AtomicUsize =
+        std::sync::atomic::AtomicUsize::new(0);
+
+    #[poem::handler]
+    fn mock_invoice_status(poem::web::Path(invoice_id): poem::web::Path<String>) -> String {
+        let calls = INVOICE_STATUS_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let status = if invoice_id == "paid_invoice" && calls >= 2 {
+            "SUCCESSFUL"
+        } else {
+            "PENDING"
+        };
+        serde_json::json!({
+            "referenceId": invoice_id,
+            "externalId": invoice_id,
+            "amount": "100",
+            "currency": "EUR",
+            "status": status,
+            "paymentReference": "payment_reference",
+            "invoiceId": invoice_id,
+            "expiryDateTime": "2024-01-01T00:00:00Z",
+            "intendedPayer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "description": "test invoice"
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_await_invoice_final() {
+        use poem::get;
+        use std::time::Duration;
+
+        let url = spawn_mock(token_routes().at(
+            "/collection/v2_0/invoice/:invoice_id",
+            get(mock_invoice_status),
+        ));
+
+        let collection = collection_at(url);
+
+        let invoice = collection
+            .await_invoice_final(
+                "paid_invoice",
+                Duration::from_secs(5),
+                Duration::from_millis(10),
+            )
+            .await
+            .unwrap();
+        assert_eq!(invoice.status_enum(), InvoiceStatus::SUCCESSFUL);
+
+        let res = collection
+            .await_invoice_final(
+                "pending_invoice",
+                Duration::from_millis(50),
+                Duration::from_millis(10),
+            )
+            .await;
+        assert!(res.is_err());
+    }
+
+    static PRE_APPROVAL_STATUS_CALLS: std::sync::atomic::AtomicUsize =
+        std::sync::atomic::AtomicUsize::new(0);
+
+    #[poem::handler]
+    fn mock_pre_approval_status(
+        poem::web::Path(pre_approval_id): poem::web::Path<String>,
+    ) -> String {
+        let status = if pre_approval_id == "approved" {
+            let calls = PRE_APPROVAL_STATUS_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if calls >= 2 {
+                "SUCCESSFUL"
+            } else {
+                "PENDING"
+            }
+        } else {
+            "CREATED"
+        };
+        serde_json::json!({
+            "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "payerCurrency": "EUR",
+            "status": status,
+            "expirationDateTime": "2024-01-01T00:00:00Z"
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_await_pre_approval() {
+        use poem::get;
+        use std::time::Duration;
+
+        let url = spawn_mock(token_routes().at(
+            "/collection/v2_0/preapproval/:pre_approval_id",
+            get(mock_pre_approval_status),
+        ));
+
+        let collection = collection_at(url);
+
+        let pre_approval = collection
+            .await_pre_approval("approved", Duration::from_secs(5), Duration::from_millis(10))
+            .await
+            .unwrap();
+        assert_eq!(pre_approval.status, "SUCCESSFUL");
+        assert_eq!(
+            PRE_APPROVAL_STATUS_CALLS.load(std::sync::atomic::Ordering::SeqCst),
+            3
+        );
+
+        let err = collection
+            .await_pre_approval(
+                "not_answered",
+                Duration::from_millis(50),
+                Duration::from_millis(10),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("still CREATED"));
+    }
+
+    #[poem::handler]
+    fn mock_bc_authorize(body: String) -> poem::Response {
+        if body != "scope=openid&login_hint=ID:563607/MSISDN&access_type=online" {
+            return poem::Response::builder()
+                .status(poem::http::StatusCode::BAD_REQUEST)
+                .body(body);
+        }
+        poem::Response::builder()
+            .body(r#"{"auth_req_id":"auth_req_id","interval":5,"expires_in":3600}"#)
+    }
+
+    #[tokio::test]
+    async fn test_bc_authorize_sends_scope() {
+        use poem::post;
+
+        let url =
+            spawn_mock(token_routes().at("/collection/v1_0/bc-authorize", post(mock_bc_authorize)));
+
+        let collection = collection_at(url);
+        let res = collection
+            .bc_authorize(
+                "563607".to_string(),
+                None,
+                Some("openid"),
+                Some(AccessType::Online),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.auth_req_id, "auth_req_id");
+    }
+
+    #[poem::handler]
+    fn mock_request_to_pay(
+        calls: poem::web::Data<&Arc<std::sync::atomic::AtomicUsize>>,
+    ) -> poem::Response {
+        calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        poem::Response::builder()
+            .status(poem::http::StatusCode::ACCEPTED)
+            .finish()
+    }
+
+    #[tokio::test]
+    async fn test_request_to_pay_idempotency() {
+        use crate::InMemoryIdempotencyStore;
+        use poem::{middleware::AddData, post, EndpointExt};
+
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let url = spawn_mock(
+            token_routes()
+                .at("/collection/v1_0/requesttopay", post(mock_request_to_pay))
+                .with(AddData::new(calls.clone())),
+        );
+
+        let collection = collection_at(url).with_idempotency_store(Arc::new(
+            InMemoryIdempotencyStore::new(std::time::Duration::from_secs(60)),
+        ));
+        let request = RequestToPay::new(
+            "100".to_string(),
+            Currency::EUR,
+            Party {
+                party_id_type: PartyIdType::MSISDN,
+                party_id: "256774290781".to_string(),
+            },
+            "payer_message".to_string(),
+            "payee_note".to_string(),
+        );
+
+        // miss, the request is sent
+        let first = collection
+            .request_to_pay(request.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // hit, the retry is refused before sending
+        let Err(err) = collection.request_to_pay(request.clone(), None).await else {
+            panic!("the duplicate request to pay was accepted");
+        };
+        let duplicate = err.downcast_ref::<DuplicateReference>().unwrap();
+        assert_eq!(duplicate.external_id, request.external_id);
+        assert_eq!(duplicate.transaction_id, first.as_str());
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // another external id is a miss
+        let mut other = request;
+        other.external_id = uuid::Uuid::new_v4().to_string();
+        collection.request_to_pay(other, None).await.unwrap();
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[poem::handler]
+    async fn mock_request_to_pay_slow(
+        calls: poem::web::Data<&Arc<std::sync::atomic::AtomicUsize>>,
+    ) -> poem::Response {
+        // the first request fails, after the concurrent duplicate was refused
+        let call = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let status = if call == 0 {
+            poem::http::StatusCode::INTERNAL_SERVER_ERROR
+        } else {
+            poem::http::StatusCode::ACCEPTED
+        };
+        poem::Response::builder().status(status).finish()
+    }
+
+    #[tokio::test]
+    async fn test_request_to_pay_concurrent_duplicates() {
+        use crate::InMemoryIdempotencyStore;
+        use poem::{middleware::AddData, post, EndpointExt};
+
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let url = spawn_mock(
+            token_routes()
+                .at(
+                    "/collection/v1_0/requesttopay",
+                    post(mock_request_to_pay_slow),
+                )
+                .with(AddData::new(calls.clone())),
+        );
+
+        let collection = collection_at(url).with_idempotency_store(Arc::new(
+            InMemoryIdempotencyStore::new(std::time::Duration::from_secs(60)),
+        ));
+        let request = RequestToPay::new(
+            "100".to_string(),
+            Currency::EUR,
+            Party {
+                party_id_type: PartyIdType::MSISDN,
+                party_id: "256774290781".to_string(),
+            },
+            "payer_message".to_string(),
+            "payee_note".to_string(),
+        );
+
+        // only one of the concurrent calls reaches MTN, the other one is a duplicate
+        let (first, second) = tokio::join!(
+            collection.request_to_pay(request.clone(), None),
+            collection.request_to_pay(request.clone(), None)
+        );
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let (Err(failed), Err(duplicate)) = (first, second) else {
+            panic!("the failed request or the duplicate was accepted");
+        };
+        let (failed, duplicate) = if failed.is::<DuplicateReference>() {
+            (duplicate, failed)
+        } else {
+            (failed, duplicate)
+        };
+        assert!(!failed.is::<DuplicateReference>());
+        assert_eq!(
+            duplicate
+                .downcast_ref::<DuplicateReference>()
+                .unwrap()
+                .external_id,
+            request.external_id
+        );
+
+        // the failure released the external id, the retry is sent
+        collection.request_to_pay(request, None).await.unwrap();
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[poem::handler]
+    fn mock_request_to_pay_normalized(
+        poem::web::Json(body): poem::web::Json<serde_json::Value>,
+    ) -> poem::Response {
+        let status = if body["payer"]["partyId"] == "242064818006" {
+            poem::http::StatusCode::ACCEPTED
+        } else {
+            poem::http::StatusCode::BAD_REQUEST
+        };
+        poem::Response::builder().status(status).finish()
+    }
+
+    #[tokio::test]
+    async fn test_request_to_pay_msisdn_policy() {
+        use poem::post;
+
+        let url = spawn_mock(token_routes().at(
+            "/collection/v1_0/requesttopay",
+            post(mock_request_to_pay_normalized),
+        ));
+
+        let collection = collection_at(url);
+        assert_eq!(collection.msisdn_policy(), MsisdnPolicy::KeepPlus);
+        let request = RequestToPay::new(
+            "100".to_string(),
+            Currency::EUR,
+            Party::msisdn("+242 06 481 8006"),
+            "payer_message".to_string(),
+            "payee_note".to_string(),
+        );
+        assert!(collection
+            .request_to_pay(request.clone(), None)
+            .await
+            .is_err());
+
+        let collection = collection.with_msisdn_policy(MsisdnPolicy::StripPlus);
+        assert!(collection.request_to_pay(request, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_account_balance_in_specific_currency() {
+        let collection = collection_at(spawn_mock(balance_routes()));
+        let balance = collection
+            .get_account_balance_in_specific_currency(Currency::EUR)
+            .await
+            .unwrap();
+        assert_eq!(balance.currency, Currency::EUR);
+        let balance = collection
+            .get_account_balance_in_specific_currency(Currency::XAF)
+            .await
+            .unwrap();
+        assert_eq!(balance.currency, Currency::XAF);
+    }
+
+    #[poem::handler]
+    fn mock_withdraw_status(
+        poem::web::Path((version, reference_id)): poem::web::Path<(String, String)>,
+    ) -> poem::Response {
+        let status = if version == "v2_0" {
+            "SUCCESSFUL"
+        } else {
+            "PENDING"
+        };
+        poem::Response::builder().body(format!(
+            r#"{{"externalId":"{}","amount":"100","currency":"EUR","payer":{{"partyIdType":"MSISDN","partyId":"256774290781"}},"payerMessage":"","payeeNote":"","status":"{}"}}"#,
+            reference_id, status
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_request_to_withdraw_status_versions() {
+        use poem::get;
+
+        let url = spawn_mock(token_routes().at(
+            "/collection/:version/requesttowithdraw/:reference_id",
+            get(mock_withdraw_status),
+        ));
+
+        let collection = collection_at(url);
+        let v1 = collection
+            .request_to_withdraw_transaction_status("withdraw_v1")
+            .await
+            .unwrap();
+        assert_eq!(v1.external_id, "withdraw_v1");
+        assert_eq!(v1.status, "PENDING");
+        let v2 = collection
+            .request_to_withdraw_v2_transaction_status("withdraw_v2")
+            .await
+            .unwrap();
+        assert_eq!(v2.external_id, "withdraw_v2");
+        assert_eq!(v2.status, "SUCCESSFUL");
+    }
+
+    #[poem::handler]
+    fn mock_delivery_notification(req: &poem::Request) -> poem::Response {
+        let status = match req.header("Language") {
+            Some("fr") | None => poem::http::StatusCode::OK,
+            Some(_) => poem::http::StatusCode::BAD_REQUEST,
+        };
+        poem::Response::builder().status(status).finish()
+    }
+
+    #[tokio::test]
+    async fn test_delivery_notification_language() {
+        use poem::post;
+
+        let url = spawn_mock(token_routes().at(
+            "/collection/v1_0/requesttopay/:reference_id/deliverynotification",
+            post(mock_delivery_notification),
+        ));
+
+        let collection = collection_at(url);
+        let notification = DeliveryNotificationRequest::new("votre commande arrive".to_string());
+        assert!(collection
+            .request_to_pay_delivery_notification("reference_id", notification.clone())
+            .await
+            .is_ok());
+        assert!(collection
+            .request_to_pay_delivery_notification(
+                "reference_id",
+                notification.clone().with_language("fr").unwrap()
+            )
+            .await
+            .is_ok());
+        let invalid = DeliveryNotificationRequest {
+            language: Some("French".to_string()),
+            ..notification.clone()
+        };
+        assert!(collection
+            .request_to_pay_delivery_notification("reference_id", invalid)
+            .await
+            .is_err());
+        let too_long = DeliveryNotificationRequest {
+            notification_message: "m".repeat(161),
+            ..notification
+        };
+        assert!(collection
+            .request_to_pay_delivery_notification("reference_id", too_long)
+            .await
+            .is_err());
+    }
+
+    #[poem::handler]
+    fn mock_ok() -> poem::Response {
+        poem::Response::builder().finish()
+    }
+
+    #[tokio::test]
+    async fn test_submission_status() {
+        use poem::post;
+
+        let url = spawn_mock(
+            token_routes()
+                .at("/collection/v1_0/requesttopay", post(mock_accepted))
+                .at("/collection/v2_0/payment", post(mock_ok)),
+        );
+
+        let collection = collection_at(url);
+        let request = RequestToPay::new(
+            "100".to_string(),
+            Currency::EUR,
+            Party::msisdn("256774290781"),
+            "payer_message".to_string(),
+            "payee_note".to_string(),
+        );
+        let external_id = request.external_id.clone();
+        let submitted = collection.request_to_pay(request, None).await.unwrap();
+        assert_eq!(submitted.status(), crate::SubmissionStatus::Accepted);
+        assert!(submitted.is_pending());
+        assert_eq!(submitted.as_str(), external_id);
+
+        let payment = CreatePaymentRequest::new(
+            Money {
+                amount: "100".to_string(),
+                currency: "EUR".to_string(),
+            },
+            "customer_reference".to_string(),
+            "service_provider_name".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            1,
+            false,
+        );
+        let submitted = collection.create_payments(payment, None).await.unwrap();
+        assert_eq!(submitted.status(), crate::SubmissionStatus::Completed);
+        assert!(!submitted.is_pending());
+    }
+
+    #[tokio::test]
+    async fn test_create_access_token() {
+        let url = spawn_mock(token_routes());
+
+        let collection = collection_at(url);
+        let token = collection.create_access_token().await.unwrap();
+        assert_eq!(token.access_token, "mock_access_token");
+        assert_eq!(token.expires_in, 3600);
+    }
+
+    #[poem::handler]
+    fn mock_account_holder_active(
+        poem::web::Path((id_type, id)): poem::web::Path<(String, String)>,
+    ) -> poem::Response {
+        let known = matches!(
+            (id_type.as_str(), id.as_str()),
+            ("msisdn", "256774290781") | ("email", "test@email.com")
+        );
+        if !known {
+            return poem::Response::builder()
+                .status(poem::http::StatusCode::NOT_FOUND)
+                .body(format!("{}/{}", id_type, id));
+        }
+        poem::Response::builder().body(r#"{"result":true}"#)
+    }
+
+    #[tokio::test]
+    async fn test_validate_account_holder_status_id_type() {
+        use poem::get;
+
+        let url = spawn_mock(token_routes().at(
+            "/collection/v1_0/accountholder/:id_type/:id/active",
+            get(mock_account_holder_active),
+        ));
+
+        let collection = collection_at(url);
+        assert!(collection
+            .validate_account_holder_status("256774290781", PartyIdType::MSISDN)
+            .await
+            .is_ok());
+        assert!(collection
+            .validate_account_holder_status("test@email.com", PartyIdType::EMAIL)
+            .await
+            .is_ok());
+        #[allow(deprecated)]
+        let res = collection
+            .validate_account_holder_status_str("256774290781", "MSISDN")
+            .await;
+        assert!(res.is_err());
+    }
+
+    static STATUS_TOKEN_REQUESTS: std::sync::atomic::AtomicUsize =
+        std::sync::atomic::AtomicUsize::new(0);
+
+    #[poem::handler]
+    fn mock_counted_access_token() -> &'static str {
+        STATUS_TOKEN_REQUESTS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        r#"{"access_token":"mock_access_token","token_type":"access_token","expires_in":3600}"#
+    }
+
+    #[poem::handler]
+    async fn mock_request_to_pay_status(
+        poem::web::Path(payment_id): poem::web::Path<String>,
+    ) -> poem::Response {
+        if payment_id == "missing" {
+            return poem::Response::builder()
+                .status(poem::http::StatusCode::NOT_FOUND)
+                .body(r#"{"code":"RESOURCE_NOT_FOUND"}"#);
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        poem::Response::builder().body(format!(
+            r#"{{"amount":"100","currency":"EUR","financialTransactionId":"363440463","externalId":"{}","payer":{{"partyIdType":"MSISDN","partyId":"256774290781"}},"payerMessage":"payer_message","payeeNote":"payee_note","status":"SUCCESSFUL"}}"#,
+            payment_id
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_request_to_pay_statuses() {
+        use poem::{get, post, Route};
+
+        let url = spawn_mock(
+            Route::new()
+                .at("/collection/token/", post(mock_counted_access_token))
+                .at(
+                    "/collection/v1_0/requesttopay/:payment_id",
+                    get(mock_request_to_pay_status),
+                ),
+        );
+
+        let collection = collection_at(url);
+        let ids = ["first", "second", "missing", "third", "fourth"];
+        let statuses = collection.request_to_pay_statuses(&ids, 2).await;
+        assert_eq!(statuses.len(), ids.len());
+        for (payment_id, res) in statuses.iter() {
+            if payment_id == "missing" {
+                assert!(res.is_err());
+            } else {
+                assert_eq!(&res.as_ref().unwrap().external_id, payment_id);
+            }
+        }
+        // the token cache is shared with the other tests, it may already hold a token
+        assert!(STATUS_TOKEN_REQUESTS.load(std::sync::atomic::Ordering::SeqCst) <= 1);
+    }
+
+    #[tokio::test]
+    async fn test_poll_statuses_keeps_order() {
+        use poem::get;
+
+        let url = spawn_mock(token_routes().at(
+            "/collection/v1_0/requesttopay/:payment_id",
+            get(mock_request_to_pay_status),
+        ));
+
+        let collection = collection_at(url);
+        // the missing payment is answered first, it must still come back third
+        let ids: Vec<String> = ["first", "second", "missing", "third"]
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        let statuses = collection.poll_statuses(&ids, 4).await;
+        let polled: Vec<&String> = statuses.iter().map(|(payment_id, _)| payment_id).collect();
+        assert_eq!(polled, ids.iter().collect::<Vec<_>>());
+        assert!(statuses[2].1.is_err());
+        for (payment_id, res) in statuses.iter().filter(|(id, _)| id != "missing") {
+            assert_eq!(&res.as_ref().unwrap().external_id, payment_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_to_pay_rejects_invalid_amount() {
+        // nothing listens there, the request must be rejected before anything is sent
+        let collection = Collection::new(
+            "http://127.0.0.1:1".to_string(),
+            Environment::Sandbox,
+            "mock_api_user".to_string(),
+            "mock_api_key".to_string(),
+            "mock_primary_key".to_string(),
+            "mock_secondary_key".to_string(),
+        );
+        let request = RequestToPay::new(
+            "100.000".to_string(),
+            Currency::EUR,
+            Party::msisdn("256774290781"),
+            "payer_message".to_string(),
+            "payee_note".to_string(),
+        );
+        let Err(err) = collection.request_to_pay(request, None).await else {
+            panic!("an invalid amount was accepted");
+        };
+        assert!(err.to_string().contains("100.000 is not a valid amount"));
+    }
+
+    #[poem::handler]
+    fn mock_pre_approval() -> poem::Response {
+        poem::Response::builder()
+            .status(poem::http::StatusCode::CREATED)
+            .body(r#"{"status":"PENDING","expirationDateTime":"2026-11-16T10:00:00.000Z"}"#)
+    }
+
+    #[poem::handler]
+    fn mock_pre_approval_unexpected_body() -> poem::Response {
+        poem::Response::builder()
+            .status(poem::http::StatusCode::ACCEPTED)
+            .body("accepted")
+    }
+
+    #[tokio::test]
+    async fn test_create_pre_approval_with_an_unexpected_body() {
+        use poem::post;
+
+        let url = spawn_mock(token_routes().at(
+            "/collection/v2_0/preapproval",
+            post(mock_pre_approval_unexpected_body),
+        ));
+        let preapproval = PreApprovalRequest::new(
+            Party::msisdn("242064818006"),
+            Currency::EUR.to_string(),
+            String::new(),
+            3600,
+        )
+        .unwrap();
+        let created = collection_at(url)
+            .create_pre_approval(preapproval)
+            .await
+            .unwrap();
+        assert_eq!(created.external_id.len(), 36);
+        assert_eq!(created.status, None);
+        assert_eq!(created.expiration_date_time, None);
+    }
+
+    #[tokio::test]
+    async fn test_create_pre_approval_returns_expiration() {
+        use poem::post;
+
+        let url =
+            spawn_mock(token_routes().at("/collection/v2_0/preapproval", post(mock_pre_approval)));
+
+        let collection = Collection::new(
+            url,
+            Environment::Sandbox,
+            "api_user".to_string(),
+            "api_key".to_string(),
+            "primary_key".to_string(),
+            "secondary_key".to_string(),
+        );
+        let preapproval = PreApprovalRequest::new(
+            Party::msisdn("+242064818006"),
+            Currency::EUR.to_string(),
+            "".to_string(),
+            3600,
+        )
+        .unwrap();
+        let created = collection.create_pre_approval(preapproval).await.unwrap();
+        assert_eq!(created.external_id.len(), 36);
+        assert_eq!(created.status.as_deref(), Some("PENDING"));
+        assert_eq!(
+            created.expiration_at().unwrap().to_rfc3339(),
+            "2026-11-16T10:00:00+00:00"
+        );
+    }
+
+    #[poem::handler]
+    fn mock_environment_balance(req: &poem::Request) -> poem::Response {
+        // answer with the environment received, to check the header value
+        poem::Response::builder().body(format!(
+            r#"{{"availableBalance":"1000","currency":"{}"}}"#,
+            match req.header("X-Target-Environment") {
+                Some("sandbox") => "EUR",
+                Some("mtncongo") => "XAF",
+                _ => "unexpected",
+            }
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_target_environment_header() {
+        use poem::get;
+
+        let url = spawn_mock(token_routes().at(
+            "/collection/v1_0/account/balance",
+            get(mock_environment_balance),
+        ));
+
+        for (environment, currency) in [
+            (Environment::Sandbox, Currency::EUR),
+            (Environment::MTNCONGO, Currency::XAF),
+        ] {
+            let collection = Collection::new(
+                url.clone(),
+                environment,
+                "mock_api_user".to_string(),
+                "mock_api_key".to_string(),
+                "mock_primary_key".to_string(),
+                "mock_secondary_key".to_string(),
+            );
+            let balance = collection.get_account_balance().await.unwrap();
+            assert_eq!(balance.currency, currency);
+        }
+    }
+
+    #[poem::handler]
+    fn mock_pre_approval_reference_id(req: &poem::Request) -> poem::Response {
+        let status = if req.header("X-Reference-Id") == Some("preallocated_reference_id") {
+            poem::http::StatusCode::ACCEPTED
+        } else {
+            poem::http::StatusCode::BAD_REQUEST
+        };
+        poem::Response::builder().status(status).finish()
+    }
+
+    #[tokio::test]
+    async fn test_pre_approval_with_reference_id() {
+        use poem::post;
+
+        let url = spawn_mock(token_routes().at(
+            "/collection/v2_0/preapproval",
+            post(mock_pre_approval_reference_id),
+        ));
+
+        let collection = collection_at(url);
+        let preapproval = PreApprovalRequest::new(
+            Party::msisdn("+242064818006"),
+            Currency::EUR.to_string(),
+            "".to_string(),
+            3600,
+        )
+        .unwrap();
+        assert!(collection.pre_approval(preapproval.clone()).await.is_err());
+        let id = collection
+            .pre_approval(preapproval.with_reference_id("preallocated_reference_id".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(id, "preallocated_reference_id");
+    }
+
+    #[poem::handler]
+    fn mock_request_to_pay_v2(
+        req: &poem::Request,
+        poem::web::Json(body): poem::web::Json<serde_json::Value>,
+    ) -> poem::Response {
+        let status = if req.header("X-Reference-Id") == body["externalId"].as_str()
+            && body["amount"] == "100"
+            && body["payer"]["partyId"] == "256774290781"
+        {
+            poem::http::StatusCode::ACCEPTED
+        } else {
+            poem::http::StatusCode::BAD_REQUEST
+        };
+        poem::Response::builder().status(status).finish()
+    }
+
+    #[tokio::test]
+    async fn test_request_to_pay_v2() {
+        use poem::post;
+
+        // only the v2 path is served, a request sent to v1 fails with 404
+        let url = spawn_mock(token_routes().at(
+            "/collection/v2_0/requesttopay",
+            post(mock_request_to_pay_v2),
+        ));
+
+        let collection = collection_at(url);
+        let request = RequestToPay::new(
+            "100".to_string(),
+            Currency::EUR,
+            Party::msisdn("256774290781"),
+            "payer_message".to_string(),
+            "payee_note".to_string(),
+        );
+        assert!(collection
+            .request_to_pay(request.clone(), None)
+            .await
+            .is_err());
+        let transaction_id = collection
+            .request_to_pay_v2(request.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(transaction_id.as_str(), request.external_id);
+    }
+
+    #[poem::handler]
+    fn mock_accepted() -> poem::Response {
+        poem::Response::builder()
+            .status(poem::http::StatusCode::ACCEPTED)
+            .finish()
+    }
+
+    #[tokio::test]
+    async fn test_invoice_store() {
+        use crate::InMemoryInvoiceStore;
+        use poem::{delete, post};
+
+        let url = spawn_mock(
+            token_routes()
+                .at("/collection/v2_0/invoice", post(mock_accepted))
+                .at(
+                    "/collection/v2_0/invoice/:invoice_id",
+                    delete(mock_accepted),
+                ),
+        );
+
+        let store = Arc::new(InMemoryInvoiceStore::new());
+        let collection = collection_at(url).with_invoice_store(store.clone());
+
+        let invoice = |description: &str| {
+            InvoiceRequest::new(
+                "100".to_string(),
+                "EUR".to_string(),
+                "3600".to_string(),
+                Party::msisdn("256774290781"),
+                Party::msisdn("256774290782"),
+                description.to_string(),
+            )
+        };
+        let kept = collection
+            .create_invoice(invoice("kept"), None)
+            .await
+            .unwrap();
+        let cancelled = collection
+            .create_invoice(invoice("cancelled"), None)
+            .await
+            .unwrap();
+        collection
+            .cancel_invoice(cancelled.as_str(), None)
+            .await
+            .unwrap();
+
+        let outstanding = store.outstanding();
+        assert_eq!(outstanding.len(), 1);
+        assert_eq!(outstanding[0].external_id, kept.as_str());
+        assert_eq!(outstanding[0].description, "kept");
+    }
+
+    #[derive(Default)]
+    struct RecordingMetrics {
+        requests: std::sync::Mutex<Vec<String>>,
+        responses: std::sync::Mutex<Vec<(String, Option<u16>)>>,
+    }
+
+    impl crate::metrics::Metrics for RecordingMetrics {
+        fn on_request(&self, operation: &str) {
+            self.requests.lock().unwrap().push(operation.to_string());
+        }
+
+        fn on_response(
+            &self,
+            operation: &str,
+            status: Option<u16>,
+            _duration: std::time::Duration,
+        ) {
+            self.responses
+                .lock()
+                .unwrap()
+                .push((operation.to_string(), status));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics() {
+        use poem::post;
+
+        // no balance route, MTN answers 404
+        let url = spawn_mock(token_routes().at("/collection/v2_0/invoice", post(mock_accepted)));
+
+        let metrics = Arc::new(RecordingMetrics::default());
+        let collection = collection_at(url).with_metrics(metrics.clone());
+
+        let invoice = InvoiceRequest::new(
+            "100".to_string(),
+            "EUR".to_string(),
+            "3600".to_string(),
+            Party::msisdn("256774290781"),
+            Party::msisdn("256774290782"),
+            "metrics".to_string(),
+        );
+        collection.create_invoice(invoice, None).await.unwrap();
+        assert!(collection.get_account_balance().await.is_err());
+
+        let responses = metrics.responses.lock().unwrap();
+        assert!(responses.contains(&("create_invoice".to_string(), Some(202))));
+        assert!(responses.contains(&("get_account_balance".to_string(), Some(404))));
+        let requests = metrics.requests.lock().unwrap();
+        assert_eq!(requests.len(), responses.len());
+    }
 }
@@ -12,18 +12,24 @@
 use std::sync::Arc;
 
 use crate::{
+    metrics::Metrics,
     responses::{
-        refund_result::RefundResult, token_response::TokenResponse, transfer_result::TransferResult,
+        refund_result::RefundResult,
+        token_response::TokenResponse,
+        transfer_result::{DepositResult, TransferResult},
     },
-    BCAuthorizeResponse, Balance, BasicUserInfoJsonResponse, Currency, DepositId, Environment,
-    OAuth2TokenResponse, RefundId, RefundRequest, TranserId, TransferRequest,
+    AccessType, BCAuthorizeResponse, Balance, BasicUserInfoJsonResponse, Currency, DepositId,
+    Environment, MsisdnPolicy, OAuth2TokenResponse, PartyIdType, ReferenceId, RefundId,
+    RefundRequest, Submitted, TranserId, TransferRequest,
 };
 
 use super::account::Account;
+use super::SendWithMetrics;
 use chrono::Utc;
 use once_cell::sync::Lazy;
 use tokio::sync::Mutex;
 
+#[derive(Clone)]
 pub struct Disbursements {
     pub url: String,
     pub primary_key: String,
@@ -32,6 +38,10 @@ pub struct Disbursements {
     pub api_user: String,
     pub api_key: String,
     account: Account,
+    client: reqwest::Client,
+    metrics: Option<Arc<dyn Metrics>>,
+    headers: reqwest::header::HeaderMap,
+    msisdn_policy: MsisdnPolicy,
 }
 
 static ACCESS_TOKEN: Lazy<Arc<Mutex<Option<TokenResponse>>>> =
@@ -52,7 +62,77 @@ impl Disbursements {
         primary_key: String,
         secondary_key: String,
     ) -> Disbursements {
-        let account = Account {};
+        Disbursements::with_client(
+            url,
+            environment,
+            api_user,
+            api_key,
+            primary_key,
+            secondary_key,
+            super::default_client(),
+        )
+    }
+
+    /// Create a new instance of Disbursements, optionally sending its requests through a proxy
+    ///
+    /// # Parameters
+    ///
+    /// * 'url', MTN MOMO disbursements url
+    /// * 'environment', environement to be used
+    /// * 'api_user', the api user to be used
+    /// * 'api_key', the api key to be use
+    /// * 'primary_key', the primary key of the disbursements product
+    /// * 'secondary_key', the secondary key of the disbursements product
+    /// * 'proxy', url of the outbound proxy, None to connect directly
+    ///
+    /// # Returns
+    /// * Disbursements, or an error when the proxy url is not valid
+    pub fn with_proxy(
+        url: String,
+        environment: Environment,
+        api_user: String,
+        api_key: String,
+        primary_key: String,
+        secondary_key: String,
+        proxy: Option<String>,
+    ) -> Result<Disbursements, Box<dyn std::error::Error>> {
+        let client = super::build_client(proxy.as_deref())?;
+        Ok(Disbursements::with_client(
+            url,
+            environment,
+            api_user,
+            api_key,
+            primary_key,
+            secondary_key,
+            client,
+        ))
+    }
+
+    /// Create a new instance of Disbursements sending its requests with the given client,
+    /// for example one going through a proxy or adding headers expected by an API gateway
+    ///
+    /// # Parameters
+    ///
+    /// * 'url', MTN MOMO disbursements url
+    /// * 'environment', environement to be used
+    /// * 'api_user', the api user to be used
+    /// * 'api_key', the api key to be use
+    /// * 'primary_key', the primary key of the disbursements product
+    /// * 'secondary_key', the secondary key of the disbursements product
+    /// * 'client', the HTTP client used for every call to MTN
+    ///
+    /// # Returns
+    /// * Disbursements
+    pub fn with_client(
+        url: String,
+        environment: Environment,
+        api_user: String,
+        api_key: String,
+        primary_key: String,
+        secondary_key: String,
+        client: reqwest::Client,
+    ) -> Disbursements {
+        let account = Account::with_client(client.clone());
         Disbursements {
             url,
             primary_key,
@@ -61,17 +141,91 @@ impl Disbursements {
             api_key,
             api_user,
             account,
+            client,
+            metrics: None,
+            headers: reqwest::header::HeaderMap::new(),
+            msisdn_policy: MsisdnPolicy::default(),
+        }
+    }
+
+    /// Format the mobile numbers sent to MTN according to the given policy, default = KeepPlus
+    ///
+    /// # Parameters
+    ///
+    /// * 'policy', how the mobile numbers are formatted
+    ///
+    /// # Returns
+    /// * Disbursements
+    pub fn with_msisdn_policy(mut self, policy: MsisdnPolicy) -> Disbursements {
+        self.msisdn_policy = policy;
+        self
+    }
+
+    /// How the mobile numbers sent to MTN are formatted
+    pub fn msisdn_policy(&self) -> MsisdnPolicy {
+        self.msisdn_policy
+    }
+
+    /// Tell the given hooks about every request sent to MTN and how long it took, see `Metrics`
+    ///
+    /// # Parameters
+    ///
+    /// * 'metrics', the hooks
+    ///
+    /// # Returns
+    /// * Disbursements
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Disbursements {
+        self.account = self.account.with_metrics(metrics.clone());
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Send these headers with every request to MTN, e.g. a partner id required by an operator
+    /// or the X-Forwarded-* headers expected by an intermediary
+    ///
+    /// They are added to the headers already set and replace the operation's headers of the same
+    /// name. For the headers of a single call, set them on a clone of the product,
+    /// `disbursements.clone().with_default_headers(headers).transfer(transfer)`.
+    ///
+    /// # Parameters
+    ///
+    /// * 'headers', the headers to add
+    ///
+    /// # Returns
+    /// * Disbursements
+    pub fn with_default_headers(mut self, headers: reqwest::header::HeaderMap) -> Disbursements {
+        self.account = self.account.with_default_headers(headers.clone());
+        self.headers.extend(headers);
+        self
+    }
+
+    /// The authorization helper, sending its requests with the client, headers and hooks of the
+    /// product
+    fn authorization(&self) -> crate::products::auth::Authorization {
+        let auth = crate::products::auth::Authorization::with_client(self.client.clone())
+            .with_default_headers(self.headers.clone());
+        match &self.metrics {
+            Some(metrics) => auth.with_metrics(metrics.clone()),
+            None => auth,
         }
     }
 
     /// This operation is used to create an access token
     ///
+    /// Normal flows don't need it, every operation requests and caches a token on its own.
+    /// It is meant for calling MTN endpoints this crate does not wrap; the new token is cached
+    /// and used by the next operations.
+    ///
     /// # Returns
     ///
     /// * 'TokenResponse'
-    async fn create_access_token(&self) -> Result<TokenResponse, Box<dyn std::error::Error>> {
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "disbursements", operation = "create_access_token")
+    )]
+    pub async fn create_access_token(&self) -> Result<TokenResponse, Box<dyn std::error::Error>> {
         let url = format!("{}/{}", self.url, "disbursement");
-        let auth = crate::products::auth::Authorization {};
+        let auth = self.authorization();
         let token = auth
             .create_access_token(
                 url,
@@ -100,12 +254,12 @@ impl Disbursements {
         auth_req_id: String,
     ) -> Result<OAuth2TokenResponse, Box<dyn std::error::Error>> {
         let url = format!("{}/{}", self.url, "disbursement");
-        let auth = crate::products::auth::Authorization {};
+        let auth = self.authorization();
         auth.create_o_auth_2_token(
             url,
             self.api_user.clone(),
             self.api_key.clone(),
-            self.environment.clone(),
+            self.environment,
             self.primary_key.clone(),
             auth_req_id,
         )
@@ -118,26 +272,36 @@ impl Disbursements {
     ///
     /// * 'msisdn', this is the phone number of the user
     /// * 'callback_url', this is the url that will be used to notify the client of the status of the transaction
+    /// * 'scope', the scope requested from the user, e.g. "profile" or "openid", default = profile
+    /// * 'access_type', the access type requested, default = offline
     ///
     /// # Returns
     ///
     /// * 'BCAuthorizeResponse'
-    #[allow(dead_code)]
-    async fn bc_authorize(
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "disbursements", operation = "bc_authorize")
+    )]
+    pub async fn bc_authorize(
         &self,
         msisdn: String,
         callback_url: Option<&str>,
+        scope: Option<&str>,
+        access_type: Option<AccessType>,
     ) -> Result<BCAuthorizeResponse, Box<dyn std::error::Error>> {
+        let msisdn = self.msisdn_policy.normalize(&msisdn);
         let url = format!("{}/{}", self.url, "disbursement");
-        let auth = crate::products::auth::Authorization {};
+        let auth = self.authorization();
         let access_token: TokenResponse = self.create_access_token().await?;
         auth.bc_authorize(
             url,
-            self.environment.clone(),
+            self.environment,
             self.primary_key.clone(),
             msisdn,
             callback_url,
             access_token,
+            scope,
+            access_type,
         )
         .await
     }
@@ -146,24 +310,24 @@ impl Disbursements {
     ///
     /// # Returns
     /// * 'TokenResponse'
-    async fn get_valid_access_token(&self) -> Result<TokenResponse, Box<dyn std::error::Error>> {
-        let token = ACCESS_TOKEN.lock().await;
-        if token.is_some() {
-            let token = token.clone().unwrap();
-            if token.created_at.is_some() {
-                let created_at = token.created_at.unwrap();
+    pub(crate) async fn get_valid_access_token(
+        &self,
+    ) -> Result<TokenResponse, Box<dyn std::error::Error>> {
+        let token = ACCESS_TOKEN.lock().await.clone();
+        if let Some(token) = token {
+            if let Some(created_at) = token.created_at {
                 let expires_in = token.expires_in;
                 let now = Utc::now();
                 let duration = now.signed_duration_since(created_at);
                 if duration.num_seconds() < expires_in as i64 {
+                    tracing::trace!("using the cached access token");
                     return Ok(token);
                 }
-                let token: TokenResponse = self.create_access_token().await?;
-                return Ok(token);
             }
         }
+        tracing::debug!("requesting a new access token");
         let token: TokenResponse = self.create_access_token().await?;
-        return Ok(token);
+        Ok(token)
     }
 
     /// Deposit operation is used to deposit an amount from the owner’s account to a payee account.
@@ -175,13 +339,16 @@ impl Disbursements {
     ///
     /// # Returns
     ///
-    /// * 'DepositId' (mtn external id)
+    /// * 'Submitted<DepositId>' (mtn external id)
+    #[tracing::instrument(skip_all, fields(product = "disbursements", operation = "deposit_v1", external_id = %transfer.external_id))]
     pub async fn deposit_v1(
         &self,
-        transfer: TransferRequest,
+        mut transfer: TransferRequest,
         callback_url: Option<&str>,
-    ) -> Result<DepositId, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+    ) -> Result<Submitted<DepositId>, Box<dyn std::error::Error>> {
+        transfer.validate()?;
+        transfer.payee = transfer.payee.normalized(self.msisdn_policy);
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let mut req = client
             .post(format!("{}/disbursement/v1_0/deposit", self.url))
@@ -193,21 +360,17 @@ impl Disbursements {
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
             .body(transfer.clone());
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
+        req = super::with_callback_url(req, callback_url);
 
-        let res = req.send().await?;
+        let res = req.send_with(self.metrics.as_deref(), &self.headers, "deposit_v1").await?;
 
         if res.status().is_success() {
-            Ok(DepositId(transfer.external_id))
+            Ok(Submitted::new(
+                DepositId(transfer.external_id),
+                res.status(),
+            ))
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -220,13 +383,16 @@ impl Disbursements {
     ///
     /// # Returns
     ///
-    /// * 'DepositId' (mtn external id)
+    /// * 'Submitted<DepositId>' (mtn external id)
+    #[tracing::instrument(skip_all, fields(product = "disbursements", operation = "deposit_v2", external_id = %transfer.external_id))]
     pub async fn deposit_v2(
         &self,
-        transfer: TransferRequest,
+        mut transfer: TransferRequest,
         callback_url: Option<&str>,
-    ) -> Result<DepositId, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+    ) -> Result<Submitted<DepositId>, Box<dyn std::error::Error>> {
+        transfer.validate()?;
+        transfer.payee = transfer.payee.normalized(self.msisdn_policy);
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let mut req = client
             .post(format!("{}/disbursement/v2_0/deposit", self.url))
@@ -238,21 +404,17 @@ impl Disbursements {
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
             .body(transfer.clone());
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
+        req = super::with_callback_url(req, callback_url);
 
-        let res = req.send().await?;
+        let res = req.send_with(self.metrics.as_deref(), &self.headers, "deposit_v2").await?;
 
         if res.status().is_success() {
-            Ok(DepositId(transfer.external_id))
+            Ok(Submitted::new(
+                DepositId(transfer.external_id),
+                res.status(),
+            ))
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -265,12 +427,13 @@ impl Disbursements {
     ///
     /// # Returns
     ///
-    /// * 'TransferResult'
+    /// * 'DepositResult', the same body as the status of a transfer
+    #[tracing::instrument(skip_all, fields(product = "disbursements", operation = "get_deposit_status", external_id = %deposit_id))]
     pub async fn get_deposit_status(
         &self,
         deposit_id: String,
-    ) -> Result<TransferResult, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+    ) -> Result<DepositResult, Box<dyn std::error::Error>> {
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let res = client
             .get(format!(
@@ -281,18 +444,15 @@ impl Disbursements {
             .header("X-Target-Environment", self.environment.to_string())
             .header("Cache-Control", "no-cache")
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "get_deposit_status")
             .await?;
 
         if res.status().is_success() {
             let body = res.text().await?;
-            let transfer_result: TransferResult = serde_json::from_str(&body)?;
-            Ok(transfer_result)
+            let deposit_result: DepositResult = serde_json::from_str(&body)?;
+            Ok(deposit_result)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -306,11 +466,12 @@ impl Disbursements {
     /// # Returns
     ///
     /// * 'RefundResult'
+    #[tracing::instrument(skip_all, fields(product = "disbursements", operation = "get_refund_status", external_id = %reference_id))]
     pub async fn get_refund_status(
         &self,
         reference_id: &str,
     ) -> Result<RefundResult, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let res = client
             .get(format!(
@@ -321,7 +482,7 @@ impl Disbursements {
             .header("X-Target-Environment", self.environment.to_string())
             .header("Cache-Control", "no-cache")
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "get_refund_status")
             .await?;
 
         if res.status().is_success() {
@@ -329,10 +490,7 @@ impl Disbursements {
             let refund_result: RefundResult = serde_json::from_str(&body)?;
             Ok(refund_result)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -346,11 +504,12 @@ impl Disbursements {
     /// # Returns
     ///
     /// * 'TransferResult'
+    #[tracing::instrument(skip_all, fields(product = "disbursements", operation = "get_transfer_status", external_id = %transfer_id))]
     pub async fn get_transfer_status(
         &self,
         transfer_id: &str,
     ) -> Result<TransferResult, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let res = client
             .get(format!(
@@ -361,7 +520,7 @@ impl Disbursements {
             .header("X-Target-Environment", self.environment.to_string())
             .header("Cache-Control", "no-cache")
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "get_transfer_status")
             .await?;
 
         if res.status().is_success() {
@@ -369,10 +528,7 @@ impl Disbursements {
             let transfer_result: TransferResult = serde_json::from_str(&body)?;
             Ok(transfer_result)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -386,14 +542,20 @@ impl Disbursements {
     ///
     /// # Returns
     ///
-    /// * 'RefundId', this is the reference id of the transaction (mtn external id)
+    /// * 'Submitted<RefundId>', this is the reference id of the transaction (mtn external id), the one set
+    ///   with `RefundRequest::with_reference_id` or a new UUID
+    #[tracing::instrument(skip_all, fields(product = "disbursements", operation = "refund_v1", external_id = tracing::field::Empty))]
     pub async fn refund_v1(
         &self,
         refund: RefundRequest,
         callback_url: Option<&str>,
-    ) -> Result<RefundId, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
-        let refund_id = uuid::Uuid::new_v4().to_string();
+    ) -> Result<Submitted<RefundId>, Box<dyn std::error::Error>> {
+        let client = &self.client;
+        let refund_id = refund
+            .reference_id
+            .clone()
+            .unwrap_or_else(|| ReferenceId::new().into());
+        tracing::Span::current().record("external_id", tracing::field::display(&refund_id));
         let access_token = self.get_valid_access_token().await?;
         let mut req = client
             .post(format!("{}/disbursement/v1_0/refund", self.url))
@@ -404,21 +566,14 @@ impl Disbursements {
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
             .body(refund);
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
+        req = super::with_callback_url(req, callback_url);
 
-        let res = req.send().await?;
+        let res = req.send_with(self.metrics.as_deref(), &self.headers, "refund_v1").await?;
 
         if res.status().is_success() {
-            Ok(RefundId(refund_id))
+            Ok(Submitted::new(RefundId(refund_id), res.status()))
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -432,14 +587,20 @@ impl Disbursements {
     ///
     /// # Returns
     ///
-    /// * 'RefundId', this is the reference id of the transaction (mtn external id)
+    /// * 'Submitted<RefundId>', this is the reference id of the transaction (mtn external id), the one set
+    ///   with `RefundRequest::with_reference_id` or a new UUID
+    #[tracing::instrument(skip_all, fields(product = "disbursements", operation = "refund_v2", external_id = tracing::field::Empty))]
     pub async fn refund_v2(
         &self,
         refund: RefundRequest,
         callback_url: Option<&str>,
-    ) -> Result<RefundId, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
-        let refund_id = uuid::Uuid::new_v4().to_string();
+    ) -> Result<Submitted<RefundId>, Box<dyn std::error::Error>> {
+        let client = &self.client;
+        let refund_id = refund
+            .reference_id
+            .clone()
+            .unwrap_or_else(|| ReferenceId::new().into());
+        tracing::Span::current().record("external_id", tracing::field::display(&refund_id));
         let access_token = self.get_valid_access_token().await?;
         let mut req = client
             .post(format!("{}/disbursement/v2_0/refund", self.url))
@@ -450,21 +611,14 @@ impl Disbursements {
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
             .body(refund);
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
+        req = super::with_callback_url(req, callback_url);
 
-        let res = req.send().await?;
+        let res = req.send_with(self.metrics.as_deref(), &self.headers, "refund_v2").await?;
 
         if res.status().is_success() {
-            Ok(RefundId(refund_id))
+            Ok(Submitted::new(RefundId(refund_id), res.status()))
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -477,13 +631,16 @@ impl Disbursements {
     ///
     /// # Returns
     ///
-    /// * 'TranserId', this is the reference id of the transaction (mtn external id)
+    /// * 'Submitted<TranserId>', this is the reference id of the transaction (mtn external id)
+    #[tracing::instrument(skip_all, fields(product = "disbursements", operation = "transfer", external_id = %transfer.external_id))]
     pub async fn transfer(
         &self,
-        transfer: TransferRequest,
+        mut transfer: TransferRequest,
         callback_url: Option<&str>,
-    ) -> Result<TranserId, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+    ) -> Result<Submitted<TranserId>, Box<dyn std::error::Error>> {
+        transfer.validate()?;
+        transfer.payee = transfer.payee.normalized(self.msisdn_policy);
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let mut req = client
             .post(format!("{}/disbursement/v1_0/transfer", self.url))
@@ -494,21 +651,17 @@ impl Disbursements {
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
             .body(transfer.clone());
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
+        req = super::with_callback_url(req, callback_url);
 
-        let res = req.send().await?;
+        let res = req.send_with(self.metrics.as_deref(), &self.headers, "transfer").await?;
 
         if res.status().is_success() {
-            Ok(TranserId(transfer.external_id))
+            Ok(Submitted::new(
+                TranserId(transfer.external_id),
+                res.status(),
+            ))
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -516,6 +669,10 @@ impl Disbursements {
     /// # Returns
     ///
     /// * 'Balance', the balance
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "disbursements", operation = "get_account_balance")
+    )]
     pub async fn get_account_balance(&self) -> Result<Balance, Box<dyn std::error::Error>> {
         let url = format!("{}/disbursement", self.url);
         let access_token = self.get_valid_access_token().await?;
@@ -537,6 +694,13 @@ impl Disbursements {
     /// # Returns
     ///
     /// * 'Balance', the balance
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            product = "disbursements",
+            operation = "get_account_balance_in_specific_currency"
+        )
+    )]
     pub async fn get_account_balance_in_specific_currency(
         &self,
         currency: Currency,
@@ -562,18 +726,50 @@ impl Disbursements {
     /// # Returns
     ///
     /// * 'BasicUserInfoJsonResponse'
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "disbursements", operation = "get_basic_user_info")
+    )]
     pub async fn get_basic_user_info(
         &self,
         account_holder_msisdn: &str,
     ) -> Result<BasicUserInfoJsonResponse, Box<dyn std::error::Error>> {
+        self.get_basic_user_info_by_id(PartyIdType::MSISDN, account_holder_msisdn)
+            .await
+    }
+
+    /// This operation is used to get the basic information of the account holder, identified
+    /// by any kind of account holder id
+    ///
+    /// # Parameters
+    /// * 'account_holder_id_type', the kind of id, e.g. EMAIL
+    /// * 'account_holder_id', the MSISDN, e-mail or party code of the account holder
+    ///
+    /// # Returns
+    ///
+    /// * 'BasicUserInfoJsonResponse'
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "disbursements", operation = "get_basic_user_info")
+    )]
+    pub async fn get_basic_user_info_by_id(
+        &self,
+        account_holder_id_type: PartyIdType,
+        account_holder_id: &str,
+    ) -> Result<BasicUserInfoJsonResponse, Box<dyn std::error::Error>> {
+        let account_holder_id = match account_holder_id_type {
+            PartyIdType::MSISDN => self.msisdn_policy.normalize(account_holder_id),
+            _ => account_holder_id.to_string(),
+        };
         let url = format!("{}/disbursement", self.url);
         let access_token = self.get_valid_access_token().await?;
         self.account
-            .get_basic_user_info(
+            .get_basic_user_info_by_id(
                 url,
                 self.environment,
                 self.primary_key.clone(),
-                account_holder_msisdn,
+                account_holder_id_type,
+                &account_holder_id,
                 access_token,
             )
             .await
@@ -588,6 +784,10 @@ impl Disbursements {
     /// # Returns
     ///
     /// * 'BasicUserInfoJsonResponse'
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "disbursements", operation = "get_user_info_with_consent")
+    )]
     pub async fn get_user_info_with_consent(
         &self,
         access_token: String,
@@ -608,13 +808,58 @@ impl Disbursements {
     /// # Parameters
     ///
     /// * 'account_holder_id', The MSISDN or email of the account holder
-    /// * 'account_holder_type', The type of the account holder.
+    /// * 'account_holder_type', The type of the account holder, MSISDN, EMAIL or PARTY_CODE
     ///
     ///
     /// # Returns
     ///
     /// * ()
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            product = "disbursements",
+            operation = "validate_account_holder_status"
+        )
+    )]
     pub async fn validate_account_holder_status(
+        &self,
+        account_holder_id: &str,
+        account_holder_type: PartyIdType,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let url = format!("{}/disbursement", self.url);
+        let access_token = self.get_valid_access_token().await?;
+        self.account
+            .validate_account_holder_status(
+                url,
+                self.environment,
+                self.primary_key.clone(),
+                account_holder_id,
+                account_holder_type.account_holder_id_type(),
+                access_token,
+            )
+            .await
+    }
+
+    /// this operation is used to validate the status of an account holder, the type is sent as is
+    ///
+    /// # Parameters
+    ///
+    /// * 'account_holder_id', The MSISDN or email of the account holder
+    /// * 'account_holder_type', The type of the account holder, e.g. "msisdn"
+    ///
+    ///
+    /// # Returns
+    ///
+    /// * ()
+    #[deprecated(note = "use validate_account_holder_status with a PartyIdType")]
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            product = "disbursements",
+            operation = "validate_account_holder_status_str"
+        )
+    )]
+    pub async fn validate_account_holder_status_str(
         &self,
         account_holder_id: &str,
         account_holder_type: &str,
@@ -634,14 +879,36 @@ impl Disbursements {
     }
 }
 
+impl std::fmt::Debug for Disbursements {
+    /// The keys are masked, so that a product can be logged
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Disbursements")
+            .field("url", &self.url)
+            .field("environment", &self.environment)
+            .field("api_user", &self.api_user)
+            .field("api_key", &crate::redaction::MASK)
+            .field("primary_key", &crate::redaction::MASK)
+            .field("secondary_key", &crate::redaction::MASK)
+            .finish_non_exhaustive()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{MomoCollection, Party, PartyIdType, RequestToPay, TransferRequest};
+    use crate::products::test_server::{
+        balance_routes, disbursements_at, mock_requires_headers, mock_transfer,
+        mock_transfer_status, short_lived_token_routes, spawn_mock,
+    };
+    use crate::{MomoCollection, Party, RequestToPay, TransferRequest};
     use dotenv::dotenv;
     use std::env;
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_account_balance() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -662,42 +929,31 @@ mod tests {
             secondary_key,
         );
         let balance_result = disbursements.get_account_balance().await;
-        if balance_result.is_ok() {
-            let balance = balance_result.unwrap();
+        if let Ok(balance) = balance_result {
             assert_eq!(balance.currency, Currency::EUR);
         }
     }
 
     #[tokio::test]
     async fn test_get_account_balance_in_specific_currency() {
-        dotenv().ok();
-        let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
-
-        let primary_key =
-            env::var("MTN_DISBURSEMENT_PRIMARY_KEY").expect("PRIMARY_KEY must be set");
-        let secondary_key =
-            env::var("MTN_DISBURSEMENT_SECONDARY_KEY").expect("SECONDARY_KEY must be set");
-        let api_user = env::var("MTN_API_USER").expect("API_USER must be set");
-        let api_key = env::var("MTN_API_KEY").expect("API_KEY must be set");
-
-        let disbursements = Disbursements::new(
-            mtn_url,
-            Environment::Sandbox,
-            api_user,
-            api_key,
-            primary_key,
-            secondary_key,
-        );
-        let balance_result = disbursements
+        let disbursements = disbursements_at(spawn_mock(balance_routes()));
+        let balance = disbursements
             .get_account_balance_in_specific_currency(Currency::EUR)
-            .await;
-        if balance_result.is_ok() {
-            let balance = balance_result.unwrap();
-            assert_eq!(balance.currency, Currency::EUR);
-        }
+            .await
+            .unwrap();
+        assert_eq!(balance.currency, Currency::EUR);
+        assert_eq!(balance.available_balance, "1000");
+        assert!(disbursements
+            .get_account_balance_in_specific_currency(Currency::GHS)
+            .await
+            .is_err());
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_basic_user_info() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -724,6 +980,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_validate_account_holder_status() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -743,12 +1003,16 @@ mod tests {
             secondary_key,
         );
         let validate_account_holder_status_result = disbursements
-            .validate_account_holder_status("256774290781", "MSISDN")
+            .validate_account_holder_status("256774290781", PartyIdType::MSISDN)
             .await;
         assert!(validate_account_holder_status_result.is_ok());
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_bc_authorize() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -767,12 +1031,18 @@ mod tests {
             primary_key,
             secondary_key,
         );
-        let bc_authorize_res = disbursements.bc_authorize("563607".to_string(), None).await;
+        let bc_authorize_res = disbursements
+            .bc_authorize("563607".to_string(), None, None, None)
+            .await;
         assert!(bc_authorize_res.is_ok());
         assert_ne!(bc_authorize_res.unwrap().auth_req_id.len(), 0);
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_create_o_auth_2_token() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -792,7 +1062,9 @@ mod tests {
             secondary_key,
         );
 
-        let bc_authorize_res = disbursements.bc_authorize("563607".to_string(), None).await;
+        let bc_authorize_res = disbursements
+            .bc_authorize("563607".to_string(), None, None, None)
+            .await;
         assert!(bc_authorize_res.is_ok());
 
         let res = disbursements
@@ -803,6 +1075,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_user_info_with_consent() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -821,7 +1097,9 @@ mod tests {
             primary_key,
             secondary_key,
         );
-        let bc_authorize_res = disbursements.bc_authorize("563607".to_string(), None).await;
+        let bc_authorize_res = disbursements
+            .bc_authorize("563607".to_string(), None, None, None)
+            .await;
         assert!(bc_authorize_res.is_ok());
 
         let res = disbursements
@@ -837,6 +1115,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_deposit_v1() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -873,6 +1155,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_deposit_v2() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -909,6 +1195,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_deposit_status() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -948,6 +1238,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_refund_v1() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1000,7 +1294,7 @@ mod tests {
             Currency::EUR.to_string(),
             "payer_message".to_string(),
             "payee_note".to_string(),
-            res.unwrap().0,
+            res.unwrap().into_id().0,
         );
         let refund_res = disbursements.refund_v1(refund, None).await;
         assert!(refund_res.is_ok());
@@ -1008,6 +1302,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_refund_v2() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1060,7 +1358,7 @@ mod tests {
             Currency::EUR.to_string(),
             "payer_message".to_string(),
             "payee_note".to_string(),
-            res.unwrap().0,
+            res.unwrap().into_id().0,
         );
         let refund_res = disbursements.refund_v2(refund, None).await;
         assert!(refund_res.is_ok());
@@ -1068,6 +1366,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_refund_status() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1119,7 +1421,7 @@ mod tests {
             Currency::EUR.to_string(),
             "payer_message".to_string(),
             "payee_note".to_string(),
-            res.unwrap().0,
+            res.unwrap().into_id().0,
         );
         let refund_res = disbursements.refund_v2(refund, None).await;
         assert!(refund_res.is_ok());
@@ -1131,6 +1433,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_transfer() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1166,6 +1472,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_transfer_status() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -1203,4 +1513,63 @@ mod tests {
             .await;
         assert!(status_result.is_ok());
     }
+
+    fn mock_disbursements(app: poem::Route) -> Disbursements {
+        disbursements_at(spawn_mock(short_lived_token_routes().nest("/", app)))
+    }
+
+    #[tokio::test]
+    async fn test_transfer_sends_headers() {
+        let disbursements = mock_disbursements(poem::Route::new().at(
+            "/disbursement/v1_0/transfer",
+            poem::post(mock_requires_headers),
+        ));
+        let transfer = mock_transfer();
+        let transfer_id = disbursements
+            .transfer(transfer.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(transfer_id.as_string(), transfer.external_id);
+    }
+
+    #[tokio::test]
+    async fn test_refund_v2_reference_id() {
+        let disbursements = mock_disbursements(poem::Route::new().at(
+            "/disbursement/v2_0/refund",
+            poem::post(mock_requires_headers),
+        ));
+        let refund = || {
+            RefundRequest::new(
+                "100".to_string(),
+                "EUR".to_string(),
+                "payer_message".to_string(),
+                "payee_note".to_string(),
+                uuid::Uuid::new_v4().to_string(),
+            )
+        };
+
+        let generated = disbursements.refund_v2(refund(), None).await.unwrap();
+        assert!(uuid::Uuid::parse_str(&generated.as_string()).is_ok());
+
+        let reference_id = uuid::Uuid::new_v4().to_string();
+        let refund_id = disbursements
+            .refund_v2(refund().with_reference_id(reference_id.clone()), None)
+            .await
+            .unwrap();
+        assert_eq!(refund_id.as_string(), reference_id);
+    }
+
+    #[tokio::test]
+    async fn test_get_transfer_status_path() {
+        let disbursements = mock_disbursements(poem::Route::new().at(
+            "/disbursement/v1_0/transfer/:transfer_id",
+            poem::get(mock_transfer_status),
+        ));
+        let status = disbursements
+            .get_transfer_status("transfer_id")
+            .await
+            .unwrap();
+        assert_eq!(status.external_id, "transfer_id");
+        assert_eq!(status.status, "SUCCESSFUL");
+    }
 }
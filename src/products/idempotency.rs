@@ -0,0 +1,135 @@
+//! Idempotency of the requests sent to MTN
+//!
+//! MTN deduplicates requests on their X-Reference-Id, but a caller retrying a request that
+//! already went through would still send it again. When an `IdempotencyStore` is set on a
+//! product, a request whose external id already succeeded is not sent a second time, it fails
+//! with a `DuplicateReference` error instead. The external id is reserved before the request
+//! is sent, so two concurrent calls with the same external id can't both reach MTN. Implement
+//! the trait to share the store between processes, e.g. in Redis.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Mutex;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+/// # IdempotencyStore
+/// Remembers the requests being sent and the ones that succeeded, keyed on their external id
+pub trait IdempotencyStore: Send + Sync {
+    /// The id returned the last time a request with this external id succeeded, if any
+    fn get(&self, external_id: &str) -> Option<String>;
+
+    /// Claim the external id for a request about to be sent, atomically, e.g. with Redis
+    /// `SET external_id "" NX`
+    ///
+    /// # Returns
+    ///
+    /// * 'bool', false when the external id is already claimed, by a request being sent or one
+    ///   that succeeded, the request must not be sent
+    fn reserve(&self, external_id: &str) -> bool;
+
+    /// Forget the claim on the external id, the request failed and can be sent again
+    fn release(&self, external_id: &str);
+
+    /// Remember that the request with this external id succeeded
+    fn put(&self, external_id: &str, id: String);
+}
+
+/// # InMemoryIdempotencyStore
+/// An `IdempotencyStore` keeping the requests in memory for a given window
+///
+/// Not available on wasm32, which has no `Instant`.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct InMemoryIdempotencyStore {
+    window: Duration,
+    entries: Mutex<HashMap<String, (Instant, Option<String>)>>, // None while being sent
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl InMemoryIdempotencyStore {
+    /// Create a new store
+    ///
+    /// # Parameters
+    ///
+    /// * 'window', how long a request is remembered, from the time it was reserved or succeeded
+    pub fn new(window: Duration) -> InMemoryIdempotencyStore {
+        InMemoryIdempotencyStore {
+            window,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IdempotencyStore for InMemoryIdempotencyStore {
+    fn get(&self, external_id: &str) -> Option<String> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(external_id)
+            .filter(|(created_at, _)| created_at.elapsed() < self.window)
+            .and_then(|(_, id)| id.clone())
+    }
+
+    fn reserve(&self, external_id: &str) -> bool {
+        let mut entries = self.entries.lock().unwrap();
+        let window = self.window;
+        entries.retain(|_, (created_at, _)| created_at.elapsed() < window);
+        if entries.contains_key(external_id) {
+            return false;
+        }
+        entries.insert(external_id.to_string(), (Instant::now(), None));
+        true
+    }
+
+    fn release(&self, external_id: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some((_, None)) = entries.get(external_id) {
+            entries.remove(external_id);
+        }
+    }
+
+    fn put(&self, external_id: &str, id: String) {
+        let mut entries = self.entries.lock().unwrap();
+        let window = self.window;
+        entries.retain(|_, (created_at, _)| created_at.elapsed() < window);
+        entries.insert(external_id.to_string(), (Instant::now(), Some(id)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_store() {
+        let store = InMemoryIdempotencyStore::new(Duration::from_secs(60));
+        assert_eq!(store.get("external_id"), None);
+        store.put("external_id", "transaction_id".to_string());
+        assert_eq!(store.get("external_id"), Some("transaction_id".to_string()));
+        assert_eq!(store.get("other_external_id"), None);
+    }
+
+    #[test]
+    fn test_in_memory_store_reserve() {
+        let store = InMemoryIdempotencyStore::new(Duration::from_secs(60));
+        assert!(store.reserve("external_id"));
+        assert!(!store.reserve("external_id"));
+        assert_eq!(store.get("external_id"), None);
+
+        store.release("external_id");
+        assert!(store.reserve("external_id"));
+        store.put("external_id", "transaction_id".to_string());
+        store.release("external_id");
+        assert!(!store.reserve("external_id"));
+        assert_eq!(store.get("external_id"), Some("transaction_id".to_string()));
+    }
+
+    #[test]
+    fn test_in_memory_store_window() {
+        let store = InMemoryIdempotencyStore::new(Duration::from_millis(10));
+        store.put("external_id", "transaction_id".to_string());
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(store.get("external_id"), None);
+    }
+}
@@ -0,0 +1,53 @@
+//! Tracking of the invoices created through the crate
+//!
+//! MTN has no endpoint listing the invoices of an account, an invoice can only be fetched by its
+//! id. When an `InvoiceStore` is set on the Collection product, it is told about every invoice
+//! created and cancelled, so that the outstanding invoices can be listed and polled with
+//! `Collection::get_invoice_status`. Implement the trait to keep them in a database.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::InvoiceRequest;
+
+/// # InvoiceStore
+/// Told about the invoices created and cancelled by the Collection product
+pub trait InvoiceStore: Send + Sync {
+    /// The invoice was created, its id is `invoice.external_id`
+    fn created(&self, invoice: &InvoiceRequest);
+
+    /// The invoice with this id was cancelled
+    fn cancelled(&self, invoice_id: &str);
+}
+
+/// # InMemoryInvoiceStore
+/// An `InvoiceStore` keeping the invoices that were not cancelled in memory
+#[derive(Default)]
+pub struct InMemoryInvoiceStore {
+    invoices: Mutex<HashMap<String, InvoiceRequest>>,
+}
+
+impl InMemoryInvoiceStore {
+    /// Create a new, empty, store
+    pub fn new() -> InMemoryInvoiceStore {
+        InMemoryInvoiceStore::default()
+    }
+
+    /// The invoices created and not cancelled, whatever their status at MTN
+    pub fn outstanding(&self) -> Vec<InvoiceRequest> {
+        self.invoices.lock().unwrap().values().cloned().collect()
+    }
+}
+
+impl InvoiceStore for InMemoryInvoiceStore {
+    fn created(&self, invoice: &InvoiceRequest) {
+        self.invoices
+            .lock()
+            .unwrap()
+            .insert(invoice.external_id.clone(), invoice.clone());
+    }
+
+    fn cancelled(&self, invoice_id: &str) {
+        self.invoices.lock().unwrap().remove(invoice_id);
+    }
+}
@@ -2,5 +2,249 @@ pub mod account;
 pub mod auth;
 pub mod collection;
 pub mod disbursements;
+pub mod idempotency;
+pub mod invoice_store;
 pub mod provisioning;
 pub mod remittance;
+#[cfg(test)]
+pub(crate) mod test_server;
+
+use std::future::Future;
+
+use crate::{enums::callback_type::CallbackType, metrics::Metrics};
+
+/// The header MTN reads the callback url of a request from
+pub const CALLBACK_URL_HEADER: &str = "X-Callback-Url";
+
+/// The callback header of a request whose callback is served by the callback server
+///
+/// # Parameters
+///
+/// * 'base_url', where the callback server is reachable, e.g. "https://callbacks.example.com"
+/// * 'callback_type', the kind of callback the request triggers
+///
+/// # Returns
+///
+/// * the header name and its value, e.g. "https://callbacks.example.com/collection_request_to_pay/REQUEST_TO_PAY"
+pub fn callback_header(
+    base_url: &str,
+    callback_type: CallbackType,
+) -> (reqwest::header::HeaderName, String) {
+    (
+        reqwest::header::HeaderName::from_static("x-callback-url"),
+        callback_type.callback_url(base_url),
+    )
+}
+
+/// Set the callback header of a request, when there is a callback url
+pub(crate) fn with_callback_url(
+    req: reqwest::RequestBuilder,
+    callback_url: Option<&str>,
+) -> reqwest::RequestBuilder {
+    match callback_url {
+        Some(callback_url) if !callback_url.is_empty() => {
+            req.header(CALLBACK_URL_HEADER, callback_url)
+        }
+        _ => req,
+    }
+}
+
+/// Send a request with the default headers of the product, telling the metrics hooks about it
+/// when there are some
+pub(crate) trait SendWithMetrics {
+    /// # Parameters
+    ///
+    /// * 'metrics', the hooks of the product, None to only send the request
+    /// * 'headers', the default headers of the product, they replace the headers of the same
+    ///   name set by the operation
+    /// * 'operation', the operation sending the request, e.g. "request_to_pay"
+    fn send_with(
+        self,
+        metrics: Option<&dyn Metrics>,
+        headers: &reqwest::header::HeaderMap,
+        operation: &str,
+    ) -> impl Future<Output = Result<reqwest::Response, reqwest::Error>>;
+}
+
+impl SendWithMetrics for reqwest::RequestBuilder {
+    async fn send_with(
+        self,
+        metrics: Option<&dyn Metrics>,
+        headers: &reqwest::header::HeaderMap,
+        operation: &str,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let req = if headers.is_empty() {
+            self
+        } else {
+            self.headers(headers.clone())
+        };
+        let Some(metrics) = metrics else {
+            return req.send().await;
+        };
+        metrics.on_request(operation);
+        // chrono rather than Instant, which is not available in wasm
+        let started = chrono::Utc::now();
+        let res = req.send().await;
+        let duration = (chrono::Utc::now() - started).to_std().unwrap_or_default();
+        metrics.on_response(
+            operation,
+            res.as_ref().ok().map(|res| res.status().as_u16()),
+            duration,
+        );
+        res
+    }
+}
+
+/// The User-Agent of the requests sent to MTN, unless the client is built with another one
+pub const DEFAULT_USER_AGENT: &str = concat!("mtnmomo-rs/", env!("CARGO_PKG_VERSION"));
+
+/// Build the client used to call MTN, going through the given proxy when there is one
+///
+/// # Parameters
+///
+/// * 'proxy', url of the proxy every request is sent through, e.g. http://proxy.local:3128
+///
+/// # Returns
+///
+/// * 'reqwest::Client', or an error when the proxy url is not valid
+pub(crate) fn build_client(
+    proxy: Option<&str>,
+) -> Result<reqwest::Client, Box<dyn std::error::Error>> {
+    build_http_client(proxy, DEFAULT_USER_AGENT)
+}
+
+/// Build a client like the one the products build, with another User-Agent,
+/// to give to the `with_client` constructor of a product
+///
+/// # Parameters
+///
+/// * 'proxy', url of the proxy every request is sent through, e.g. http://proxy.local:3128
+/// * 'user_agent', the User-Agent of every request, e.g. "my-shop/1.2", ignored in wasm where
+///   the browser sets it
+///
+/// # Returns
+///
+/// * 'reqwest::Client', or an error when the proxy url is not valid
+pub fn build_http_client(
+    proxy: Option<&str>,
+    user_agent: &str,
+) -> Result<reqwest::Client, Box<dyn std::error::Error>> {
+    let builder = reqwest::Client::builder();
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut builder = builder.user_agent(user_agent);
+    #[cfg(target_arch = "wasm32")]
+    let _ = user_agent;
+    // rustls wins when both TLS features are enabled
+    #[cfg(all(feature = "rustls-tls", not(target_arch = "wasm32")))]
+    {
+        builder = builder.use_rustls_tls();
+    }
+    if let Some(proxy) = proxy {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let proxy = reqwest::Proxy::all(proxy).map_err(|err| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("{} is not a valid proxy url: {}", proxy, err),
+                )
+            })?;
+            builder = builder.proxy(proxy);
+        }
+        // requests from a browser go through the proxy the browser is configured with
+        #[cfg(target_arch = "wasm32")]
+        return Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!(
+                "proxies are not supported on wasm32, {} can't be used",
+                proxy
+            ),
+        )));
+    }
+    Ok(builder.build()?)
+}
+
+/// The client used when none is given, on the TLS backend picked by the crate features
+pub(crate) fn default_client() -> reqwest::Client {
+    build_client(None).expect("the TLS backend could not be initialized")
+}
+
+/// Wait for the given duration, on the tokio timer natively and on the browser timer in wasm
+pub(crate) async fn sleep(duration: std::time::Duration) {
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(duration).await;
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::sleep(duration).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::products::test_server::spawn_mock;
+
+    #[test]
+    fn test_callback_header() {
+        for (callback_type, url) in [
+            (
+                CallbackType::RequestToPay,
+                "https://callbacks.example.com/collection_request_to_pay/REQUEST_TO_PAY",
+            ),
+            (
+                CallbackType::DisbursementDepositV2,
+                "https://callbacks.example.com/disbursement_deposit_v2/DISBURSEMENT_DEPOSIT_V2",
+            ),
+            (
+                CallbackType::RemittanceCashTransfer,
+                "https://callbacks.example.com/remittance_cash_transfer/REMITTANCE_CASH_TRANSFER",
+            ),
+        ] {
+            let (name, value) = callback_header("https://callbacks.example.com/", callback_type);
+            assert_eq!(name, CALLBACK_URL_HEADER);
+            assert_eq!(value, url);
+        }
+    }
+
+    #[test]
+    fn test_with_callback_url() {
+        let client = reqwest::Client::new();
+        let req = with_callback_url(
+            client.post("http://localhost/"),
+            Some("https://callbacks.example.com"),
+        )
+        .build()
+        .unwrap();
+        assert_eq!(
+            req.headers().get(CALLBACK_URL_HEADER).unwrap(),
+            "https://callbacks.example.com"
+        );
+        for callback_url in [None, Some("")] {
+            let req = with_callback_url(client.post("http://localhost/"), callback_url)
+                .build()
+                .unwrap();
+            assert!(req.headers().get(CALLBACK_URL_HEADER).is_none());
+        }
+    }
+
+    #[poem::handler]
+    fn echo_user_agent(headers: &poem::http::HeaderMap) -> String {
+        headers
+            .get("User-Agent")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_user_agent() {
+        use poem::{get, Route};
+
+        let url = format!("{}/", spawn_mock(Route::new().at("/", get(echo_user_agent))));
+
+        let user_agent = default_client().get(&url).send().await.unwrap();
+        assert_eq!(user_agent.text().await.unwrap(), DEFAULT_USER_AGENT);
+        assert!(DEFAULT_USER_AGENT.starts_with("mtnmomo-rs/"));
+
+        let client = build_http_client(None, "my-shop/1.2").unwrap();
+        let user_agent = client.get(&url).send().await.unwrap();
+        assert_eq!(user_agent.text().await.unwrap(), "my-shop/1.2");
+    }
+}
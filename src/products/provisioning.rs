@@ -9,42 +9,161 @@
 //!
 //!
 
+use std::sync::Arc;
+
 use crate::{
+    errors::error::ProvisioningError, metrics::Metrics,
     requests::provisioning::ProvisioningRequest, responses::api_user_key::ApiUserKeyResult,
+    ApiCredentials, ReferenceId,
 };
 
+use super::SendWithMetrics;
+
 pub struct Provisioning {
     pub subscription_key: String,
     pub url: String,
+    client: reqwest::Client,
+    metrics: Option<Arc<dyn Metrics>>,
+    headers: reqwest::header::HeaderMap,
 }
 
 impl Provisioning {
     pub fn new(url: String, subscription_key: String) -> Self {
+        Provisioning::with_client(url, subscription_key, super::default_client())
+    }
+
+    /// Same as `new`, sending the requests with the given client
+    ///
+    /// # Parameters
+    ///
+    /// * 'url', MTN sandbox url
+    /// * 'subscription_key', the subscription key used for provisioning
+    /// * 'client', the HTTP client used to call MTN
+    pub fn with_client(url: String, subscription_key: String, client: reqwest::Client) -> Self {
         Provisioning {
             subscription_key,
             url,
+            client,
+            metrics: None,
+            headers: reqwest::header::HeaderMap::new(),
+        }
+    }
+
+    /// Tell the given hooks about every request sent to MTN, see `Metrics`
+    ///
+    /// # Parameters
+    ///
+    /// * 'metrics', the hooks
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Send these headers with every request to MTN, e.g. a partner id required by an operator,
+    /// they are added to the headers already set and replace the operation's headers of the same
+    /// name
+    ///
+    /// # Parameters
+    ///
+    /// * 'headers', the headers to add
+    pub fn with_default_headers(mut self, headers: reqwest::header::HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Create a sandbox api user and its api key, to keep and give to `Momo::new` on the next
+    /// runs rather than provisioning a new api user each time, see `ApiCredentials`
+    ///
+    /// # Parameters
+    ///
+    /// * 'provider_callback_host', the host name MTN sends the callbacks to, e.g.
+    ///   "callbacks.example.com"
+    ///
+    /// # Returns
+    ///
+    /// * 'ApiCredentials', the api user, a new UUID, and its api key
+    pub async fn provision(
+        &self,
+        provider_callback_host: &str,
+    ) -> Result<ApiCredentials, Box<dyn std::error::Error>> {
+        let reference_id: String = ReferenceId::new().into();
+        self.provision_with_reference(provider_callback_host, &reference_id)
+            .await
+    }
+
+    /// Same as `provision`, with the given reference id as api user, so that a provisioning
+    /// which failed partway can be retried
+    ///
+    /// An api user which already exists with this reference id, e.g. created by the failed
+    /// attempt, is reused and given a new api key.
+    ///
+    /// # Parameters
+    ///
+    /// * 'provider_callback_host', the host name MTN sends the callbacks to
+    /// * 'reference_id', the UUID of the api user, kept by the caller between the attempts
+    ///
+    /// # Returns
+    ///
+    /// * 'ApiCredentials', or a `ProvisioningError` when MTN refuses the callback host or the
+    ///   api key
+    pub async fn provision_with_reference(
+        &self,
+        provider_callback_host: &str,
+        reference_id: &str,
+    ) -> Result<ApiCredentials, Box<dyn std::error::Error>> {
+        if let Err(err) = self
+            .create_sandox(reference_id, provider_callback_host)
+            .await
+        {
+            match err.downcast_ref::<ProvisioningError>() {
+                Some(ProvisioningError::UserAlreadyExists { .. }) => {
+                    tracing::info!("the api user {} already exists, reusing it", reference_id);
+                }
+                _ => return Err(err),
+            }
         }
+        let api = self.create_api_information(reference_id).await?;
+        Ok(ApiCredentials {
+            api_user: reference_id.to_string(),
+            api_key: api.api_key,
+        })
     }
 
     /// Used to create an API user in the sandbox target environment
     ///
     /// # Parameters
     ///
-    /// * 'reference_id', reference identification number
-    /// * 'provider_callback_host',
+    /// * 'reference_id', reference identification number, a UUID
+    /// * 'provider_callback_host', the host name MTN sends the callbacks to, e.g.
+    ///   "callbacks.example.com", without scheme, port or path
     ///
     /// # Returns
     ///
-    /// * '()'
+    /// * '()', or a `ProvisioningError` when MTN refuses to create the api user, e.g.
+    ///   `UserAlreadyExists` when the reference id is already used, or `CallbackHostRejected`
+    ///   when MTN names the provider callback host in its refusal. `InvalidReferenceId` and
+    ///   `CallbackHostRejected` are also returned before anything is sent, when the reference id
+    ///   is not a UUID or the provider callback host is not a bare host name
     pub async fn create_sandox(
         &self,
         reference_id: &str,
         provider_callback_host: &str,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        if uuid::Uuid::parse_str(reference_id).is_err() {
+            return Err(Box::new(ProvisioningError::InvalidReferenceId {
+                reference_id: reference_id.to_string(),
+            }));
+        }
+        let client = &self.client;
         let provisioning = ProvisioningRequest {
             provider_callback_host: provider_callback_host.to_string(),
         };
+        if let Err(message) = provisioning.validate() {
+            return Err(Box::new(ProvisioningError::CallbackHostRejected {
+                provider_callback_host: provider_callback_host.to_string(),
+                message,
+            }));
+        }
 
         let res = client
             .post(format!("{}/v1_0/apiuser", self.url))
@@ -53,17 +172,36 @@ impl Provisioning {
             .header("Cache-Control", "no-cache")
             .header("Ocp-Apim-Subscription-Key", &self.subscription_key)
             .body(provisioning)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "create_sandox")
             .await?;
 
-        if res.status().is_success() {
+        let status = res.status();
+        if status.is_success() {
             return Ok(());
-        } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
         }
+        let message = res.text().await?;
+        let err = match status {
+            reqwest::StatusCode::CONFLICT => ProvisioningError::UserAlreadyExists {
+                reference_id: reference_id.to_string(),
+            },
+            // a 400 can be about any field, only one naming the host is about the host
+            reqwest::StatusCode::BAD_REQUEST
+                if message.contains(provider_callback_host)
+                    || message
+                        .to_ascii_lowercase()
+                        .contains("providercallbackhost") =>
+            {
+                ProvisioningError::CallbackHostRejected {
+                    provider_callback_host: provider_callback_host.to_string(),
+                    message,
+                }
+            }
+            _ => ProvisioningError::UserCreationFailed {
+                status: status.as_u16(),
+                message,
+            },
+        };
+        Err(Box::new(err))
     }
 
     /// Used to get API user information.
@@ -82,21 +220,18 @@ impl Provisioning {
         &self,
         reference_id: &str,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let res = client
             .get(format!("{}/v1_0/apiuser/{}", self.url, reference_id))
             .header("Cache-Control", "no-cache")
             .header("Ocp-Apim-Subscription-Key", &self.subscription_key)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "get_api_information")
             .await?;
 
         if res.status().is_success() {
-            return Ok(());
+            Ok(())
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -109,19 +244,20 @@ impl Provisioning {
     ///
     /// # Returns
     ///
-    /// * 'ApiUserKeyResult'
+    /// * 'ApiUserKeyResult', or a `ProvisioningError::ApiKeyCreationFailed` when MTN refuses to
+    ///   create the api key
     pub async fn create_api_information(
         &self,
         reference_id: &str,
     ) -> Result<ApiUserKeyResult, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let res = client
             .post(format!("{}/v1_0/apiuser/{}/apikey", self.url, reference_id))
             .header("Cache-Control", "no-cache")
             .header("Ocp-Apim-Subscription-Key", &self.subscription_key)
             .header("Content-Length", "0")
             .body("")
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "create_api_information")
             .await?;
 
         if res.status().is_success() {
@@ -129,22 +265,42 @@ impl Provisioning {
             let api_key: ApiUserKeyResult = serde_json::from_str(&response)?;
             Ok(api_key)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(ProvisioningError::ApiKeyCreationFailed {
+                reference_id: reference_id.to_string(),
+                status: res.status().as_u16(),
+                message: res.text().await?,
+            }))
         }
     }
 }
 
+impl std::fmt::Debug for Provisioning {
+    /// The keys are masked, so that a product can be logged
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Provisioning")
+            .field("url", &self.url)
+            .field("subscription_key", &crate::redaction::MASK)
+            .finish_non_exhaustive()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::products::test_server::spawn_mock;
     use dotenv::dotenv;
     use std::env;
     use uuid::Uuid;
 
+    const MOCK_REFERENCE_ID: &str = "0d7a3a3c-8a2d-4b7e-9f43-2b6c2f1d5e01";
+    // created by an earlier attempt
+    const EXISTING_REFERENCE_ID: &str = "5a1c9e27-3f6b-4d08-b2e4-7c8d9a0b1f02";
+
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_0() {
         dotenv().ok();
         let mtn_url = env::var("MTN_URL").expect("MTN_COLLECTION_URL must be set");
@@ -153,11 +309,158 @@ mod tests {
         let provisioning = Provisioning::new(mtn_url, subscription_key);
         let reference_id = Uuid::new_v4().to_string();
         let result = provisioning.create_sandox(&reference_id, "test").await;
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
         let resullt = provisioning.get_api_information(&reference_id).await;
-        assert_eq!(resullt.is_ok(), true);
+        assert!(resullt.is_ok());
         let result = provisioning.create_api_information(&reference_id).await;
         let api_key = result.unwrap();
-        assert_eq!(api_key.clone().api_key.len() > 0, true);
+        assert!(!api_key.api_key.is_empty());
+    }
+
+    #[poem::handler]
+    fn mock_create_api_user(
+        req: &poem::Request,
+        poem::web::Json(body): poem::web::Json<serde_json::Value>,
+    ) -> poem::Response {
+        if req.header("X-Reference-Id") == Some(EXISTING_REFERENCE_ID) {
+            return poem::Response::builder()
+                .status(poem::http::StatusCode::CONFLICT)
+                .body(r#"{"code":"RESOURCE_ALREADY_EXIST"}"#);
+        }
+        if req.header("Ocp-Apim-Subscription-Key") != Some("mock_subscription_key") {
+            return poem::Response::builder()
+                .status(poem::http::StatusCode::BAD_REQUEST)
+                .body(r#"{"message":"the subscription key is not valid"}"#);
+        }
+        if body["providerCallbackHost"] != "callbacks.example.com" {
+            return poem::Response::builder()
+                .status(poem::http::StatusCode::BAD_REQUEST)
+                .body(format!(
+                    r#"{{"message":"{} is not an allowed providerCallbackHost"}}"#,
+                    body["providerCallbackHost"].as_str().unwrap_or_default()
+                ));
+        }
+        poem::Response::builder()
+            .status(poem::http::StatusCode::CREATED)
+            .finish()
+    }
+
+    #[poem::handler]
+    fn mock_create_api_key(
+        poem::web::Path(reference_id): poem::web::Path<String>,
+    ) -> poem::Response {
+        if Uuid::parse_str(&reference_id).is_err() {
+            return poem::Response::builder()
+                .status(poem::http::StatusCode::NOT_FOUND)
+                .finish();
+        }
+        poem::Response::builder()
+            .status(poem::http::StatusCode::CREATED)
+            .content_type("application/json")
+            .body(r#"{"apiKey":"mock_api_key"}"#)
+    }
+
+    #[tokio::test]
+    async fn test_provisioning_requests() {
+        use poem::{post, Route};
+
+        let url = spawn_mock(
+            Route::new()
+                .at("/v1_0/apiuser", post(mock_create_api_user))
+                .at(
+                    "/v1_0/apiuser/:reference_id/apikey",
+                    post(mock_create_api_key),
+                ),
+        );
+
+        let provisioning = Provisioning::new(url, "mock_subscription_key".to_string());
+        provisioning
+            .create_sandox(MOCK_REFERENCE_ID, "callbacks.example.com")
+            .await
+            .unwrap();
+        let api_key = provisioning
+            .create_api_information(MOCK_REFERENCE_ID)
+            .await
+            .unwrap();
+        assert_eq!(api_key.api_key, "mock_api_key");
+        let err = provisioning
+            .create_api_information("other_reference_id")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProvisioningError>(),
+            Some(ProvisioningError::ApiKeyCreationFailed { status: 404, .. })
+        ));
+
+        let err = provisioning
+            .create_sandox(EXISTING_REFERENCE_ID, "callbacks.example.com")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ProvisioningError>(),
+            Some(&ProvisioningError::UserAlreadyExists {
+                reference_id: EXISTING_REFERENCE_ID.to_string()
+            })
+        );
+        let err = provisioning
+            .create_sandox(MOCK_REFERENCE_ID, "refused.example.com")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProvisioningError>(),
+            Some(ProvisioningError::CallbackHostRejected { provider_callback_host, .. })
+                if provider_callback_host == "refused.example.com"
+        ));
+        // a 400 about something else than the host
+        let err = Provisioning::new(provisioning.url.clone(), "other_key".to_string())
+            .create_sandox(MOCK_REFERENCE_ID, "callbacks.example.com")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProvisioningError>(),
+            Some(ProvisioningError::UserCreationFailed { status: 400, .. })
+        ));
+        // not sent to MTN
+        let err = provisioning
+            .create_sandox("mock_reference_id", "callbacks.example.com")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ProvisioningError>(),
+            Some(&ProvisioningError::InvalidReferenceId {
+                reference_id: "mock_reference_id".to_string()
+            })
+        );
+        let err = provisioning
+            .create_sandox(MOCK_REFERENCE_ID, "http://foo/bar")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProvisioningError>(),
+            Some(ProvisioningError::CallbackHostRejected { message, .. }) if message.contains("scheme")
+        ));
+
+        // a retry with the reference id of the earlier attempt reuses its api user
+        let momo = crate::Momo::new_with_provisioning_reference(
+            provisioning.url.clone(),
+            "mock_subscription_key".to_string(),
+            "callbacks.example.com",
+            EXISTING_REFERENCE_ID,
+        )
+        .await
+        .unwrap();
+        assert_eq!(momo.api_user, EXISTING_REFERENCE_ID);
+        assert_eq!(momo.api_key, "mock_api_key");
+
+        let credentials = provisioning
+            .provision("callbacks.example.com")
+            .await
+            .unwrap();
+        assert!(Uuid::parse_str(&credentials.api_user).is_ok());
+        assert_eq!(credentials.api_key, "mock_api_key");
+        assert!(!format!("{:?}", credentials).contains("mock_api_key"));
+        let kept: ApiCredentials =
+            serde_json::from_str(&serde_json::to_string(&credentials).unwrap()).unwrap();
+        assert_eq!(kept, credentials);
     }
 }
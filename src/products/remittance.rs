@@ -11,10 +11,12 @@
 
 use std::sync::Arc;
 
+use super::SendWithMetrics;
 use crate::{
-    BCAuthorizeResponse, Balance, BasicUserInfoJsonResponse, CashTransferRequest,
-    CashTransferResult, Currency, Environment, OAuth2TokenResponse, TokenResponse, TranserId,
-    TransferRequest, TransferResult,
+    metrics::Metrics, AccessType, BCAuthorizeResponse, Balance, BasicUserInfoJsonResponse,
+    CashTransferRequest, CashTransferResult, Currency, Environment, MsisdnPolicy,
+    OAuth2TokenResponse, PartyIdType, Submitted, TokenResponse, TranserId, TransferRequest,
+    TransferResult,
 };
 use chrono::Utc;
 use once_cell::sync::Lazy;
@@ -22,6 +24,7 @@ use tokio::sync::Mutex;
 
 use super::account::Account;
 
+#[derive(Clone)]
 pub struct Remittance {
     pub url: String,
     pub primary_key: String,
@@ -30,6 +33,10 @@ pub struct Remittance {
     pub api_user: String,
     pub api_key: String,
     account: Account,
+    client: reqwest::Client,
+    metrics: Option<Arc<dyn Metrics>>,
+    headers: reqwest::header::HeaderMap,
+    msisdn_policy: MsisdnPolicy,
 }
 
 static ACCESS_TOKEN: Lazy<Arc<Mutex<Option<TokenResponse>>>> =
@@ -59,7 +66,77 @@ impl Remittance {
         primary_key: String,
         secondary_key: String,
     ) -> Remittance {
-        let account = Account {};
+        Remittance::with_client(
+            url,
+            environment,
+            api_user,
+            api_key,
+            primary_key,
+            secondary_key,
+            super::default_client(),
+        )
+    }
+
+    /// Create a new instance of Remittance, optionally sending its requests through a proxy
+    ///
+    /// # Parameters
+    ///
+    /// * 'url', MTN MOMO remittance url
+    /// * 'environment', environement to be used
+    /// * 'api_user', the api user to be used
+    /// * 'api_key', the api key to be use
+    /// * 'primary_key', the primary key of the remittance product
+    /// * 'secondary_key', the secondary key of the remittance product
+    /// * 'proxy', url of the outbound proxy, None to connect directly
+    ///
+    /// # Returns
+    /// * Remittance, or an error when the proxy url is not valid
+    pub fn with_proxy(
+        url: String,
+        environment: Environment,
+        api_user: String,
+        api_key: String,
+        primary_key: String,
+        secondary_key: String,
+        proxy: Option<String>,
+    ) -> Result<Remittance, Box<dyn std::error::Error>> {
+        let client = super::build_client(proxy.as_deref())?;
+        Ok(Remittance::with_client(
+            url,
+            environment,
+            api_user,
+            api_key,
+            primary_key,
+            secondary_key,
+            client,
+        ))
+    }
+
+    /// Create a new instance of Remittance sending its requests with the given client,
+    /// for example one going through a proxy or adding headers expected by an API gateway
+    ///
+    /// # Parameters
+    ///
+    /// * 'url', MTN MOMO remittance url
+    /// * 'environment', environement to be used
+    /// * 'api_user', the api user to be used
+    /// * 'api_key', the api key to be use
+    /// * 'primary_key', the primary key of the remittance product
+    /// * 'secondary_key', the secondary key of the remittance product
+    /// * 'client', the HTTP client used for every call to MTN
+    ///
+    /// # Returns
+    /// * Remittance
+    pub fn with_client(
+        url: String,
+        environment: Environment,
+        api_user: String,
+        api_key: String,
+        primary_key: String,
+        secondary_key: String,
+        client: reqwest::Client,
+    ) -> Remittance {
+        let account = Account::with_client(client.clone());
         Remittance {
             url,
             primary_key,
@@ -68,17 +145,91 @@ impl Remittance {
             api_user,
             api_key,
             account,
+            client,
+            metrics: None,
+            headers: reqwest::header::HeaderMap::new(),
+            msisdn_policy: MsisdnPolicy::default(),
+        }
+    }
+
+    /// Format the mobile numbers sent to MTN according to the given policy, default = KeepPlus
+    ///
+    /// # Parameters
+    ///
+    /// * 'policy', how the mobile numbers are formatted
+    ///
+    /// # Returns
+    /// * Remittance
+    pub fn with_msisdn_policy(mut self, policy: MsisdnPolicy) -> Remittance {
+        self.msisdn_policy = policy;
+        self
+    }
+
+    /// How the mobile numbers sent to MTN are formatted
+    pub fn msisdn_policy(&self) -> MsisdnPolicy {
+        self.msisdn_policy
+    }
+
+    /// Tell the given hooks about every request sent to MTN and how long it took, see `Metrics`
+    ///
+    /// # Parameters
+    ///
+    /// * 'metrics', the hooks
+    ///
+    /// # Returns
+    /// * Remittance
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Remittance {
+        self.account = self.account.with_metrics(metrics.clone());
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Send these headers with every request to MTN, e.g. a partner id required by an operator
+    /// or the X-Forwarded-* headers expected by an intermediary
+    ///
+    /// They are added to the headers already set and replace the operation's headers of the same
+    /// name. For the headers of a single call, set them on a clone of the product,
+    /// `remittance.clone().with_default_headers(headers).transfer(transfer)`.
+    ///
+    /// # Parameters
+    ///
+    /// * 'headers', the headers to add
+    ///
+    /// # Returns
+    /// * Remittance
+    pub fn with_default_headers(mut self, headers: reqwest::header::HeaderMap) -> Remittance {
+        self.account = self.account.with_default_headers(headers.clone());
+        self.headers.extend(headers);
+        self
+    }
+
+    /// The authorization helper, sending its requests with the client, headers and hooks of the
+    /// product
+    fn authorization(&self) -> crate::products::auth::Authorization {
+        let auth = crate::products::auth::Authorization::with_client(self.client.clone())
+            .with_default_headers(self.headers.clone());
+        match &self.metrics {
+            Some(metrics) => auth.with_metrics(metrics.clone()),
+            None => auth,
         }
     }
 
     /// This operation is used to create an access token
     ///
+    /// Normal flows don't need it, every operation requests and caches a token on its own.
+    /// It is meant for calling MTN endpoints this crate does not wrap; the new token is cached
+    /// and used by the next operations.
+    ///
     /// # Returns
     ///
     /// * 'TokenResponse'
-    async fn create_access_token(&self) -> Result<TokenResponse, Box<dyn std::error::Error>> {
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "remittance", operation = "create_access_token")
+    )]
+    pub async fn create_access_token(&self) -> Result<TokenResponse, Box<dyn std::error::Error>> {
         let url = format!("{}/{}", self.url, "remittance");
-        let auth = crate::products::auth::Authorization {};
+        let auth = self.authorization();
         let token = auth
             .create_access_token(
                 url,
@@ -107,12 +258,12 @@ impl Remittance {
         auth_req_id: String,
     ) -> Result<OAuth2TokenResponse, Box<dyn std::error::Error>> {
         let url = format!("{}/{}", self.url, "remittance");
-        let auth = crate::products::auth::Authorization {};
+        let auth = self.authorization();
         auth.create_o_auth_2_token(
             url,
             self.api_user.clone(),
             self.api_key.clone(),
-            self.environment.clone(),
+            self.environment,
             self.primary_key.clone(),
             auth_req_id,
         )
@@ -125,26 +276,33 @@ impl Remittance {
     ///
     /// * 'msisdn', this is the phone number of the user
     /// * 'callback_url', this is the url that will be used to notify the client of the status of the transaction
+    /// * 'scope', the scope requested from the user, e.g. "profile" or "openid", default = profile
+    /// * 'access_type', the access type requested, default = offline
     ///
     /// # Returns
     ///
     /// * 'BCAuthorizeResponse'
-    #[allow(dead_code)]
-    async fn bc_authorize(
+    #[tracing::instrument(skip_all, fields(product = "remittance", operation = "bc_authorize"))]
+    pub async fn bc_authorize(
         &self,
         msisdn: String,
         callback_url: Option<&str>,
+        scope: Option<&str>,
+        access_type: Option<AccessType>,
     ) -> Result<BCAuthorizeResponse, Box<dyn std::error::Error>> {
+        let msisdn = self.msisdn_policy.normalize(&msisdn);
         let url = format!("{}/{}", self.url, "remittance");
-        let auth = crate::products::auth::Authorization {};
+        let auth = self.authorization();
         let access_token: TokenResponse = self.create_access_token().await?;
         auth.bc_authorize(
             url,
-            self.environment.clone(),
+            self.environment,
             self.primary_key.clone(),
             msisdn,
             callback_url,
             access_token,
+            scope,
+            access_type,
         )
         .await
     }
@@ -153,24 +311,24 @@ impl Remittance {
     ///
     /// # Returns
     /// * 'TokenResponse'
-    async fn get_valid_access_token(&self) -> Result<TokenResponse, Box<dyn std::error::Error>> {
-        let token = ACCESS_TOKEN.lock().await;
-        if token.is_some() {
-            let token = token.clone().unwrap();
-            if token.created_at.is_some() {
-                let created_at = token.created_at.unwrap();
+    pub(crate) async fn get_valid_access_token(
+        &self,
+    ) -> Result<TokenResponse, Box<dyn std::error::Error>> {
+        let token = ACCESS_TOKEN.lock().await.clone();
+        if let Some(token) = token {
+            if let Some(created_at) = token.created_at {
                 let expires_in = token.expires_in;
                 let now = Utc::now();
                 let duration = now.signed_duration_since(created_at);
                 if duration.num_seconds() < expires_in as i64 {
+                    tracing::trace!("using the cached access token");
                     return Ok(token);
                 }
-                let token: TokenResponse = self.create_access_token().await?;
-                return Ok(token);
             }
         }
+        tracing::debug!("requesting a new access token");
         let token: TokenResponse = self.create_access_token().await?;
-        return Ok(token);
+        Ok(token)
     }
 
     /// Cash transfer operation is used to transfer an amount from the owner’s account to a payee account.
@@ -183,12 +341,15 @@ impl Remittance {
     /// # Returns
     ///
     /// * ()
+    #[tracing::instrument(skip_all, fields(product = "remittance", operation = "cash_transfer", external_id = %transfer.external_id))]
     pub async fn cash_transfer(
         &self,
-        transfer: CashTransferRequest,
+        mut transfer: CashTransferRequest,
         callback_url: Option<&str>,
     ) -> Result<String, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        transfer.validate()?;
+        transfer.payee = transfer.payee.normalized(self.msisdn_policy);
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let mut req = client
             .post(format!("{}/remittance/v2_0/cashtransfer", self.url))
@@ -199,21 +360,16 @@ impl Remittance {
             .header("Content-Type", "application/json")
             .body(transfer.clone());
 
-        if let Some(callback_url) = callback_url {
-            if !callback_url.is_empty() {
-                req = req.header("X-Callback-Url", callback_url);
-            }
-        }
+        req = super::with_callback_url(req, callback_url);
 
-        let res = req.send().await?;
+        let res = req
+            .send_with(self.metrics.as_deref(), &self.headers, "cash_transfer")
+            .await?;
 
         if res.status().is_success() {
             Ok(transfer.external_id)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -226,11 +382,12 @@ impl Remittance {
     /// # Returns
     ///
     /// * 'CashTransferResult'
+    #[tracing::instrument(skip_all, fields(product = "remittance", operation = "get_cash_transfer_status", external_id = %transfer_id))]
     pub async fn get_cash_transfer_status(
         &self,
         transfer_id: &str,
     ) -> Result<CashTransferResult, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let res = client
             .get(format!(
@@ -240,7 +397,8 @@ impl Remittance {
             .bearer_auth(access_token.access_token)
             .header("X-Target-Environment", self.environment.to_string())
             .header("Cache-Control", "no-cache")
-            .send()
+            .header("Ocp-Apim-Subscription-Key", &self.primary_key)
+            .send_with(self.metrics.as_deref(), &self.headers, "get_cash_transfer_status")
             .await?;
 
         if res.status().is_success() {
@@ -248,10 +406,7 @@ impl Remittance {
             let cash_transfer_result: CashTransferResult = serde_json::from_str(&body)?;
             Ok(cash_transfer_result)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -265,12 +420,15 @@ impl Remittance {
     ///
     /// # Returns
     ///
-    /// * 'TransferId', the transfer id (MTN Momo external id)
+    /// * 'Submitted<TranserId>', the transfer id (MTN Momo external id)
+    #[tracing::instrument(skip_all, fields(product = "remittance", operation = "transfer", external_id = %transfer.external_id))]
     pub async fn transfer(
         &self,
-        transfer: TransferRequest,
-    ) -> Result<TranserId, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        mut transfer: TransferRequest,
+    ) -> Result<Submitted<TranserId>, Box<dyn std::error::Error>> {
+        transfer.validate()?;
+        transfer.payee = transfer.payee.normalized(self.msisdn_policy);
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let res = client
             .post(format!("{}/remittance/v1_0/transfer", self.url))
@@ -280,16 +438,16 @@ impl Remittance {
             .header("Cache-Control", "no-cache")
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
             .body(transfer.clone())
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "transfer")
             .await?;
 
         if res.status().is_success() {
-            Ok(TranserId(transfer.external_id))
+            Ok(Submitted::new(
+                TranserId(transfer.external_id),
+                res.status(),
+            ))
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -303,11 +461,12 @@ impl Remittance {
     /// # Returns
     ///
     /// * 'TransferResult'
+    #[tracing::instrument(skip_all, fields(product = "remittance", operation = "get_transfer_status", external_id = %transfer_id))]
     pub async fn get_transfer_status(
         &self,
         transfer_id: &str,
     ) -> Result<TransferResult, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let access_token = self.get_valid_access_token().await?;
         let res = client
             .get(format!(
@@ -318,7 +477,7 @@ impl Remittance {
             .header("X-Target-Environment", self.environment.to_string())
             .header("Cache-Control", "no-cache")
             .header("Ocp-Apim-Subscription-Key", &self.primary_key)
-            .send()
+            .send_with(self.metrics.as_deref(), &self.headers, "get_transfer_status")
             .await?;
 
         if res.status().is_success() {
@@ -326,10 +485,7 @@ impl Remittance {
             let transfer_result: TransferResult = serde_json::from_str(&body)?;
             Ok(transfer_result)
         } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                res.text().await?,
-            )))
+            Err(Box::new(std::io::Error::other(res.text().await?)))
         }
     }
 
@@ -337,6 +493,10 @@ impl Remittance {
     /// # Returns
     ///
     /// * 'Balance', the balance
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "remittance", operation = "get_account_balance")
+    )]
     pub async fn get_account_balance(&self) -> Result<Balance, Box<dyn std::error::Error>> {
         let url = format!("{}/remittance", self.url);
         let access_token = self.get_valid_access_token().await?;
@@ -358,6 +518,13 @@ impl Remittance {
     /// # Returns
     ///
     /// * 'Balance', the balance
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            product = "remittance",
+            operation = "get_account_balance_in_specific_currency"
+        )
+    )]
     pub async fn get_account_balance_in_specific_currency(
         &self,
         currency: Currency,
@@ -383,18 +550,50 @@ impl Remittance {
     /// # Returns
     ///
     /// * 'BasicUserInfoJsonResponse'
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "remittance", operation = "get_basic_user_info")
+    )]
     pub async fn get_basic_user_info(
         &self,
         account_holder_msisdn: &str,
     ) -> Result<BasicUserInfoJsonResponse, Box<dyn std::error::Error>> {
+        self.get_basic_user_info_by_id(PartyIdType::MSISDN, account_holder_msisdn)
+            .await
+    }
+
+    /// This operation is used to get the basic information of the account holder, identified
+    /// by any kind of account holder id
+    ///
+    /// # Parameters
+    /// * 'account_holder_id_type', the kind of id, e.g. EMAIL
+    /// * 'account_holder_id', the MSISDN, e-mail or party code of the account holder
+    ///
+    /// # Returns
+    ///
+    /// * 'BasicUserInfoJsonResponse'
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "remittance", operation = "get_basic_user_info")
+    )]
+    pub async fn get_basic_user_info_by_id(
+        &self,
+        account_holder_id_type: PartyIdType,
+        account_holder_id: &str,
+    ) -> Result<BasicUserInfoJsonResponse, Box<dyn std::error::Error>> {
+        let account_holder_id = match account_holder_id_type {
+            PartyIdType::MSISDN => self.msisdn_policy.normalize(account_holder_id),
+            _ => account_holder_id.to_string(),
+        };
         let url = format!("{}/remittance", self.url);
         let access_token = self.get_valid_access_token().await?;
         self.account
-            .get_basic_user_info(
+            .get_basic_user_info_by_id(
                 url,
                 self.environment,
                 self.primary_key.clone(),
-                account_holder_msisdn,
+                account_holder_id_type,
+                &account_holder_id,
                 access_token,
             )
             .await
@@ -409,6 +608,10 @@ impl Remittance {
     /// # Returns
     ///
     /// * 'BasicUserInfoJsonResponse'
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "remittance", operation = "get_user_info_with_consent")
+    )]
     pub async fn get_user_info_with_consent(
         &self,
         access_token: String,
@@ -429,13 +632,55 @@ impl Remittance {
     /// # Parameters
     ///
     /// * 'account_holder_id', The MSISDN or email of the account holder
-    /// * 'account_holder_type', The type of the account holder.
+    /// * 'account_holder_type', The type of the account holder, MSISDN, EMAIL or PARTY_CODE
     ///
     ///
     /// # Returns
     ///
     /// * ()
+    #[tracing::instrument(
+        skip_all,
+        fields(product = "remittance", operation = "validate_account_holder_status")
+    )]
     pub async fn validate_account_holder_status(
+        &self,
+        account_holder_id: &str,
+        account_holder_type: PartyIdType,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let url = format!("{}/remittance", self.url);
+        let access_token = self.get_valid_access_token().await?;
+        self.account
+            .validate_account_holder_status(
+                url,
+                self.environment,
+                self.primary_key.clone(),
+                account_holder_id,
+                account_holder_type.account_holder_id_type(),
+                access_token,
+            )
+            .await
+    }
+
+    /// this operation is used to validate the status of an account holder, the type is sent as is
+    ///
+    /// # Parameters
+    ///
+    /// * 'account_holder_id', The MSISDN or email of the account holder
+    /// * 'account_holder_type', The type of the account holder, e.g. "msisdn"
+    ///
+    ///
+    /// # Returns
+    ///
+    /// * ()
+    #[deprecated(note = "use validate_account_holder_status with a PartyIdType")]
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            product = "remittance",
+            operation = "validate_account_holder_status_str"
+        )
+    )]
+    pub async fn validate_account_holder_status_str(
         &self,
         account_holder_id: &str,
         account_holder_type: &str,
@@ -455,13 +700,70 @@ impl Remittance {
     }
 }
 
+impl std::fmt::Debug for Remittance {
+    /// The keys are masked, so that a product can be logged
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Remittance")
+            .field("url", &self.url)
+            .field("environment", &self.environment)
+            .field("api_user", &self.api_user)
+            .field("api_key", &crate::redaction::MASK)
+            .field("primary_key", &crate::redaction::MASK)
+            .field("secondary_key", &crate::redaction::MASK)
+            .finish_non_exhaustive()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::products::test_server::{
+        balance_routes, mock_requires_headers, mock_transfer, mock_transfer_status, remittance_at,
+        short_lived_token_routes, spawn_mock, token_routes,
+    };
     use dotenv::dotenv;
     use std::env;
 
-    use crate::{MomoRemittance, Party, PartyIdType};
+    use crate::{MomoRemittance, Party};
+    use poem::{get, handler, http::StatusCode, post, Route};
+
+    #[handler]
+    fn mock_cash_transfer_status(req: &poem::Request) -> poem::Response {
+        if req.header("Ocp-Apim-Subscription-Key") != Some("mock_primary_key") {
+            return poem::Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .finish();
+        }
+        poem::Response::builder().status(StatusCode::OK).body(
+            r#"{
+                "financialTransactionId": "363440463", "status": "SUCCESSFUL", "reason": "",
+                "amount": "1000", "currency": "EUR",
+                "payee": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+                "externalId": "transfer_id", "originatingCountry": "UG",
+                "originalAmount": "1000", "originalCurrency": "EUR",
+                "payerMessage": "payer_message", "payeeNote": "payee_note",
+                "payerIdentificationType": "PASS", "payerIdentificationNumber": "256774290781",
+                "payerIdentity": "256774290781", "payerFirstName": "John", "payerSurname": "Doe",
+                "payerLanguageCode": "en", "payerEmail": "test@email.com",
+                "payerMsisdn": "256774290781", "payerGender": "M"
+            }"#,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_get_cash_transfer_status_sends_subscription_key() {
+        let url = spawn_mock(token_routes().at(
+            "/remittance/v2_0/cashtransfer/:transfer_id",
+            get(mock_cash_transfer_status),
+        ));
+
+        let remittance = remittance_at(url);
+        let status = remittance
+            .get_cash_transfer_status("transfer_id")
+            .await
+            .expect("Error getting cash transfer status");
+        assert_eq!(status.external_id, "transfer_id");
+    }
 
     // #[tokio::test]
     // async fn test_cash_transfer() {
@@ -497,6 +799,10 @@ mod tests {
     // }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_transfer() {
         dotenv().ok();
         let url = env::var("MTN_URL").expect("MTN_URL not set");
@@ -531,6 +837,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_transfer_status() {
         dotenv().ok();
         let url = env::var("MTN_URL").expect("MTN_URL not set");
@@ -569,6 +879,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_basic_user_info() {
         dotenv().ok();
         let url = env::var("MTN_URL").expect("MTN_URL not set");
@@ -594,6 +908,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_validate_account_holder_status() {
         dotenv().ok();
         let url = env::var("MTN_URL").expect("MTN_URL not set");
@@ -612,12 +930,16 @@ mod tests {
             secondary_key,
         );
         let holder_status_result = remittance
-            .validate_account_holder_status("256774290781", "msisdn")
+            .validate_account_holder_status("256774290781", PartyIdType::MSISDN)
             .await;
         assert!(holder_status_result.is_ok());
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_account_balance() {
         dotenv().ok();
         let url = env::var("MTN_URL").expect("MTN_URL not set");
@@ -636,27 +958,31 @@ mod tests {
             secondary_key,
         );
         let balance_result = remittance.get_account_balance().await;
-        if balance_result.is_ok() {
-            assert!(balance_result.is_ok());
-            assert_eq!(balance_result.unwrap().currency, Currency::EUR);
+        if let Ok(balance) = balance_result {
+            assert_eq!(balance.currency, Currency::EUR);
         }
     }
 
-    // #[tokio::test]
-    // async fn test_get_account_balance_in_specific_currency() {
-    //     dotenv().ok();
-    //     let url = env::var("MTN_URL").expect("MTN_URL not set");
-    //     let primary_key = env::var("MTN_REMITTANCE_PRIMARY_KEY").expect("MTN_REMITTANCE_PRIMARY_KEY not set");
-    //     let secondary_key = env::var("MTN_REMITTANCE_SECONDARY_KEY").expect("MTN_REMITTANCE_SECONDARY_KEY not set");
-    //     let api_user = env::var("MTN_API_USER").expect("MTN_API_USER not set");
-    //     let api_key = env::var("MTN_API_KEY").expect("MTN_API_KEY not set");
-    //     let remittance = Remittance::new(url, Environment::Sandbox, api_user, api_key, primary_key, secondary_key);
-    //     let balance: Balance = remittance.get_account_balance_in_specific_currency(Currency::EUR).await.unwrap();
-    //     println!("{:?}", balance);
-    //     // todo()
-    // }
+    #[tokio::test]
+    async fn test_get_account_balance_in_specific_currency() {
+        let remittance = remittance_at(spawn_mock(balance_routes()));
+        let balance = remittance
+            .get_account_balance_in_specific_currency(Currency::EUR)
+            .await
+            .unwrap();
+        assert_eq!(balance.currency, Currency::EUR);
+        assert_eq!(balance.available_balance, "1000");
+        assert!(remittance
+            .get_account_balance_in_specific_currency(Currency::GHS)
+            .await
+            .is_err());
+    }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_bc_authorize() {
         dotenv().ok();
         let url = env::var("MTN_URL").expect("MTN_URL not set");
@@ -676,12 +1002,18 @@ mod tests {
             primary_key,
             secondary_key,
         );
-        let bc_authorize_result = remittance.bc_authorize("563607".to_string(), None).await;
+        let bc_authorize_result = remittance
+            .bc_authorize("563607".to_string(), None, None, None)
+            .await;
         assert!(bc_authorize_result.is_ok());
         assert_ne!(bc_authorize_result.unwrap().auth_req_id.len(), 0);
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_create_o_auth_2_token() {
         dotenv().ok();
         let url = env::var("MTN_URL").expect("MTN_URL not set");
@@ -701,7 +1033,9 @@ mod tests {
             primary_key,
             secondary_key,
         );
-        let bc_authorize_result = remittance.bc_authorize("563607".to_string(), None).await;
+        let bc_authorize_result = remittance
+            .bc_authorize("563607".to_string(), None, None, None)
+            .await;
         assert!(bc_authorize_result.is_ok());
         let auth_req_id = bc_authorize_result.unwrap().auth_req_id;
         let res = remittance.create_o_auth_2_token(auth_req_id).await;
@@ -710,6 +1044,10 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(
+        not(feature = "integration-tests"),
+        ignore = "calls the MTN sandbox, run with --features integration-tests"
+    )]
     async fn test_get_user_info_with_consent() {
         dotenv().ok();
         let url = env::var("MTN_URL").expect("MTN_URL not set");
@@ -727,7 +1065,9 @@ mod tests {
             primary_key,
             secondary_key,
         );
-        let bc_authorize_result = remittance.bc_authorize("563607".to_string(), None).await;
+        let bc_authorize_result = remittance
+            .bc_authorize("563607".to_string(), None, None, None)
+            .await;
         assert!(bc_authorize_result.is_ok());
         let auth_req_id = bc_authorize_result.unwrap().auth_req_id;
         let res = remittance.create_o_auth_2_token(auth_req_id).await;
@@ -738,4 +1078,48 @@ mod tests {
             .unwrap();
         assert_ne!(user_info_with_consent.family_name.len(), 0);
     }
+
+    fn mock_remittance(app: Route) -> Remittance {
+        remittance_at(spawn_mock(short_lived_token_routes().nest("/", app)))
+    }
+
+    #[tokio::test]
+    async fn test_transfer_sends_headers() {
+        let remittance = mock_remittance(
+            Route::new().at("/remittance/v1_0/transfer", post(mock_requires_headers)),
+        );
+        let transfer = mock_transfer();
+        let transfer_id = remittance.transfer(transfer.clone()).await.unwrap();
+        assert_eq!(transfer_id.as_string(), transfer.external_id);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_rejects_invalid_amount() {
+        // refused before anything is sent, there is no server
+        let remittance = Remittance::new(
+            "http://127.0.0.1:1".to_string(),
+            Environment::Sandbox,
+            "mock_api_user".to_string(),
+            "mock_api_key".to_string(),
+            "mock_primary_key".to_string(),
+            "mock_secondary_key".to_string(),
+        );
+        let mut transfer = mock_transfer();
+        transfer.amount = "1,000".to_string();
+        assert!(remittance.transfer(transfer).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_transfer_status_path() {
+        let remittance = mock_remittance(Route::new().at(
+            "/remittance/v1_0/transfer/:transfer_id",
+            get(mock_transfer_status),
+        ));
+        let status = remittance.get_transfer_status("transfer_id").await.unwrap();
+        assert_eq!(status.external_id, "transfer_id");
+        assert_eq!(
+            status.financial_transaction_id.as_deref(),
+            Some("363440463")
+        );
+    }
 }
@@ -0,0 +1,150 @@
+//! The local stand-in for the MTN API shared by the tests of the products
+//!
+//! A test builds the routes it needs, usually on top of `token_routes`, serves them with
+//! `spawn_mock` and points a product at the returned url.
+
+use poem::{
+    get, handler, listener::TcpAcceptor, post, web::Path, IntoEndpoint, Response, Route, Server,
+};
+
+use crate::{
+    products::{collection::Collection, disbursements::Disbursements, remittance::Remittance},
+    Currency, Environment, Party, TransferRequest,
+};
+
+#[handler]
+fn mock_access_token() -> &'static str {
+    r#"{"access_token":"mock_access_token","token_type":"access_token","expires_in":3600}"#
+}
+
+/// The token endpoint of every product, to add the endpoints under test to
+pub(crate) fn token_routes() -> Route {
+    Route::new().at("/:product/token/", post(mock_access_token))
+}
+
+/// expires at once, so that the process wide token cache of each product stays cold for the
+/// sandbox tests
+#[handler]
+fn mock_short_lived_token() -> &'static str {
+    r#"{"access_token":"mock_access_token","token_type":"access_token","expires_in":0}"#
+}
+
+/// Same as `token_routes`, with tokens that expire at once
+pub(crate) fn short_lived_token_routes() -> Route {
+    Route::new().at("/:product/token/", post(mock_short_lived_token))
+}
+
+/// Accepts a request only when it carries the headers MTN requires
+#[handler]
+pub(crate) fn mock_requires_headers(req: &poem::Request) -> Response {
+    let accepted = req.header("Authorization") == Some("Bearer mock_access_token")
+        && req.header("X-Target-Environment") == Some("sandbox")
+        && req.header("Ocp-Apim-Subscription-Key") == Some("mock_primary_key")
+        && req
+            .header("X-Reference-Id")
+            .is_some_and(|reference_id| uuid::Uuid::parse_str(reference_id).is_ok());
+    let status = if accepted {
+        poem::http::StatusCode::ACCEPTED
+    } else {
+        poem::http::StatusCode::BAD_REQUEST
+    };
+    Response::builder().status(status).finish()
+}
+
+/// A successful transfer, with the id of the path as external id
+#[handler]
+pub(crate) fn mock_transfer_status(Path(transfer_id): Path<String>) -> Response {
+    Response::builder()
+        .content_type("application/json")
+        .body(format!(
+            r#"{{"amount": "100", "currency": "EUR", "financialTransactionId": "363440463",
+            "externalId": "{}", "payee": {{"partyIdType": "MSISDN", "partyId": "256774290781"}},
+            "payerMessage": "payer_message", "payeeNote": "payee_note", "status": "SUCCESSFUL"}}"#,
+            transfer_id
+        ))
+}
+
+/// A transfer of 100 EUR, as the mocks of the transfer endpoints expect it
+pub(crate) fn mock_transfer() -> TransferRequest {
+    TransferRequest::new(
+        "100".to_string(),
+        Currency::EUR,
+        Party::msisdn("256774290781"),
+        "payer_message".to_string(),
+        "payee_note".to_string(),
+    )
+}
+
+#[handler]
+fn mock_balance_in_currency(Path((_, currency)): Path<(String, String)>) -> Response {
+    if currency != "EUR" && currency != "XAF" {
+        return Response::builder()
+            .status(poem::http::StatusCode::NOT_FOUND)
+            .body(currency);
+    }
+    Response::builder().body(format!(
+        r#"{{"availableBalance":"1000","currency":"{}"}}"#,
+        currency
+    ))
+}
+
+/// `token_routes` and the balance in a given currency of every product, held in EUR and XAF
+pub(crate) fn balance_routes() -> Route {
+    token_routes().at(
+        "/:product/v1_0/account/balance/:currency",
+        get(mock_balance_in_currency),
+    )
+}
+
+/// Serve the app on a free local port, for as long as the runtime of the test runs
+///
+/// # Returns
+///
+/// * the url of the server, e.g. "http://127.0.0.1:49152"
+pub(crate) fn spawn_mock<E>(app: E) -> String
+where
+    E: IntoEndpoint + Send + 'static,
+    E::Endpoint: 'static,
+{
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(Server::new_with_acceptor(TcpAcceptor::from_std(listener).unwrap()).run(app));
+    url
+}
+
+/// A collection product calling the given url with mock credentials
+pub(crate) fn collection_at(url: String) -> Collection {
+    Collection::new(
+        url,
+        Environment::Sandbox,
+        "mock_api_user".to_string(),
+        "mock_api_key".to_string(),
+        "mock_primary_key".to_string(),
+        "mock_secondary_key".to_string(),
+    )
+}
+
+/// A disbursements product calling the given url with mock credentials
+pub(crate) fn disbursements_at(url: String) -> Disbursements {
+    Disbursements::new(
+        url,
+        Environment::Sandbox,
+        "mock_api_user".to_string(),
+        "mock_api_key".to_string(),
+        "mock_primary_key".to_string(),
+        "mock_secondary_key".to_string(),
+    )
+}
+
+/// A remittance product calling the given url with mock credentials
+pub(crate) fn remittance_at(url: String) -> Remittance {
+    Remittance::new(
+        url,
+        Environment::Sandbox,
+        "mock_api_user".to_string(),
+        "mock_api_key".to_string(),
+        "mock_primary_key".to_string(),
+        "mock_secondary_key".to_string(),
+    )
+}
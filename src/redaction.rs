@@ -0,0 +1,110 @@
+//! Redaction of secrets and personal data before they are logged
+//!
+//! Callback bodies carry the MSISDN, e-mail and identity number of the payer, and the products
+//! handle access tokens and subscription keys. Nothing of the sort is logged as is: the callback
+//! server only logs bodies when `CallbackServerConfig::log_bodies` is set, and then redacted.
+
+/// What the `Debug` output of the products and of `Momo` shows instead of a key
+pub const MASK: &str = "****";
+
+/// The JSON fields whose values are masked by `redact_body`
+pub const SENSITIVE_FIELDS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "id_token",
+    "Ocp-Apim-Subscription-Key",
+    "partyId",
+    "payerMsisdn",
+    "payerEmail",
+    "payerIdentificationNumber",
+    "payerIdentity",
+    "payerFirstName",
+    "payerSurname",
+    "msisdn",
+    "email",
+];
+
+/// Mask a secret, only its last 4 characters are kept when it is long enough to stay secret
+///
+/// # Parameters
+///
+/// * 'value', the secret, e.g. an access token or a subscription key
+///
+/// # Returns
+///
+/// * the masked secret, e.g. "****cdef"
+pub fn redact(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return MASK.to_string();
+    }
+    let last: String = chars[chars.len() - 4..].iter().collect();
+    format!("****{}", last)
+}
+
+/// Mask the values of the `SENSITIVE_FIELDS` of a JSON body, at any depth
+///
+/// # Parameters
+///
+/// * 'body', the body, as received or sent
+///
+/// # Returns
+///
+/// * the body with the sensitive values masked, or only its length when it is not JSON
+pub fn redact_body(body: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => format!("<{} bytes, not JSON>", body.len()),
+    }
+}
+
+fn redact_value(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match value {
+                    serde_json::Value::String(string)
+                        if SENSITIVE_FIELDS.contains(&key.as_str()) =>
+                    {
+                        *string = redact(string);
+                    }
+                    _ => redact_value(value),
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(redact("eyJ0eXAiOiJKV1QiLCJhbGciOiJSMjU2In0"), "****2In0");
+        assert_eq!(redact("short"), "****");
+        assert_eq!(redact(""), "****");
+    }
+
+    #[test]
+    fn test_redact_body() {
+        let body = r#"{"financialTransactionId":"363440463","payer":{"partyIdType":"MSISDN","partyId":"256774290781"},"payerEmail":"payer@example.com","payerIdentificationNumber":"CM1234567","amount":"100"}"#;
+        let redacted: serde_json::Value = serde_json::from_str(&redact_body(body)).unwrap();
+        assert_eq!(redacted["payer"]["partyId"], "****0781");
+        assert_eq!(redacted["payer"]["partyIdType"], "MSISDN");
+        assert_eq!(redacted["payerEmail"], "****.com");
+        assert_eq!(redacted["payerIdentificationNumber"], "****4567");
+        assert_eq!(redacted["financialTransactionId"], "363440463");
+        assert_eq!(redacted["amount"], "100");
+    }
+
+    #[test]
+    fn test_redact_body_not_json() {
+        assert_eq!(redact_body("partyId=256774290781"), "<20 bytes, not JSON>");
+    }
+}
@@ -4,7 +4,7 @@ use reqwest::Body;
 #[doc(hidden)]
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AccessTokenRequest{
     pub grant_type: String,
     pub auth_req_id: String,
@@ -10,7 +10,7 @@ use serde::{Serialize, Deserialize};
 
 
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BcAuthorize {
     pub scope: String,
     #[serde(rename = "login_hint")]
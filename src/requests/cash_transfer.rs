@@ -6,7 +6,24 @@ use serde::{Serialize, Deserialize};
 
 use crate::{structs::party::Party, enums::{currency::Currency, payer_identification_type::PayerIdentificationType}};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// The local currency of the MTN markets, by ISO 3166-1 alpha-2 country code
+pub const MARKET_CURRENCIES: &[(&str, Currency)] = &[
+    ("BJ", Currency::XOF),
+    ("CG", Currency::XAF),
+    ("CI", Currency::XOF),
+    ("CM", Currency::XAF),
+    ("GH", Currency::GHS),
+    ("GN", Currency::GNF),
+    ("NG", Currency::NGN),
+    ("RW", Currency::RWF),
+    ("SS", Currency::SSP),
+    ("SZ", Currency::SZL),
+    ("UG", Currency::UGX),
+    ("ZA", Currency::ZAR),
+    ("ZM", Currency::ZMW),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CashTransferRequest {
     pub amount: String,
     pub currency: Currency,
@@ -45,14 +62,54 @@ pub struct CashTransferRequest {
 
 
 impl CashTransferRequest {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(amount: String, currency: Currency, payee: Party, originating_country: String, original_amount: String,
          original_currency: Currency, payer_message: String, payee_note: String, payer_identification_type: PayerIdentificationType, payer_identification_number: String, 
          payer_identity: String, payer_first_name: String, payer_surname: String, payer_language_code: String, payer_email: String, payer_msisdn: String, payer_gender: String) -> Self{
-        let external_id = uuid::Uuid::new_v4().to_string();
+        let external_id = crate::ReferenceId::new().into();
         Self { amount, currency, payee, external_id, originating_country, original_amount, original_currency, payer_message, payee_note, payer_identification_type, payer_identification_number, payer_identity, 
             payer_first_name, payer_surname, payer_language_code, payer_email, payer_msisdn, payer_gender }
 
     }
+
+    /// Check the request before it is sent: the amount and the original amount must be digits with
+    /// at most two decimals and the originating country an ISO 3166-1 alpha-2 code, e.g. UG
+    ///
+    /// The original currency of a transfer from one of the `MARKET_CURRENCIES` can't be the local
+    /// currency of another market, e.g. UG with GHS. Any other currency, e.g. EUR in the sandbox
+    /// or USD, is accepted, and so is any currency from a country missing from the table.
+    pub fn validate(&self) -> Result<(), std::io::Error> {
+        super::validate_amount(&self.amount)?;
+        super::validate_amount(&self.original_amount)?;
+        let country = &self.originating_country;
+        if country.len() != 2 || !country.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not an ISO 3166-1 alpha-2 country code", country),
+            ));
+        }
+        let local_currency = |country: &str| {
+            MARKET_CURRENCIES
+                .iter()
+                .find(|(market, _)| *market == country)
+                .map(|(_, currency)| *currency)
+        };
+        let foreign_market_currency = MARKET_CURRENCIES
+            .iter()
+            .any(|(_, currency)| *currency == self.original_currency);
+        if let Some(currency) = local_currency(country) {
+            if currency != self.original_currency && foreign_market_currency {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "{} is not the currency of {}, expected {}",
+                        self.original_currency, country, currency
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 
@@ -62,4 +119,59 @@ impl From<CashTransferRequest> for Body {
     fn from(cash_transfer_request: CashTransferRequest) -> Self {
         Body::from(serde_json::to_string(&cash_transfer_request).unwrap())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(amount: &str, originating_country: &str) -> CashTransferRequest {
+        CashTransferRequest::new(
+            amount.to_string(),
+            Currency::EUR,
+            Party::msisdn("256774290781"),
+            originating_country.to_string(),
+            amount.to_string(),
+            Currency::EUR,
+            "payer_message".to_string(),
+            "payee_note".to_string(),
+            PayerIdentificationType::PASS,
+            "CA0000000".to_string(),
+            "256774290781".to_string(),
+            "Jane".to_string(),
+            "Doe".to_string(),
+            "en".to_string(),
+            "jane@example.com".to_string(),
+            "256774290781".to_string(),
+            "F".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(request("100", "UG").validate().is_ok());
+        assert!(request("100.000", "UG").validate().is_err());
+        assert!(request("100", "ug").validate().is_err());
+        assert!(request("100", "UGA").validate().is_err());
+        assert!(request("100", "").validate().is_err());
+
+        let mut transfer = request("100", "UG");
+        transfer.original_amount = "abc".to_string();
+        assert!(transfer.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_original_currency() {
+        let mut transfer = request("100", "UG");
+        for currency in [Currency::UGX, Currency::EUR, Currency::USD] {
+            transfer.original_currency = currency;
+            assert!(transfer.validate().is_ok(), "{}", currency);
+        }
+        transfer.original_currency = Currency::GHS;
+        assert!(transfer.validate().is_err());
+
+        // the table only rejects combinations it knows about
+        let mut transfer = request("100", "FR");
+        transfer.original_currency = Currency::GHS;
+        assert!(transfer.validate().is_ok());
+    }
+}
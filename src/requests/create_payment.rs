@@ -7,7 +7,7 @@ use serde::{Serialize, Deserialize};
 use crate::structs::money::Money;
 
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CreatePayment {
     #[serde(rename = "externalTransactionId")]
     pub external_transaction_id: String,
@@ -33,8 +33,9 @@ pub struct CreatePayment {
 }
 
 impl CreatePayment {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(money: Money, customer_reference: String, service_provider_user_name: String, coupon_id: String, product_id: String, product_offering_id: String, receiver_message: String, sender_note: String, max_number_of_retries: i32, include_sender_charges: bool) -> Self {
-        let external_id = uuid::Uuid::new_v4().to_string();
+        let external_id = crate::ReferenceId::new().into();
         CreatePayment {
             external_transaction_id:external_id,
             money,
@@ -5,14 +5,114 @@ use reqwest::Body;
 use serde::{Serialize, Deserialize};
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DeliveryNotification {
     #[serde(rename = "notificationMessage")]
-    pub notification_message: String
+    pub notification_message: String,
+    /// The language of the notification, one of SUPPORTED_NOTIFICATION_LANGUAGES, sent in the
+    /// Language header. When None no Language header is sent and MTN uses the default language
+    /// of the market of the payer.
+    #[serde(skip)]
+    pub language: Option<String>,
+}
+
+/// Longest notification message MTN accepts, in characters, longer messages are rejected by the API
+pub const MAX_NOTIFICATION_MESSAGE_LENGTH: usize = 160;
+
+/// Languages MTN has notification templates for, English and French, as ISO 639-1 or ISO 639-3
+/// codes
+pub const SUPPORTED_NOTIFICATION_LANGUAGES: [&str; 4] = ["en", "eng", "fr", "fra"];
+
+impl DeliveryNotification {
+    /// Create a delivery notification without a language, MTN sends it in the default language
+    /// of the market of the payer
+    ///
+    /// # Parameters
+    ///
+    /// * 'notification_message', the message sent to the payer
+    pub fn new(notification_message: String) -> Self {
+        DeliveryNotification {
+            notification_message,
+            language: None,
+        }
+    }
+
+    /// Send the notification in this language
+    ///
+    /// # Parameters
+    ///
+    /// * 'language', one of SUPPORTED_NOTIFICATION_LANGUAGES, e.g. "fr" or "fra"
+    ///
+    /// # Returns
+    ///
+    /// * 'DeliveryNotification', or an InvalidInput error when MTN does not support the language
+    pub fn with_language(mut self, language: &str) -> Result<Self, std::io::Error> {
+        self.language = Some(language.to_string());
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Check the notification before it is sent: the message must be at most
+    /// MAX_NOTIFICATION_MESSAGE_LENGTH characters long, and the language, when set, must be one of
+    /// SUPPORTED_NOTIFICATION_LANGUAGES
+    pub fn validate(&self) -> Result<(), std::io::Error> {
+        super::validate_length(
+            "notification message",
+            &self.notification_message,
+            MAX_NOTIFICATION_MESSAGE_LENGTH,
+        )?;
+        match &self.language {
+            Some(language) if !SUPPORTED_NOTIFICATION_LANGUAGES.contains(&language.as_str()) => {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "MTN does not send notifications in {}, use one of {}",
+                        language,
+                        SUPPORTED_NOTIFICATION_LANGUAGES.join(", ")
+                    ),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 impl From<DeliveryNotification> for Body {
     fn from(delivery_notification: DeliveryNotification) -> Self {
         Body::from(serde_json::to_string(&delivery_notification).unwrap())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_language() {
+        let notification = DeliveryNotification::new("message".to_string());
+        assert!(notification.validate().is_ok());
+        for language in SUPPORTED_NOTIFICATION_LANGUAGES {
+            let notification = notification.clone().with_language(language).unwrap();
+            assert_eq!(notification.language.as_deref(), Some(language));
+        }
+        for language in ["", "e", "EN", "en-US", "engl", "f1", "lug", "de"] {
+            assert!(
+                notification.clone().with_language(language).is_err(),
+                "{}",
+                language
+            );
+        }
+        let body = serde_json::to_value(notification.with_language("fr").unwrap()).unwrap();
+        assert_eq!(body, serde_json::json!({"notificationMessage": "message"}));
+    }
+
+    #[test]
+    fn test_validate_length() {
+        let longest = DeliveryNotification::new("é".repeat(MAX_NOTIFICATION_MESSAGE_LENGTH));
+        assert!(longest.validate().is_ok());
+        let too_long = DeliveryNotification::new("m".repeat(MAX_NOTIFICATION_MESSAGE_LENGTH + 1));
+        let err = too_long.validate().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("notification message"));
+    }
+}
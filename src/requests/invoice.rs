@@ -7,7 +7,7 @@ use crate::structs::party::Party;
 
 
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InvoiceRequest {
     #[serde(rename = "externalId")]
     pub external_id: String,
@@ -23,7 +23,7 @@ pub struct InvoiceRequest {
 
 impl InvoiceRequest {
     pub fn new(amount: String, currency: String, validity_duration: String, intended_payer: Party, payee: Party, description: String) -> Self {
-        let external_id = uuid::Uuid::new_v4().to_string();
+        let external_id = crate::ReferenceId::new().into();
         InvoiceRequest {
             external_id,
             amount,
@@ -4,7 +4,7 @@ use reqwest::Body;
 use serde::{Serialize, Deserialize};
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InvoiceDelete {
     #[serde(rename = "externalId")]
     pub external_id: String,
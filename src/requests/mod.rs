@@ -9,4 +9,69 @@ pub mod refund;
 pub mod provisioning;
 pub mod bc_authorize;
 pub mod access_token;
-pub mod cash_transfer;
\ No newline at end of file
+pub mod cash_transfer;
+
+/// Check that an amount is made of digits with at most two decimals, e.g. 100 or 100.50
+///
+/// # Returns
+///
+/// * an InvalidInput error naming the amount when it is not
+pub(crate) fn validate_amount(amount: &str) -> Result<(), std::io::Error> {
+    let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    let valid = match amount.split_once('.') {
+        Some((units, decimals)) => digits(units) && digits(decimals) && decimals.len() <= 2,
+        None => digits(amount),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "{} is not a valid amount, expected digits with at most two decimals",
+                amount
+            ),
+        ))
+    }
+}
+
+/// Check that a message is at most max_length characters long, as MTN rejects longer ones
+///
+/// # Returns
+///
+/// * an InvalidInput error naming the field when it is longer
+pub(crate) fn validate_length(
+    field: &str,
+    value: &str,
+    max_length: usize,
+) -> Result<(), std::io::Error> {
+    let length = value.chars().count();
+    if length <= max_length {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "the {} is {} characters long, MTN accepts at most {}",
+                field, length, max_length
+            ),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_amount() {
+        for amount in ["0", "100", "100.5", "100.50", "0.99"] {
+            assert!(validate_amount(amount).is_ok(), "{}", amount);
+        }
+        for amount in [
+            "", "abc", "100.000", "100.", ".50", "-100", "1e3", "1,000", " 100", "١٠٠",
+        ] {
+            assert!(validate_amount(amount).is_err(), "{}", amount);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 
 
+use std::time::Duration;
+
 #[doc(hidden)]
 use reqwest::Body;
 
@@ -10,20 +12,183 @@ use crate::structs::party::Party;
 
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PreApproval {
     pub payer : Party,
     #[serde(rename = "payerCurrency")]
     pub payer_currency : String,
     #[serde(rename = "payerMessage")]
     pub payer_message : String,
+    /// How long the pre-approval is valid, in seconds, see `with_validity`
     #[serde(rename = "validityTime")]
     pub validity_time : i32,
+    /// The X-Reference-Id to create the pre-approval with, a new UUID when None
+    #[serde(skip)]
+    pub reference_id: Option<String>,
 }
 
+impl PreApproval {
+    /// Create a pre-approval request
+    ///
+    /// # Parameters
+    ///
+    /// * 'payer', the party asked to approve
+    /// * 'payer_currency', ISO4217 currency of the payer, e.g. EUR
+    /// * 'payer_message', message shown to the payer
+    /// * 'validity_time', how long the pre-approval is valid, in seconds
+    ///
+    /// # Returns
+    ///
+    /// * 'PreApproval', or an InvalidInput error when the currency is empty or the validity time
+    ///   is not positive
+    pub fn new(
+        payer: Party,
+        payer_currency: String,
+        payer_message: String,
+        validity_time: i32,
+    ) -> Result<Self, std::io::Error> {
+        let pre_approval = PreApproval {
+            payer,
+            payer_currency,
+            payer_message,
+            validity_time,
+            reference_id: None,
+        };
+        pre_approval.validate()?;
+        Ok(pre_approval)
+    }
+
+    /// Keep the pre-approval valid for this long, sent to MTN in whole seconds
+    ///
+    /// # Parameters
+    ///
+    /// * 'validity', how long the pre-approval is valid, the fraction of a second is dropped
+    ///
+    /// # Returns
+    ///
+    /// * 'PreApproval', or an InvalidInput error when the validity is shorter than a second, or
+    ///   too long to be sent as a 32 bits number of seconds
+    pub fn with_validity(mut self, validity: Duration) -> Result<Self, std::io::Error> {
+        self.validity_time = i32::try_from(validity.as_secs()).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "a validity of {} seconds is too long for a pre-approval",
+                    validity.as_secs()
+                ),
+            )
+        })?;
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// How long the pre-approval is valid, None when the validity time is negative
+    pub fn validity(&self) -> Option<Duration> {
+        u64::try_from(self.validity_time)
+            .ok()
+            .map(Duration::from_secs)
+    }
+
+    /// Check the pre-approval before it is sent: the payer currency can't be empty and the
+    /// validity time must be at least one second
+    pub fn validate(&self) -> Result<(), std::io::Error> {
+        if self.payer_currency.trim().is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "the payer currency of a pre-approval can't be empty",
+            ));
+        }
+        if self.validity_time <= 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "the validity time of a pre-approval must be positive, got {} seconds",
+                    self.validity_time
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Create the pre-approval with this X-Reference-Id instead of a new UUID, e.g. an id
+    /// allocated beforehand for reconciliation
+    pub fn with_reference_id(mut self, reference_id: String) -> Self {
+        self.reference_id = Some(reference_id);
+        self
+    }
+}
 
 impl From<PreApproval> for Body {
     fn from(pre_approval: PreApproval) -> Self {
         Body::from(serde_json::to_string(&pre_approval).unwrap())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Currency;
+
+    #[test]
+    fn test_new() {
+        let pre_approval = PreApproval::new(
+            Party::msisdn("242064818006"),
+            Currency::EUR.to_string(),
+            "payer_message".to_string(),
+            3600,
+        )
+        .unwrap();
+        assert_eq!(pre_approval.payer_currency, "EUR");
+        assert_eq!(pre_approval.validity_time, 3600);
+
+        let new = |currency: &str, validity_time| {
+            PreApproval::new(
+                Party::msisdn("242064818006"),
+                currency.to_string(),
+                String::new(),
+                validity_time,
+            )
+        };
+        assert!(new("EUR", 1).is_ok());
+        assert!(new("EUR", i32::MAX).is_ok());
+        assert!(new("", 3600).is_err());
+        assert!(new(" ", 3600).is_err());
+        assert!(new("EUR", 0).is_err());
+        assert!(new("EUR", -1).is_err());
+    }
+
+    #[test]
+    fn test_with_validity() {
+        let pre_approval = PreApproval::new(
+            Party::msisdn("242064818006"),
+            Currency::EUR.to_string(),
+            String::new(),
+            3600,
+        )
+        .unwrap();
+        let day = pre_approval
+            .clone()
+            .with_validity(Duration::from_secs(24 * 3600))
+            .unwrap();
+        assert_eq!(day.validity_time, 24 * 3600);
+        assert_eq!(day.validity(), Some(Duration::from_secs(24 * 3600)));
+        let longest = Duration::from_secs(i32::MAX as u64);
+        assert!(pre_approval.clone().with_validity(longest).is_ok());
+        assert!(pre_approval
+            .clone()
+            .with_validity(longest + Duration::from_secs(1))
+            .is_err());
+        assert!(pre_approval
+            .clone()
+            .with_validity(Duration::from_millis(999))
+            .is_err());
+        assert!(pre_approval.clone().with_validity(Duration::ZERO).is_err());
+
+        let negative = PreApproval {
+            validity_time: -1,
+            ..pre_approval
+        };
+        assert!(negative.validate().is_err());
+        assert_eq!(negative.validity(), None);
+    }
+}
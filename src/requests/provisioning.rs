@@ -6,15 +6,74 @@ use serde::{Serialize, Deserialize};
 
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProvisioningRequest {
     #[serde(rename = "providerCallbackHost")]
     pub provider_callback_host: String
 }
 
+impl ProvisioningRequest {
+    /// Check the request before it is sent: the provider callback host must be a bare host name,
+    /// e.g. "callbacks.example.com", without scheme, port or path, as MTN expects
+    ///
+    /// # Returns
+    ///
+    /// * '()', or the reason the host is not a bare host name
+    pub fn validate(&self) -> Result<(), String> {
+        let host = &self.provider_callback_host;
+        if host.is_empty() {
+            return Err("the provider callback host can't be empty".to_string());
+        }
+        if host.contains("://") {
+            return Err("give the host name only, without the scheme".to_string());
+        }
+        let valid_label = |label: &str| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        };
+        if host.len() > 253 || !host.split('.').all(valid_label) {
+            return Err(
+                "it is not a bare host name, e.g. callbacks.example.com, without port or path"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
 
 impl From<ProvisioningRequest> for Body {
     fn from(provisioning_request: ProvisioningRequest) -> Self {
         Body::from(serde_json::to_string(&provisioning_request).unwrap())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let request = |host: &str| ProvisioningRequest {
+            provider_callback_host: host.to_string(),
+        };
+        for host in ["test", "google.com", "callbacks.example.com", "my-shop.co.ug", "127.0.0.1"] {
+            assert!(request(host).validate().is_ok(), "{}", host);
+        }
+        for host in [
+            "http://foo/bar",
+            "https://callbacks.example.com",
+            "foo/bar",
+            "callbacks.example.com:8080",
+            "callbacks.example.com.",
+            "-shop.example.com",
+            "not a host",
+            "",
+        ] {
+            assert!(request(host).validate().is_err(), "{}", host);
+        }
+        assert!(request("http://foo/bar").validate().unwrap_err().contains("scheme"));
+    }
+}
@@ -6,7 +6,7 @@ use reqwest::Body;
 use serde::{Serialize, Deserialize};
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Refund {
     pub amount: String,
     pub currency: String,
@@ -18,24 +18,184 @@ pub struct Refund {
     pub payee_note: String,
     #[serde(rename = "referenceIdToRefund")]
     pub reference_id_to_refund: String,
+    /// The X-Reference-Id to send the refund with, a new UUID when None
+    #[serde(skip)]
+    pub reference_id: Option<String>,
 }
 
 impl Refund {
     pub fn new(amount: String, currency: String, payer_message: String, payee_note: String, reference_id_to_refund: String) -> Self {
-        let external_id = uuid::Uuid::new_v4().to_string();
+        let external_id = crate::ReferenceId::new().into();
         Refund {
             amount,
             currency,
             external_id,
             payer_message,
             payee_note,
-            reference_id_to_refund
+            reference_id_to_refund,
+            reference_id: None,
         }
     }
+
+    /// Send the refund with this X-Reference-Id instead of a new UUID, e.g. an id allocated
+    /// beforehand for reconciliation
+    pub fn with_reference_id(mut self, reference_id: String) -> Self {
+        self.reference_id = Some(reference_id);
+        self
+    }
+
+    /// Build a refund field by field instead of with the positional `new`
+    pub fn builder() -> RefundBuilder {
+        RefundBuilder::default()
+    }
+
+    /// Check the refund before it is sent: the amount must be digits with at most two decimals and
+    /// the reference id to refund the UUID of the original payment
+    pub fn validate(&self) -> Result<(), std::io::Error> {
+        super::validate_amount(&self.amount)?;
+        if uuid::Uuid::parse_str(&self.reference_id_to_refund).is_err() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "'{}' is not the UUID of the payment to refund",
+                    self.reference_id_to_refund
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// # RefundBuilder
+/// Builds a `Refund`, checked with `Refund::validate`
+///
+/// - 'amount', the amount to refund, e.g. "100"
+/// - 'currency', the currency of the amount, e.g. "EUR"
+/// - 'reference_id_to_refund', the X-Reference-Id of the payment to refund
+/// - 'payer_message', default = empty
+/// - 'payee_note', default = empty
+/// - 'external_id', default = a new UUID
+#[derive(Debug, Clone, Default)]
+pub struct RefundBuilder {
+    amount: String,
+    currency: String,
+    reference_id_to_refund: String,
+    payer_message: String,
+    payee_note: String,
+    external_id: Option<String>,
+}
+
+impl RefundBuilder {
+    pub fn amount(mut self, amount: &str) -> Self {
+        self.amount = amount.to_string();
+        self
+    }
+
+    pub fn currency(mut self, currency: &str) -> Self {
+        self.currency = currency.to_string();
+        self
+    }
+
+    pub fn reference_id_to_refund(mut self, reference_id_to_refund: &str) -> Self {
+        self.reference_id_to_refund = reference_id_to_refund.to_string();
+        self
+    }
+
+    pub fn payer_message(mut self, payer_message: &str) -> Self {
+        self.payer_message = payer_message.to_string();
+        self
+    }
+
+    pub fn payee_note(mut self, payee_note: &str) -> Self {
+        self.payee_note = payee_note.to_string();
+        self
+    }
+
+    pub fn external_id(mut self, external_id: &str) -> Self {
+        self.external_id = Some(external_id.to_string());
+        self
+    }
+
+    /// # Returns
+    ///
+    /// * 'Refund', or an InvalidInput error when the amount or the reference id to refund is not valid
+    pub fn build(self) -> Result<Refund, std::io::Error> {
+        let mut refund = Refund::new(
+            self.amount,
+            self.currency,
+            self.payer_message,
+            self.payee_note,
+            self.reference_id_to_refund,
+        );
+        if let Some(external_id) = self.external_id {
+            refund.external_id = external_id;
+        }
+        refund.validate()?;
+        Ok(refund)
+    }
 }
 
 impl From<Refund> for Body {
     fn from(refund: Refund) -> Self {
         Body::from(serde_json::to_string(&refund).unwrap())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refund_builder() {
+        let reference_id_to_refund = uuid::Uuid::new_v4().to_string();
+        let refund = Refund::builder()
+            .amount("100")
+            .currency("EUR")
+            .reference_id_to_refund(&reference_id_to_refund)
+            .payee_note("payee_note")
+            .external_id("external_id")
+            .build()
+            .unwrap();
+        assert_eq!(refund.amount, "100");
+        assert_eq!(refund.reference_id_to_refund, reference_id_to_refund);
+        assert_eq!(refund.external_id, "external_id");
+        assert_eq!(refund.payer_message, "");
+
+        let builder = Refund::builder().amount("100").currency("EUR");
+        assert!(builder.clone().build().is_err());
+        assert!(builder.clone().reference_id_to_refund("").build().is_err());
+        assert!(builder.reference_id_to_refund("payment_1").build().is_err());
+        assert!(Refund::builder()
+            .amount("1,000")
+            .currency("EUR")
+            .reference_id_to_refund(&reference_id_to_refund)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_reference_id() {
+        let reference_id_to_refund = uuid::Uuid::new_v4().to_string();
+        let refund = Refund::new(
+            "100".to_string(),
+            "EUR".to_string(),
+            "payer_message".to_string(),
+            "payee_note".to_string(),
+            reference_id_to_refund.clone(),
+        );
+        assert_eq!(refund.reference_id, None);
+        let refund = refund.with_reference_id("preallocated_reference_id".to_string());
+        assert_eq!(
+            refund.reference_id.as_deref(),
+            Some("preallocated_reference_id")
+        );
+
+        // sent as the X-Reference-Id header, not in the body
+        let body = serde_json::to_value(&refund).unwrap();
+        assert!(body.get("referenceId").is_none());
+        assert!(body.get("reference_id").is_none());
+        assert_eq!(body["referenceIdToRefund"], reference_id_to_refund);
+        let parsed: Refund = serde_json::from_value(body).unwrap();
+        assert_eq!(parsed.reference_id, None);
+    }
+}
@@ -8,7 +8,7 @@ use serde::{Serialize, Deserialize};
 use crate::{structs::party::Party, enums::currency::Currency};
 
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RequestToPay {
     pub amount : String, // Amount that will be debited from the payer account.
     pub currency : Currency, // ISO4217 Currency
@@ -23,23 +23,41 @@ pub struct RequestToPay {
     #[serde(rename = "payerMessage")]
     pub payer_message : String, // Message that will be written in the payer transaction history message field.
     #[serde(rename = "payeeNote")]
-    pub payee_note : String // Message that will be written in the payee transaction history note field.
-
+    pub payee_note : String, // Message that will be written in the payee transaction history note field.
 }
 
+/// Longest payer message MTN accepts, in characters, longer messages are rejected by the API
+pub const MAX_PAYER_MESSAGE_LENGTH: usize = 160;
+/// Longest payee note MTN accepts, in characters, longer notes are rejected by the API
+pub const MAX_PAYEE_NOTE_LENGTH: usize = 160;
+
 impl RequestToPay {
 
     pub fn new(amount: String, currency: Currency, payer: Party, payer_message: String, payee_note: String) -> Self {
-        let external_id = uuid::Uuid::new_v4().to_string();
+        let external_id = crate::ReferenceId::new().into();
         RequestToPay {
             amount,
             currency,
             external_id,
             payer,
             payer_message,
-            payee_note
+            payee_note,
         }
     }
+
+    /// Check the request before it is sent: the amount must be digits with at most two decimals,
+    /// the payer message and the payee note at most MAX_PAYER_MESSAGE_LENGTH and
+    /// MAX_PAYEE_NOTE_LENGTH characters long
+    pub fn validate(&self) -> Result<(), std::io::Error> {
+        super::validate_amount(&self.amount)?;
+        super::validate_length(
+            "payer message",
+            &self.payer_message,
+            MAX_PAYER_MESSAGE_LENGTH,
+        )?;
+        super::validate_length("payee note", &self.payee_note, MAX_PAYEE_NOTE_LENGTH)?;
+        Ok(())
+    }
 }
 
 
@@ -48,3 +66,30 @@ impl From<RequestToPay> for Body {
         Body::from(serde_json::to_string(&request_to_pay).unwrap())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(payer_message: &str, payee_note: &str) -> RequestToPay {
+        RequestToPay::new(
+            "100".to_string(),
+            Currency::EUR,
+            Party::msisdn("256774290781"),
+            payer_message.to_string(),
+            payee_note.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_validate_lengths() {
+        let longest = "é".repeat(MAX_PAYER_MESSAGE_LENGTH);
+        assert!(request(&longest, &"n".repeat(MAX_PAYEE_NOTE_LENGTH))
+            .validate()
+            .is_ok());
+        assert!(request(&format!("{}a", longest), "").validate().is_err());
+        assert!(request("", &"n".repeat(MAX_PAYEE_NOTE_LENGTH + 1))
+            .validate()
+            .is_err());
+    }
+}
@@ -8,7 +8,7 @@ use reqwest::Body;
 
 use crate::{structs::party::Party, enums::currency::Currency};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Transfer {
     pub amount : String,
     pub currency : Currency,
@@ -24,7 +24,7 @@ pub struct Transfer {
 
 impl Transfer {
     pub fn new(amount: String, currency: Currency, payee: Party, payer_message: String, payee_note: String) -> Self {
-        let external_id = uuid::Uuid::new_v4().to_string();
+        let external_id = crate::ReferenceId::new().into();
         Transfer {
             amount,
             currency,
@@ -34,6 +34,94 @@ impl Transfer {
             payee_note
         }
     }
+
+    /// Check the request before it is sent: the amount must be digits with at most two decimals
+    pub fn validate(&self) -> Result<(), std::io::Error> {
+        super::validate_amount(&self.amount)
+    }
+
+    /// Build a transfer field by field instead of with the positional `new`
+    pub fn builder() -> TransferBuilder {
+        TransferBuilder::default()
+    }
+}
+
+/// # TransferBuilder
+/// Builds a `Transfer`, checked with `Transfer::validate`
+///
+/// - 'amount', the amount to transfer, e.g. "100"
+/// - 'currency', the currency of the amount
+/// - 'payee', who receives the transfer
+/// - 'payer_message', default = empty
+/// - 'payee_note', default = empty
+/// - 'external_id', default = a new UUID
+#[derive(Debug, Clone, Default)]
+pub struct TransferBuilder {
+    amount: String,
+    currency: Option<Currency>,
+    payee: Option<Party>,
+    payer_message: String,
+    payee_note: String,
+    external_id: Option<String>,
+}
+
+impl TransferBuilder {
+    pub fn amount(mut self, amount: &str) -> Self {
+        self.amount = amount.to_string();
+        self
+    }
+
+    pub fn currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    pub fn payee(mut self, payee: Party) -> Self {
+        self.payee = Some(payee);
+        self
+    }
+
+    pub fn payer_message(mut self, payer_message: &str) -> Self {
+        self.payer_message = payer_message.to_string();
+        self
+    }
+
+    pub fn payee_note(mut self, payee_note: &str) -> Self {
+        self.payee_note = payee_note.to_string();
+        self
+    }
+
+    pub fn external_id(mut self, external_id: &str) -> Self {
+        self.external_id = Some(external_id.to_string());
+        self
+    }
+
+    /// # Returns
+    ///
+    /// * 'Transfer', or an InvalidInput error when the currency or the payee is missing or the
+    ///   amount is not valid
+    pub fn build(self) -> Result<Transfer, std::io::Error> {
+        let missing = |field: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("the {} of the transfer is missing", field),
+            )
+        };
+        let currency = self.currency.ok_or_else(|| missing("currency"))?;
+        let payee = self.payee.ok_or_else(|| missing("payee"))?;
+        let mut transfer = Transfer::new(
+            self.amount,
+            currency,
+            payee,
+            self.payer_message,
+            self.payee_note,
+        );
+        if let Some(external_id) = self.external_id {
+            transfer.external_id = external_id;
+        }
+        transfer.validate()?;
+        Ok(transfer)
+    }
 }
 
 impl From<Transfer> for Body {
@@ -4,7 +4,7 @@
 use serde::{Serialize, Deserialize};
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BasicUserInfoJsonResponse {
     pub given_name: String,
     pub family_name: String,
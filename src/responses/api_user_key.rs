@@ -6,8 +6,16 @@
 use serde::{Serialize, Deserialize};
 
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct ApiUserKeyResult {
     #[serde(rename = "apiKey")]
     pub api_key: String,
-}
\ No newline at end of file
+}
+
+impl std::fmt::Debug for ApiUserKeyResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiUserKeyResult")
+            .field("api_key", &crate::redaction::MASK)
+            .finish()
+    }
+}
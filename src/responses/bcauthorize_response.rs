@@ -3,7 +3,7 @@
 use serde::{Serialize, Deserialize};
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BCAuthorizeResponse {
     pub auth_req_id: String,
     pub interval: i64,
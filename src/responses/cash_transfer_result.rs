@@ -6,7 +6,7 @@ use serde::{Serialize, Deserialize};
 use crate::structs::party::Party;
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CashTransferResult {
     #[serde(rename = "financialTransactionId")]
     pub financial_transaction_id: String,
@@ -2,10 +2,13 @@
 #[doc(hidden)]
 use serde::{Serialize, Deserialize};
 
+use chrono::{DateTime, Utc};
+
+use crate::enums::invoice_status::InvoiceStatus;
 use crate::structs::party::Party;
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InvoiceResult {
     #[serde(rename = "referenceId")]
     pub reference_id: String,
@@ -23,4 +26,18 @@ pub struct InvoiceResult {
     #[serde(rename = "intendedPayer")]
     pub intended_payer: Party,
     pub description: String,
-}
\ No newline at end of file
+}
+
+
+impl InvoiceResult {
+    /// The status of the invoice, UNKNOWN when MTN sends a status this crate does not know
+    pub fn status_enum(&self) -> InvoiceStatus {
+        InvoiceStatus::from_string(&self.status)
+    }
+
+    /// The invoice expiry time, parsed as a UTC timestamp.
+    /// Fails if the value is empty or not a valid ISO-8601 date time.
+    pub fn expiry_at(&self) -> Result<DateTime<Utc>, chrono::ParseError> {
+        crate::parse_momo_date_time(&self.expiry_date_time)
+    }
+}
@@ -2,7 +2,7 @@
 use serde::{Serialize, Deserialize};
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OAuth2TokenResponse {
     pub access_token: String,
     pub token_type: String,
@@ -4,7 +4,7 @@
 use serde::{Serialize, Deserialize};
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PaymentResult {
     #[serde(rename = "referenceId")]
     pub reference_id: String,
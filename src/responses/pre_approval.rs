@@ -1,17 +1,50 @@
-
-
 #[doc(hidden)]
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
 
-use crate::structs::party::Party;
+use chrono::{DateTime, Utc};
 
+use crate::structs::party::Party;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PreApprovalResult {
     pub payer: Party,
     #[serde(rename = "payerCurrency")]
     pub payer_currency: String,
     pub status: String,
     #[serde(rename = "expirationDateTime")]
-    pub expiration_date_time: String
-}
\ No newline at end of file
+    pub expiration_date_time: String,
+}
+
+impl PreApprovalResult {
+    /// The pre-approval expiration time, parsed as a UTC timestamp.
+    /// Fails if the value is empty or not a valid ISO-8601 date time.
+    pub fn expiration_at(&self) -> Result<DateTime<Utc>, chrono::ParseError> {
+        crate::parse_momo_date_time(&self.expiration_date_time)
+    }
+
+    /// Whether the payer has not answered yet, the status is PENDING or CREATED
+    pub fn is_pending(&self) -> bool {
+        matches!(self.status.as_str(), "PENDING" | "CREATED")
+    }
+}
+
+/// A pre-approval created by `Collection::create_pre_approval`, with what MTN returned about it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreApprovalCreated {
+    /// The X-Reference-Id the pre-approval was created with, used to get its status
+    #[serde(skip)]
+    pub external_id: String,
+    pub status: Option<String>,
+    #[serde(rename = "expirationDateTime")]
+    pub expiration_date_time: Option<String>,
+}
+
+impl PreApprovalCreated {
+    /// The pre-approval expiration time, parsed as a UTC timestamp.
+    /// None if MTN did not return it or it is not a valid ISO-8601 date time.
+    pub fn expiration_at(&self) -> Option<DateTime<Utc>> {
+        self.expiration_date_time
+            .as_deref()
+            .and_then(|value| crate::parse_momo_date_time(value).ok())
+    }
+}
@@ -5,11 +5,14 @@ use serde::{Serialize, Deserialize};
 use crate::structs::party::Party;
 
 
-#[derive(Debug, Serialize, Deserialize)]
+/// The status of a refund
+///
+/// MTN only sends the `financialTransactionId` of successful refunds, it is empty until then.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct  RefundResult {
     pub amount : String,
     pub currency : String,
-    #[serde(rename = "financialTransactionId")]
+    #[serde(rename = "financialTransactionId", default)]
     pub financial_transaction_id : String,
     #[serde(rename = "externalId")]
     pub external_id : String,
@@ -19,4 +22,21 @@ pub struct  RefundResult {
     #[serde(rename = "payeeNote")]
     pub payee_note : String,
     pub status : String,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refund_result() {
+        let body = r#"{"amount":"100","currency":"EUR","financialTransactionId":"1073587524","externalId":"947354","payee":{"partyIdType":"MSISDN","partyId":"4656473839"},"payerMessage":"payer_message","payeeNote":"payee_note","status":"SUCCESSFUL"}"#;
+        let result: RefundResult = serde_json::from_str(body).unwrap();
+        assert_eq!(result.financial_transaction_id, "1073587524");
+
+        let body = r#"{"amount":"100","currency":"EUR","externalId":"947354","payee":{"partyIdType":"MSISDN","partyId":"4656473839"},"payerMessage":"payer_message","payeeNote":"payee_note","status":"PENDING"}"#;
+        let result: RefundResult = serde_json::from_str(body).unwrap();
+        assert_eq!(result.status, "PENDING");
+        assert_eq!(result.financial_transaction_id, "");
+    }
+}
@@ -1,8 +1,9 @@
+use crate::enums::request_to_pay_status::RequestToPayStatus;
 use crate::structs::party::Party;
 #[doc(hidden)]
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RequestToPayResult {
     pub amount: String,
     pub currency: String,
@@ -18,3 +19,71 @@ pub struct RequestToPayResult {
     pub status: String,
     pub reason: Option<String>,
 }
+
+impl RequestToPayResult {
+    /// The status of the request to pay, UNKNOWN when MTN sends a status this crate does not know
+    pub fn status_enum(&self) -> RequestToPayStatus {
+        RequestToPayStatus::from_string(&self.status)
+    }
+
+    /// The id of the financial transaction, only set once the request to pay is successful
+    pub fn financial_transaction_id(&self) -> Option<&str> {
+        self.financial_transaction_id.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(status: &str, financial_transaction_id: Option<&str>) -> RequestToPayResult {
+        serde_json::from_value(serde_json::json!({
+            "amount": "100",
+            "currency": "EUR",
+            "financialTransactionId": financial_transaction_id,
+            "externalId": "external_id",
+            "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "payerMessage": "payer_message",
+            "payeeNote": "payee_note",
+            "status": status,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_status_enum() {
+        assert_eq!(
+            result("PENDING", None).status_enum(),
+            RequestToPayStatus::PENDING
+        );
+        assert_eq!(
+            result("SUCCESSFUL", Some("363440463")).status_enum(),
+            RequestToPayStatus::SUCCESSFULL
+        );
+        assert_eq!(
+            result("FAILED", None).status_enum(),
+            RequestToPayStatus::FAILED
+        );
+        assert_eq!(
+            result("REJECTED", None).status_enum(),
+            RequestToPayStatus::UNKNOWN
+        );
+    }
+
+    #[test]
+    fn test_financial_transaction_id() {
+        assert_eq!(
+            result("SUCCESSFUL", Some("363440463")).financial_transaction_id(),
+            Some("363440463")
+        );
+        assert_eq!(result("PENDING", None).financial_transaction_id(), None);
+    }
+
+    #[test]
+    fn test_clone_and_compare() {
+        let pending = result("PENDING", None);
+        let stored = pending.clone();
+        assert_eq!(stored, pending);
+        assert_ne!(stored, result("SUCCESSFUL", Some("363440463")));
+    }
+}
@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 #[doc(hidden)]
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Clone, PartialEq, Serialize)]
 pub struct TokenResponse {
     pub access_token: String,
     pub token_type: String,
@@ -10,6 +10,21 @@ pub struct TokenResponse {
     pub created_at: Option<DateTime<Utc>>,
 }
 
+// the access token is masked, so that logging a token response does not leak it
+impl std::fmt::Debug for TokenResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenResponse")
+            .field(
+                "access_token",
+                &crate::redaction::redact(&self.access_token),
+            )
+            .field("token_type", &self.token_type)
+            .field("expires_in", &self.expires_in)
+            .field("created_at", &self.created_at)
+            .finish()
+    }
+}
+
 impl<'de> Deserialize<'de> for TokenResponse {
     fn deserialize<D>(deserializer: D) -> Result<TokenResponse, D::Error>
     where
@@ -8,10 +8,16 @@ use serde::{Serialize, Deserialize};
 use crate::structs::party::Party;
 
 
-#[derive(Debug, Serialize, Deserialize)]
+/// The status of a transfer or of a deposit, MTN answers both with the same body
+///
+/// `financial_transaction_id` is only set once the transfer is successful and `reason` only
+/// once it has failed. Refunds are answered with a `RefundResult`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TransferResult {
     pub amount : String,
     pub currency : String,
+    #[serde(rename = "financialTransactionId")]
+    pub financial_transaction_id : Option<String>,
     #[serde(rename = "externalId")]
     pub external_id : String,
     pub payee : Party,
@@ -20,4 +26,31 @@ pub struct TransferResult {
     #[serde(rename = "payeeNote")]
     pub payee_note : String,
     pub status : String,
-}
\ No newline at end of file
+    pub reason : Option<String>,
+}
+
+/// The status of a deposit, see `TransferResult`
+pub type DepositResult = TransferResult;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_successful_transfer() {
+        let body = r#"{"amount":"100","currency":"EUR","financialTransactionId":"1073587524","externalId":"947354","payee":{"partyIdType":"MSISDN","partyId":"4656473839"},"payerMessage":"payer_message","payeeNote":"payee_note","status":"SUCCESSFUL"}"#;
+        let result: TransferResult = serde_json::from_str(body).unwrap();
+        assert_eq!(result.status, "SUCCESSFUL");
+        assert_eq!(result.financial_transaction_id.as_deref(), Some("1073587524"));
+        assert_eq!(result.reason, None);
+    }
+
+    #[test]
+    fn test_failed_deposit() {
+        let body = r#"{"amount":"100","currency":"EUR","externalId":"947354","payee":{"partyIdType":"MSISDN","partyId":"46733123450"},"payerMessage":"payer_message","payeeNote":"payee_note","status":"FAILED","reason":"PAYEE_NOT_FOUND"}"#;
+        let result: DepositResult = serde_json::from_str(body).unwrap();
+        assert_eq!(result.status, "FAILED");
+        assert_eq!(result.financial_transaction_id, None);
+        assert_eq!(result.reason.as_deref(), Some("PAYEE_NOT_FOUND"));
+    }
+}
@@ -0,0 +1,47 @@
+#[doc(hidden)]
+use serde::{Deserialize, Serialize};
+
+/// # ApiCredentials
+/// The api user and api key of a sandbox api user, returned by `MomoProvisioning::provision`
+///
+/// Keep them, e.g. in a file, to reuse the api user across runs rather than provisioning a new
+/// one each time, and give them back to `Momo::new`:
+///
+/// ```no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use mtnmomo::{ApiCredentials, Environment, Momo, MomoProvisioning};
+///
+/// let url = "https://sandbox.momodeveloper.mtn.com".to_string();
+/// let provisioning = MomoProvisioning::new(url.clone(), "subscription_key".to_string());
+/// let credentials = provisioning.provision("callbacks.example.com").await?;
+/// std::fs::write("sandbox_api_user.json", serde_json::to_string(&credentials)?)?;
+///
+/// // on the next runs
+/// let credentials: ApiCredentials =
+///     serde_json::from_str(&std::fs::read_to_string("sandbox_api_user.json")?)?;
+/// let momo = Momo::new(
+///     url,
+///     credentials.api_user,
+///     Environment::Sandbox,
+///     Some(credentials.api_key),
+/// )
+/// .await;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiCredentials {
+    /// The reference id the api user was created with, the 'api_user' of `Momo::new`
+    pub api_user: String,
+    pub api_key: String,
+}
+
+impl std::fmt::Debug for ApiCredentials {
+    /// The api key is masked, so that the credentials can be logged
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiCredentials")
+            .field("api_user", &self.api_user)
+            .field("api_key", &crate::redaction::MASK)
+            .finish()
+    }
+}
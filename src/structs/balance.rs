@@ -4,9 +4,22 @@ use serde::{Serialize, Deserialize};
 
 use crate::enums::currency::Currency;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Balance {
     #[serde(rename = "availableBalance")] // The available balance of the account
     pub available_balance: String, // The available balance of the account
     pub currency: Currency, // ISO4217 Currency
-}
\ No newline at end of file
+}
+
+/// # Balances
+/// The balance of every product, see `Momo::all_balances`
+///
+/// - 'collection', the balance of the collection account, or why it could not be fetched
+/// - 'disbursements', the balance of the disbursements account, or why it could not be fetched
+/// - 'remittance', the balance of the remittance account, or why it could not be fetched
+#[derive(Debug)]
+pub struct Balances {
+    pub collection: Result<Balance, Box<dyn std::error::Error>>,
+    pub disbursements: Result<Balance, Box<dyn std::error::Error>>,
+    pub remittance: Result<Balance, Box<dyn std::error::Error>>,
+}
@@ -1,3 +1,7 @@
 pub mod money;
 pub mod balance;
-pub mod party;
\ No newline at end of file
+pub mod party;
+pub mod transaction_event;
+pub mod reference_id;
+pub mod submitted;
+pub mod api_credentials;
@@ -2,9 +2,101 @@
 #[doc(hidden)]
 use serde::{Serialize, Deserialize};
 
+use rust_decimal::Decimal;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::enums::currency::Currency;
+
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Money {
     pub amount: String,
     pub currency: String
-}
\ No newline at end of file
+}
+
+
+impl Money {
+    /// Create an amount of money, stored in the string shape MTN expects
+    ///
+    /// # Parameters
+    ///
+    /// * 'amount', the amount
+    /// * 'currency', the currency of the amount
+    pub fn new(amount: Decimal, currency: Currency) -> Self {
+        Money {
+            amount: amount.to_string(),
+            currency: currency.to_string(),
+        }
+    }
+
+    /// The amount, parsed as a decimal
+    pub fn amount_decimal(&self) -> Result<Decimal, Box<dyn std::error::Error>> {
+        Ok(self.amount.parse::<Decimal>()?)
+    }
+
+    /// true when both amounts are in the same currency
+    pub fn same_currency_as(&self, other: &Money) -> bool {
+        self.currency == other.currency
+    }
+
+    /// Add two amounts of money
+    ///
+    /// # Returns
+    ///
+    /// * 'Money', the sum, or an error when the currencies differ, an amount is not a number
+    ///   or the sum overflows
+    pub fn checked_add(&self, other: &Money) -> Result<Money, Box<dyn std::error::Error>> {
+        if !self.same_currency_as(other) {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("cannot add {} to {}", other.currency, self.currency),
+            )));
+        }
+        let amount = self
+            .amount_decimal()?
+            .checked_add(other.amount_decimal()?)
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "amount overflow")
+            })?;
+        Ok(Money {
+            amount: amount.to_string(),
+            currency: self.currency.clone(),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_add() {
+        let total = Money::new(Decimal::new(1050, 2), Currency::EUR)
+            .checked_add(&Money::new(Decimal::new(250, 2), Currency::EUR))
+            .unwrap();
+        assert_eq!(total.amount, "13.00");
+        assert_eq!(total.currency, "EUR");
+        assert_eq!(
+            serde_json::to_string(&total).unwrap(),
+            r#"{"amount":"13.00","currency":"EUR"}"#
+        );
+    }
+
+    #[test]
+    fn test_checked_add_currency_mismatch() {
+        let eur = Money::new(Decimal::new(100, 0), Currency::EUR);
+        let xaf = Money::new(Decimal::new(100, 0), Currency::XAF);
+        assert!(!eur.same_currency_as(&xaf));
+        assert!(eur.checked_add(&xaf).is_err());
+    }
+
+    #[test]
+    fn test_checked_add_invalid_amount() {
+        let eur = Money::new(Decimal::new(100, 0), Currency::EUR);
+        let invalid = Money {
+            amount: "one hundred".to_string(),
+            currency: "EUR".to_string(),
+        };
+        assert!(eur.checked_add(&invalid).is_err());
+    }
+}
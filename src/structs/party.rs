@@ -1,9 +1,9 @@
 #[doc(hidden)]
 use serde::{Deserialize, Serialize};
 
-use crate::enums::party_id_type::PartyIdType;
+use crate::enums::{msisdn_policy::MsisdnPolicy, party_id_type::PartyIdType};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Party {
     /// Party identifies a account holder in the wallet platform. Party consists of two parameters, type and partyId. Each type have its own validation of the partyId
     /// MSISDN - Mobile Number validated according to ITU-T E.164. Validated with IsMSISDN
@@ -14,3 +14,108 @@ pub struct Party {
     #[serde(rename = "partyId")]
     pub party_id: String,
 }
+
+impl Party {
+    /// A party identified by its mobile number
+    ///
+    /// # Parameters
+    ///
+    /// * 'number', the mobile number, e.g. 256774290781 or +256774290781
+    pub fn msisdn(number: &str) -> Party {
+        Party {
+            party_id_type: PartyIdType::MSISDN,
+            party_id: number.to_string(),
+        }
+    }
+
+    /// A party identified by its e-mail address
+    ///
+    /// # Parameters
+    ///
+    /// * 'addr', the e-mail address
+    pub fn email(addr: &str) -> Party {
+        Party {
+            party_id_type: PartyIdType::EMAIL,
+            party_id: addr.to_string(),
+        }
+    }
+
+    /// The same party, with its mobile number formatted according to the policy
+    /// (other kinds of party are left untouched)
+    ///
+    /// # Parameters
+    ///
+    /// * 'policy', how the mobile number is formatted
+    pub fn normalized(self, policy: MsisdnPolicy) -> Party {
+        match self.party_id_type {
+            PartyIdType::MSISDN => Party {
+                party_id: policy.normalize(&self.party_id),
+                ..self
+            },
+            _ => self,
+        }
+    }
+}
+
+/// A mobile number, only made of digits with an optional leading `+` and at most 15 digits (E.164)
+impl TryFrom<&str> for Party {
+    type Error = std::io::Error;
+
+    fn try_from(number: &str) -> Result<Self, Self::Error> {
+        let digits = number.strip_prefix('+').unwrap_or(number);
+        if digits.is_empty() || digits.len() > 15 || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not a valid MSISDN", number),
+            ));
+        }
+        Ok(Party::msisdn(number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constructors() {
+        let party = Party::msisdn("256774290781");
+        assert_eq!(party.party_id_type, PartyIdType::MSISDN);
+        assert_eq!(party.party_id, "256774290781");
+
+        let party = Party::email("test@email.com");
+        assert_eq!(party.party_id_type, PartyIdType::EMAIL);
+        assert_eq!(party.party_id, "test@email.com");
+    }
+
+    #[test]
+    fn test_try_from() {
+        let party = Party::try_from("+242064818006").unwrap();
+        assert_eq!(party.party_id_type, PartyIdType::MSISDN);
+        assert_eq!(party.party_id, "+242064818006");
+        assert!(Party::try_from("46733123450").is_ok());
+
+        assert!(Party::try_from("").is_err());
+        assert!(Party::try_from("+").is_err());
+        assert!(Party::try_from("0700 123 456").is_err());
+        assert!(Party::try_from("24+2064818006").is_err());
+        assert!(Party::try_from("1234567890123456").is_err());
+        assert!(Party::try_from("test@email.com").is_err());
+    }
+
+    #[test]
+    fn test_normalized() {
+        let party = Party::msisdn(" +242 06-481.8006 ");
+        assert_eq!(
+            party.clone().normalized(MsisdnPolicy::KeepPlus).party_id,
+            "+242064818006"
+        );
+        assert_eq!(
+            party.normalized(MsisdnPolicy::StripPlus).party_id,
+            "242064818006"
+        );
+
+        let party = Party::email("first last@email.com").normalized(MsisdnPolicy::StripPlus);
+        assert_eq!(party.party_id, "first last@email.com");
+    }
+}
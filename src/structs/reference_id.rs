@@ -0,0 +1,127 @@
+#[doc(hidden)]
+use serde::{Deserialize, Serialize};
+
+/// The longest reference `ReferenceId::with_prefix` creates, a prefix can be up to 27
+/// characters long since the UUID and its separator take 37
+pub const MAX_REFERENCE_ID_LENGTH: usize = 64;
+
+/// # ReferenceId
+/// A reference identifying a transaction, a new UUID v4 or a prefixed one, e.g.
+/// "order-1234-9b8f2a52-4c1e-4f7b-9b1e-0f5c2d3e4a5b"
+///
+/// MTN only accepts a UUID as the X-Reference-Id of a request, `is_uuid` tells whether the
+/// reference can be one. A prefixed reference is meant for the external id of a request, which
+/// MTN sends back in the callbacks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ReferenceId(String);
+
+impl ReferenceId {
+    /// A new UUID v4
+    pub fn new() -> ReferenceId {
+        ReferenceId(uuid::Uuid::new_v4().to_string())
+    }
+
+    /// A new UUID v4 after the given prefix and a dash
+    ///
+    /// # Parameters
+    ///
+    /// * 'prefix', e.g. "order-1234", made of ASCII letters, digits, '-' and '_'
+    ///
+    /// # Returns
+    ///
+    /// * 'ReferenceId', or an InvalidInput error when the prefix is empty, has another character
+    ///   or makes the reference longer than MAX_REFERENCE_ID_LENGTH
+    pub fn with_prefix(prefix: &str) -> Result<ReferenceId, std::io::Error> {
+        if prefix.is_empty()
+            || !prefix
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "'{}' is not a valid reference prefix, only ASCII letters, digits, '-' and '_' are allowed",
+                    prefix
+                ),
+            ));
+        }
+        let reference_id = format!("{}-{}", prefix, uuid::Uuid::new_v4());
+        if reference_id.len() > MAX_REFERENCE_ID_LENGTH {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "the reference prefix '{}' is too long, a reference is at most {} characters",
+                    prefix, MAX_REFERENCE_ID_LENGTH
+                ),
+            ));
+        }
+        Ok(ReferenceId(reference_id))
+    }
+
+    /// true when the reference is a UUID, the only kind MTN accepts as X-Reference-Id
+    pub fn is_uuid(&self) -> bool {
+        uuid::Uuid::parse_str(&self.0).is_ok()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for ReferenceId {
+    fn default() -> Self {
+        ReferenceId::new()
+    }
+}
+
+impl std::fmt::Display for ReferenceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<ReferenceId> for String {
+    fn from(reference_id: ReferenceId) -> Self {
+        reference_id.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let first = ReferenceId::new();
+        let second = ReferenceId::new();
+        assert!(first.is_uuid());
+        assert_ne!(first, second);
+        assert_eq!(
+            uuid::Uuid::parse_str(first.as_str())
+                .unwrap()
+                .get_version_num(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_with_prefix() {
+        let reference_id = ReferenceId::with_prefix("order-shop_42").unwrap();
+        let uuid = reference_id.as_str().strip_prefix("order-shop_42-").unwrap();
+        assert!(uuid::Uuid::parse_str(uuid).is_ok());
+        assert!(!reference_id.is_uuid());
+
+        let longest = "a".repeat(MAX_REFERENCE_ID_LENGTH - 37);
+        assert_eq!(
+            ReferenceId::with_prefix(&longest).unwrap().as_str().len(),
+            MAX_REFERENCE_ID_LENGTH
+        );
+        let too_long = ReferenceId::with_prefix(&format!("{}a", longest)).unwrap_err();
+        assert_eq!(too_long.kind(), std::io::ErrorKind::InvalidInput);
+
+        for prefix in ["", "order 1", "commande-é", "order/1"] {
+            assert!(ReferenceId::with_prefix(prefix).is_err(), "{}", prefix);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::ops::Deref;
+
+/// Whether MTN carried out an operation when it answered, or only accepted it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubmissionStatus {
+    /// MTN answered 202 Accepted, the operation is pending: poll its status or wait for its
+    /// callback
+    Accepted,
+    /// MTN answered with another success status, e.g. 200 OK or 201 Created
+    Completed,
+}
+
+/// # Submitted
+/// The id of an operation sent to MTN, with whether MTN only accepted it or completed it
+///
+/// It dereferences to the id, e.g. `submitted.as_str()` is the id of the operation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Submitted<T> {
+    id: T,
+    status: SubmissionStatus,
+}
+
+impl<T> Submitted<T> {
+    /// The id of an operation MTN answered with the given success status
+    pub(crate) fn new(id: T, http_status: reqwest::StatusCode) -> Submitted<T> {
+        let status = if http_status == reqwest::StatusCode::ACCEPTED {
+            SubmissionStatus::Accepted
+        } else {
+            SubmissionStatus::Completed
+        };
+        Submitted { id, status }
+    }
+
+    /// The id of the operation
+    pub fn id(&self) -> &T {
+        &self.id
+    }
+
+    /// The id of the operation, dropping its status
+    pub fn into_id(self) -> T {
+        self.id
+    }
+
+    /// Whether MTN completed the operation or only accepted it
+    pub fn status(&self) -> SubmissionStatus {
+        self.status
+    }
+
+    /// Whether the operation is still pending, MTN answered 202 Accepted
+    pub fn is_pending(&self) -> bool {
+        self.status == SubmissionStatus::Accepted
+    }
+}
+
+impl<T> Deref for Submitted<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        let accepted = Submitted::new("id".to_string(), reqwest::StatusCode::ACCEPTED);
+        assert!(accepted.is_pending());
+        assert_eq!(accepted.status(), SubmissionStatus::Accepted);
+        assert_eq!(accepted.len(), 2);
+        let created = Submitted::new("id".to_string(), reqwest::StatusCode::CREATED);
+        assert!(!created.is_pending());
+        assert_eq!(created.into_id(), "id");
+    }
+}
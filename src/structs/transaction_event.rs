@@ -0,0 +1,19 @@
+#[doc(hidden)]
+use serde::{Deserialize, Serialize};
+
+/// # TransactionEvent
+/// The fields shared by the callbacks of the transactions, see `CallbackResponse::transaction_event`
+///
+/// - 'external_id', the external id of the transaction
+/// - 'amount', the amount of the transaction, e.g. "100"
+/// - 'currency', the currency of the amount, e.g. "EUR"
+/// - 'status', as sent by MTN, e.g. SUCCESSFUL or FAILED
+/// - 'reason', the reason code of a failed transaction, e.g. PAYER_NOT_FOUND
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionEvent {
+    pub external_id: String,
+    pub amount: String,
+    pub currency: String,
+    pub status: String,
+    pub reason: Option<String>,
+}
@@ -1,6 +1,6 @@
 mod common;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "integration-tests"))]
 mod tests {
     use mtnmomo::{Currency, Momo, Party, PartyIdType, RequestToPay};
     use once_cell::sync::Lazy;
@@ -0,0 +1,103 @@
+#[cfg(all(test, feature = "callback-server"))]
+mod tests {
+    use futures_util::StreamExt;
+    use mtnmomo::{
+        CallbackServerConfig, Currency, Environment, MomoCallbackListener, MomoCollection, Party,
+        PartyIdType, RequestToPay,
+    };
+    use poem::{handler, listener::TcpAcceptor, post, Route, Server};
+    use tracing_test::traced_test;
+
+    #[handler]
+    fn mock_access_token() -> &'static str {
+        r#"{"access_token":"mock_access_token","token_type":"access_token","expires_in":3600}"#
+    }
+
+    #[handler]
+    fn mock_request_to_pay() -> poem::Response {
+        poem::Response::builder()
+            .status(poem::http::StatusCode::ACCEPTED)
+            .finish()
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_product_span_records_external_id() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let app = Route::new()
+            .at("/collection/token/", post(mock_access_token))
+            .at("/collection/v1_0/requesttopay", post(mock_request_to_pay));
+        tokio::spawn(Server::new_with_acceptor(TcpAcceptor::from_std(listener).unwrap()).run(app));
+
+        let collection = MomoCollection::new(
+            url,
+            Environment::Sandbox,
+            "mock_api_user".to_string(),
+            "mock_api_key".to_string(),
+            "mock_primary_key".to_string(),
+            "mock_secondary_key".to_string(),
+        );
+        let request = RequestToPay::new(
+            "100".to_string(),
+            Currency::EUR,
+            Party {
+                party_id_type: PartyIdType::MSISDN,
+                party_id: "256774290781".to_string(),
+            },
+            "payer_message".to_string(),
+            "payee_note".to_string(),
+        );
+        let external_id = request.external_id.clone();
+        collection.request_to_pay(request, None).await.unwrap();
+
+        assert!(logs_contain("product=\"collection\""));
+        assert!(logs_contain("operation=\"request_to_pay\""));
+        assert!(logs_contain(&format!("external_id={}", external_id)));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_callback_span_records_external_id() {
+        let config = CallbackServerConfig {
+            host: "127.0.0.1".to_string(),
+            http_port: 0,
+            ..Default::default()
+        };
+        let (addresses, updates) = MomoCallbackListener::serve_with_addresses(config)
+            .await
+            .unwrap();
+        let http_port = addresses[0].port();
+        let mut updates = std::pin::pin!(updates);
+
+        let body = r#"{"RequestToPaySuccess": {
+            "financialTransactionId": "363440463", "externalId": "callback_external_id",
+            "amount": "100", "currency": "EUR",
+            "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "payeeNote": "payee_note", "payerMessage": "payer_message", "status": "SUCCESSFULL"
+        }}"#;
+        let client = reqwest::Client::new();
+        let mut sent = false;
+        for _ in 0..50 {
+            let res = client
+                .post(format!(
+                    "http://127.0.0.1:{}/collection_request_to_pay/REQUEST_TO_PAY",
+                    http_port
+                ))
+                .body(body)
+                .send()
+                .await;
+            if res.is_ok() {
+                sent = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(sent);
+        updates.next().await.unwrap();
+
+        assert!(logs_contain("callback_type=REQUEST_TO_PAY"));
+        assert!(logs_contain("external_id=callback_external_id"));
+    }
+}
@@ -0,0 +1,133 @@
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use mtnmomo::{
+        enums::reason::RequestToPayReason, mock::MockMomoServer, Currency, Environment,
+        MomoCollection, Party, RequestToPay,
+    };
+    use poem::{
+        handler, listener::TcpAcceptor, put, web::Data, web::Json, EndpointExt, Route, Server,
+    };
+    use tokio::sync::mpsc;
+
+    fn collection(url: &str) -> MomoCollection {
+        MomoCollection::new(
+            url.to_string(),
+            Environment::Sandbox,
+            "api_user".to_string(),
+            "api_key".to_string(),
+            "primary_key".to_string(),
+            "secondary_key".to_string(),
+        )
+    }
+
+    fn request(payer: &str) -> RequestToPay {
+        RequestToPay::new(
+            "100".to_string(),
+            Currency::EUR,
+            Party::msisdn(payer),
+            "payer_message".to_string(),
+            "payee_note".to_string(),
+        )
+    }
+
+    #[handler]
+    async fn capture_callback(
+        Json(body): Json<serde_json::Value>,
+        Data(sender): Data<&mpsc::UnboundedSender<serde_json::Value>>,
+    ) {
+        sender.send(body).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_request_to_pay_end_to_end() {
+        let server = MockMomoServer::builder()
+            .with_balance("2500", Currency::XAF)
+            .start()
+            .await
+            .unwrap();
+        let collection = collection(server.url());
+
+        let transaction_id = collection
+            .request_to_pay(request("256774290781"), None)
+            .await
+            .unwrap();
+        let result = collection
+            .request_to_pay_transaction_status(transaction_id.as_str())
+            .await
+            .unwrap();
+        assert_eq!(result.status, "SUCCESSFUL");
+        assert_eq!(result.amount, "100");
+        assert_eq!(server.requests_to_pay().len(), 1);
+
+        let balance = collection.get_account_balance().await.unwrap();
+        assert_eq!(balance.available_balance, "2500");
+    }
+
+    #[tokio::test]
+    async fn test_request_to_pay_failure_reason() {
+        let server = MockMomoServer::builder()
+            .with_payer_failure("256774290782", RequestToPayReason::PAYERNOTFOUND)
+            .start()
+            .await
+            .unwrap();
+        let collection = collection(server.url());
+
+        let failed = collection
+            .request_to_pay(request("256774290782"), None)
+            .await
+            .unwrap();
+        let result = collection
+            .request_to_pay_transaction_status(failed.as_str())
+            .await
+            .unwrap();
+        assert_eq!(result.status, "FAILED");
+        assert_eq!(result.reason.as_deref(), Some("PAYER_NOT_FOUND"));
+
+        let succeeded = collection
+            .request_to_pay(request("256774290781"), None)
+            .await
+            .unwrap();
+        let result = collection
+            .request_to_pay_transaction_status(succeeded.as_str())
+            .await
+            .unwrap();
+        assert_eq!(result.status, "SUCCESSFUL");
+    }
+
+    #[tokio::test]
+    async fn test_request_to_pay_sends_callback() {
+        let server = MockMomoServer::builder()
+            .with_failure(RequestToPayReason::APPROVALREJECTED)
+            .start()
+            .await
+            .unwrap();
+        let collection = collection(server.url());
+
+        let (sender, mut receiver) = mpsc::unbounded_channel::<serde_json::Value>();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let callback_url = format!(
+            "http://{}/collection_request_to_pay/REQUEST_TO_PAY",
+            listener.local_addr().unwrap()
+        );
+        let app = Route::new()
+            .at(
+                "/collection_request_to_pay/REQUEST_TO_PAY",
+                put(capture_callback),
+            )
+            .data(sender);
+        tokio::spawn(Server::new_with_acceptor(TcpAcceptor::from_std(listener).unwrap()).run(app));
+
+        collection
+            .request_to_pay(request("256774290781"), Some(&callback_url))
+            .await
+            .unwrap();
+        let body = tokio::time::timeout(std::time::Duration::from_secs(5), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(body["status"], "FAILED");
+        assert_eq!(body["reason"]["code"], "APPROVAL_REJECTED");
+        assert_eq!(body["amount"], "100");
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(all(test, feature = "callback-server"))]
+mod tests {
+    use mtnmomo::{CallbackServerConfig, CallbackType, MomoCallbackListener};
+    use poem::{
+        get, handler, listener::TcpAcceptor, middleware::AddData, web::Data, EndpointExt, Route,
+        Server,
+    };
+
+    #[derive(Clone)]
+    struct AppName(&'static str);
+
+    #[handler]
+    fn app_name(Data(name): Data<&AppName>) -> String {
+        name.0.to_string()
+    }
+
+    #[tokio::test]
+    async fn test_routes_mounted_in_custom_app() {
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(10);
+        let routes = MomoCallbackListener::create_callback_routes_with_sender(
+            &CallbackServerConfig::default(),
+            sender,
+        )
+        .unwrap();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let app = Route::new()
+            .at("/name", get(app_name))
+            .nest("/momo", routes)
+            .with(AddData::new(AppName("my-shop")));
+        tokio::spawn(Server::new_with_acceptor(TcpAcceptor::from_std(listener).unwrap()).run(app));
+
+        let client = reqwest::Client::new();
+        let name = client.get(format!("{}/name", url)).send().await.unwrap();
+        assert_eq!(name.text().await.unwrap(), "my-shop");
+        let health = client
+            .get(format!("{}/momo/health", url))
+            .send()
+            .await
+            .unwrap();
+        assert!(health.status().is_success());
+
+        let body = r#"{"RequestToPaySuccess": {
+            "financialTransactionId": "363440463", "externalId": "custom_app",
+            "amount": "100", "currency": "EUR",
+            "payer": {"partyIdType": "MSISDN", "partyId": "256774290781"},
+            "payeeNote": "payee_note", "payerMessage": "payer_message", "status": "SUCCESSFUL"
+        }}"#;
+        let res = client
+            .put(CallbackType::RequestToPay.callback_url(&format!("{}/momo", url)))
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+
+        let update = receiver.recv().await.unwrap();
+        assert_eq!(update.update_type, CallbackType::RequestToPay);
+        assert!(update.raw_body.contains("custom_app"));
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use mtnmomo::{Environment, MomoDisbursements, MomoRemittance};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    /// Serves the token and balance endpoints of every product. The token expires
+    /// immediately so that each call has to request a fresh one.
+    fn spawn_mock() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let response_body = if request_line.contains("/token/") {
+                    r#"{"access_token":"mock_access_token","token_type":"access_token","expires_in":0}"#
+                } else {
+                    r#"{"availableBalance":"1000","currency":"EUR"}"#
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response_body.len(),
+                    response_body
+                )
+                .unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_disbursements_refreshes_expired_token() {
+        let disbursements = MomoDisbursements::new(
+            spawn_mock(),
+            Environment::Sandbox,
+            "mock_api_user".to_string(),
+            "mock_api_key".to_string(),
+            "mock_primary_key".to_string(),
+            "mock_secondary_key".to_string(),
+        );
+        for _ in 0..2 {
+            let balance = tokio::time::timeout(
+                Duration::from_secs(5),
+                disbursements.get_account_balance(),
+            )
+            .await
+            .expect("refreshing an expired token must not deadlock");
+            assert!(balance.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remittance_refreshes_expired_token() {
+        let remittance = MomoRemittance::new(
+            spawn_mock(),
+            Environment::Sandbox,
+            "mock_api_user".to_string(),
+            "mock_api_key".to_string(),
+            "mock_primary_key".to_string(),
+            "mock_secondary_key".to_string(),
+        );
+        for _ in 0..2 {
+            let balance = tokio::time::timeout(
+                Duration::from_secs(5),
+                remittance.get_account_balance(),
+            )
+            .await
+            .expect("refreshing an expired token must not deadlock");
+            assert!(balance.is_ok());
+        }
+    }
+}